@@ -1,48 +1,143 @@
-use egui::Color32;
-use egui::FontData;
-use egui::FontDefinitions;
-use egui::Stroke;
-use egui::Vec2;
-use egui::{FontFamily, FontId};
-use std::collections::BTreeMap;
-
-use egui::TextStyle::*;
-use egui::epaint::Margin;
-use std::sync::{Arc, Mutex};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::time::{Duration, Instant};
+
+use zeroize::{Zeroize, Zeroizing};
+
+use crate::directory_picker::{DirectoryBrowser, DirectoryPicker};
+use crate::file_chooser::{FileBrowser, FileChooser, FileFilter};
+use crate::fonts;
+use crate::i18n::Messages;
+use crate::input_dialog::{apply_text_styles, Feedback, InputDialog, Selection};
+use crate::list_dialog::{ListDialog, ListState, Table};
+use crate::login_dialog::{Credential, LoginDialog};
+use crate::message_box::{ButtonRole, MessageBox, MessageButton};
+use crate::progress_dialog::{ProgressDialog, ProgressUpdate};
+use crate::theme::{Appearance, Theme, ThemeSet};
+use crate::validation::{
+    code_point, existing_entry, next_available_name, split_extension, validate_folder_name,
+    zero_width_char, NameError, Normalization, MAX_NAME_BYTES,
+};
+use crate::{DialogOutcome, DialogValue, InputResponse};
+
+/// What the dialog asks for: a new folder by default, or one of the zenity compatible dialogs
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DialogKind {
+    /// Ask for a folder name and create the folder inside the parent directory
+    #[default]
+    NewFolder,
+    /// Ask for a line of text, answered with the text
+    Entry,
+    /// Ask for the path of an existing folder, answered with its absolute path
+    Directory,
+    /// Ask for a new name of a file or folder and rename it, answered with its new absolute path
+    Rename,
+    /// Show a message, answered with its buttons
+    Message(MessageKind),
+    /// Ask for a password or another secret, masked and never logged, answered with it
+    Password,
+    /// Ask for a user name and a password, answered with a [`Credential`]
+    Login,
+    /// Show the progress of a job, fed with percentages and status lines, answered once it is done
+    Progress,
+    /// Pick rows of a table, answered with their indices in the table
+    List,
+    /// Choose existing files, answered with their absolute paths
+    OpenFile,
+    /// Choose an existing folder in the file chooser, answered with its absolute path
+    OpenFolder,
+    /// Choose where a file is saved, answered with its absolute path once an existing file may
+    /// be replaced
+    SaveFile,
+}
+
+impl DialogKind {
+    /// Whether the dialog has a text field
+    pub fn has_field(self) -> bool {
+        !matches!(self, Self::Message(_))
+    }
+}
+
+/// Kind of message, which decides the icon and the default buttons of the dialog
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(clap::ValueEnum))]
+pub enum MessageKind {
+    /// Yes and No
+    Question,
+    /// OK only
+    Info,
+    /// OK only
+    Warning,
+    /// OK only
+    Error,
+}
+
+impl MessageKind {
+    /// Lowercase name, the suffix of the catalog keys with its default title and text
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Question => "question",
+            Self::Info => "info",
+            Self::Warning => "warning",
+            Self::Error => "error",
+        }
+    }
+}
 
 /// We derive Deserialize/Serialize so we can persist app state on shutdown.
 #[derive(serde::Deserialize, serde::Serialize)]
 #[serde(default)] // if we add new fields, give them default values when deserializing old state
 pub struct TemplateApp {
-    // Example stuff:
-    folder_name: String,
-    current_folder: String,
+    title: String,
     #[serde(skip)] // This is how you opt-out of serialization of a field
-    result: Arc<Mutex<String>>, // Output result
+    setup: Setup, // What the setters asked for, turned into the dialog on the first frame
     #[serde(skip)] // This is how you opt-out of serialization of a field
-    stroke_color: Color32,
-    is_focused: bool,
+    dialog: Option<Dialog>, // The dialog shown and the state of its widgets, once started
     #[serde(skip)] // This is how you opt-out of serialization of a field
-    selection_color: Color32,
+    text: String, // Question or message shown by the dialogs other than New Folder
     #[serde(skip)] // This is how you opt-out of serialization of a field
-    ok_button_color: Color32,
-
+    ok_label: Option<String>, // Default button label instead of the one of the kind
+    #[serde(skip)] // This is how you opt-out of serialization of a field
+    cancel_label: Option<String>, // Cancel button label instead of the one of the kind
+    #[serde(skip)] // This is how you opt-out of serialization of a field
+    error_message: Option<String>, // Last error, shown inline until the value is edited
+    #[serde(skip)] // This is how you opt-out of serialization of a field
+    deadline: Option<Instant>, // The dialog is dismissed when this is reached
+    #[serde(skip)] // This is how you opt-out of serialization of a field
+    outcome_sender: Option<OutcomeSender>, // Output result, taken once it is sent
+    #[serde(skip)] // This is how you opt-out of serialization of a field
+    themes: ThemeSet, // Colors, rounding, margins and font sizes of the dialog, dark and light
+    #[serde(skip)] // This is how you opt-out of serialization of a field
+    appearance: Appearance, // Which of the themes is drawn
+    #[serde(skip)] // This is how you opt-out of serialization of a field
+    appearance_applied: bool, // Whether egui was told to follow the appearance
     #[serde(skip)] // This is how you opt-out of serialization of a field
-    value: f32,
+    applied_theme: Option<Theme>, // Theme whose font sizes are installed as text styles
+    #[serde(skip)] // This is how you opt-out of serialization of a field
+    messages: Messages, // Dialog strings in the user's language
+    #[serde(skip)] // This is how you opt-out of serialization of a field
+    child: bool, // Shown in a window of another dialog, which closes it once it has the outcome
 }
 
 impl Default for TemplateApp {
     fn default() -> Self {
         Self {
-            // Example stuff:
-            folder_name: String::from("untitled folder"),
-            current_folder: String::from("current folder name"),
-            result: Arc::new(Mutex::new(String::new())), // Output result
-            value: 2.7,
-            stroke_color: Color32::TRANSPARENT,
-            is_focused: false,
-            selection_color: Color32::from_rgb(71,98,135), // macOS text selection color
-            ok_button_color: Color32::from_rgb(48, 98, 212), // macOS blue button color
+            title: String::from("New Folder"),
+            setup: Setup::default(),
+            dialog: None,
+            text: String::new(),
+            ok_label: None,
+            cancel_label: None,
+            error_message: None,
+            deadline: None,
+            outcome_sender: None, // Output result
+            themes: ThemeSet::default(),
+            appearance: Appearance::default(),
+            appearance_applied: false,
+            applied_theme: None,
+            messages: Messages::default(),
+            child: false,
         }
     }
 }
@@ -50,133 +145,22 @@ impl Default for TemplateApp {
 impl TemplateApp {
     /// Called once before the first frame.
     pub fn new(cc: &eframe::CreationContext<'_>) -> Self {
-        // Customize the fonts used by egui
-        // Reference: https://docs.rs/egui/latest/egui/struct.FontDefinitions.html
-
-        // Call the default Font Definitions
-        let mut fonts = FontDefinitions::default();
-
-        // Install my own fonts
-
-        // System Text Heavy
-        // 1st option
-        fonts.font_data.insert(
-            "system-text-heavy-1".to_owned(),
-            std::sync::Arc::new(
-                // system font data (.ttf and .otf supported)
-                // FontData::from_static(include_bytes!("/Library/Fonts/SF-Pro-Display-Bold.otf")),
-                // FontData::from_static(include_bytes!("/Library/Fonts/SF-Pro-Display-Heavy.otf")),
-                // FontData::from_static(include_bytes!("../assets/HelveticaNeueLTPro85Heavy.otf")),
-                // 1st option
-                FontData::from_static(include_bytes!("/Library/Fonts/SF-Pro-Text-Heavy.otf")),
-            ),
-        );
-        // 2nd option
-        fonts.font_data.insert(
-            "system-text-heavy-2".to_owned(),
-            std::sync::Arc::new(
-                // 2nd option
-                FontData::from_static(include_bytes!("../assets/HelveticaNeueHeavy.otf")),
-            ),
-        );
-
-        // System Text Medium
-        // 1st option
-        fonts.font_data.insert(
-            "system-text-medium-1".to_owned(),
-            std::sync::Arc::new(
-                FontData::from_static(include_bytes!("/Library/Fonts/SF-Pro-Text-Medium.otf")),
-            ),
-        );
-        // 2nd option
-
-        // System Display Medium
-        // 1st option
-        fonts.font_data.insert(
-            "system-display-medium-1".to_owned(),
-            std::sync::Arc::new(
-                FontData::from_static(include_bytes!("/Library/Fonts/SF-Pro-Display-Medium.otf")),
-            ),
-        );
-        // 2nd option
-
-        // Create a new font family
-        // Reference: https://github.com/emilk/egui/discussions/4449 | https://stackoverflow.com/questions/78069584/how-to-set-a-new-fontfamily-to-my-egui-app
-
-        // System Text Heavy
-        let mut newfam = BTreeMap::new();
-        newfam.insert(
-            FontFamily::Name("System-Text-Heavy".into()),
-            vec![
-                "system-text-heavy-1".to_owned(),
-                "system-text-heavy-2".to_owned(),
-            ],
-        );
-        fonts.families.append(&mut newfam);
-
-        // System Text Medium
-        newfam = BTreeMap::new();
-        newfam.insert(
-            FontFamily::Name("System-Text-Medium".into()),
-            vec!["system-text-medium-1".to_owned()],
-        );
-        fonts.families.append(&mut newfam);
-
-        // System Display Medium
-        newfam = BTreeMap::new();
-        newfam.insert(
-            FontFamily::Name("System-Display-Medium".into()),
-            vec!["system-display-medium-1".to_owned()],
-        );
-        fonts.families.append(&mut newfam);
-
-        // Put my font first (highest priority):
-        // fonts.families.get_mut(&FontFamily::Proportional).unwrap()
-        //     .insert(0, "system_font".to_owned());
-
-        // Put my font as last fallback for monospace:
-        // fonts.families.get_mut(&FontFamily::Monospace).unwrap()
-        //     .push("system_font".to_owned());
+        Self::new_with_fonts(cc, &[], "en")
+    }
 
+    /// Called once before the first frame, looking for the dialog fonts in `font_dirs` first
+    /// and adding a fallback face for the script of `language` (an ISO 639-1 code).
+    pub fn new_with_fonts(
+        cc: &eframe::CreationContext<'_>,
+        font_dirs: &[PathBuf],
+        language: &str,
+    ) -> Self {
+        // Customize the fonts used by egui, found at runtime with fallbacks
+        // Reference: https://docs.rs/egui/latest/egui/struct.FontDefinitions.html
         // This is also where you can customize the look and feel of egui using
         // `cc.egui_ctx.set_visuals` and `cc.egui_ctx.set_fonts`.
         // Reference: https://github.com/emilk/eframe_template/blob/main/src/app.rs | eframe-0.30.0/src/lib.rs
-        cc.egui_ctx.set_fonts(fonts);
-
-        // Redefine text_styles adding new text styles
-        // Reference: https://docs.rs/egui/latest/egui/style/struct.Style.html#structfield.text_styles
-        let text_styles: BTreeMap<_, _> = [
-            (Heading, FontId::new(30.0, FontFamily::Proportional)),
-            (Body, FontId::new(12.0, FontFamily::Proportional)),
-            (Monospace, FontId::new(14.0, FontFamily::Proportional)),
-            (Button, FontId::new(14.0, FontFamily::Proportional)),
-            (Small, FontId::new(10.0, FontFamily::Proportional)),
-            (
-                Name("DialogHeading".into()),
-                FontId::new(13.0, FontFamily::Name("System-Text-Heavy".into())),
-            ),
-            (
-                Name("DialogBody".into()),
-                FontId::new(11.0, FontFamily::Name("System-Text-Medium".into())),
-            ),
-            (
-              Name("DialogFolderName".into()),
-              FontId::new(11.5, FontFamily::Name("System-Text-Medium".into())),
-          ),
-            (
-                Name("TextInputBody".into()),
-                FontId::new(13.0, FontFamily::Name("System-Text-Medium".into())),
-            ),
-            (
-                Name("ButtonBody".into()),
-                FontId::new(13.5, FontFamily::Name("System-Display-Medium".into())),
-            )
-        ]
-        .into();
-
-        // Mutate global styles with new text styles
-        cc.egui_ctx
-            .all_styles_mut(move |style| style.text_styles = text_styles.clone());
+        fonts::install(&cc.egui_ctx, font_dirs, language);
 
         // Load previous app state (if any).
         // Note that you must enable the `persistence` feature for this to work.
@@ -187,21 +171,273 @@ impl TemplateApp {
         Default::default()
     }
 
-    /// Setter for current_folder value
-    pub fn with_current_folder(mut self, name: String) -> Self {
-        self.current_folder = name;
+    /// Setter for the directory the new folder is created in, Directory paths are relative to
+    /// and the Open and Save dialogs start in
+    pub fn with_parent_dir(mut self, path: PathBuf) -> Self {
+        self.setup.parent_dir = Some(path);
+        self
+    }
+
+    /// Setter for the name suggested in the text field, numbered if it is already taken
+    pub fn with_default_name(mut self, name: String) -> Self {
+        self.setup.default_name = Some(name);
+        self
+    }
+
+    /// Setter for what the dialog asks for
+    pub fn with_kind(mut self, kind: DialogKind) -> Self {
+        self.setup.kind = kind;
+        self
+    }
+
+    /// Rename `path`: the field starts with its name, the file stem selected, Finder-style
+    pub fn with_rename_target(mut self, path: PathBuf) -> Self {
+        self.setup.kind = DialogKind::Rename;
+        self.setup.rename_target = Some(path);
+        self
+    }
+
+    /// Setter for the text the field starts with, as typed (no numbering of taken names)
+    pub fn with_entry_text(mut self, text: String) -> Self {
+        self.setup.entry_text = Some(text);
+        self
+    }
+
+    /// Setter for an error shown under the field until it is edited, e.g. why the last answer
+    /// was refused
+    pub fn with_error(mut self, message: String) -> Self {
+        self.error_message = Some(message);
+        self
+    }
+
+    /// Setter for the question or message of the dialogs other than New Folder
+    pub fn with_text(mut self, text: String) -> Self {
+        self.text = text;
+        self
+    }
+
+    /// Setter for the default button label
+    pub fn with_ok_label(mut self, label: String) -> Self {
+        self.ok_label = Some(label);
+        self
+    }
+
+    /// Setter for the Cancel button label
+    pub fn with_cancel_label(mut self, label: String) -> Self {
+        self.cancel_label = Some(label);
+        self
+    }
+
+    /// Setter for the buttons of a message box, one to three
+    pub fn with_buttons(mut self, buttons: Vec<MessageButton>) -> Self {
+        self.setup.buttons = buttons;
+        self
+    }
+
+    /// Setter for where the Progress dialog gets its updates from
+    pub fn with_progress_updates(mut self, updates: Receiver<ProgressUpdate>) -> Self {
+        self.setup.progress.updates = Some(updates);
+        self
+    }
+
+    /// Setter for the percentage the Progress dialog starts at
+    pub fn with_percentage(mut self, percentage: f32) -> Self {
+        self.setup.progress.percentage = percentage.clamp(0.0, 100.0);
+        self.setup.progress.done = self.setup.progress.percentage >= 100.0;
+        self
+    }
+
+    /// Setter for whether the Progress dialog pulsates, for jobs of unknown length
+    pub fn with_pulsate(mut self, pulsate: bool) -> Self {
+        self.setup.progress.pulsate = pulsate;
+        self
+    }
+
+    /// Setter for whether the Progress dialog closes by itself once the job is done
+    pub fn with_auto_close(mut self, auto_close: bool) -> Self {
+        self.setup.progress.auto_close = auto_close;
+        self
+    }
+
+    /// Setter for whether the Progress dialog has a Cancel button
+    pub fn with_cancellable(mut self, cancellable: bool) -> Self {
+        self.setup.progress.cancellable = cancellable;
+        self
+    }
+
+    /// Setter for the rows the List dialog picks from
+    pub fn with_table(mut self, table: Table) -> Self {
+        self.setup.table = table;
         self
     }
 
-    /// Setter for the result variable
-    pub fn with_result(mut self, result: Arc<Mutex<String>>) -> Self {
-        self.result = result;
+    /// Setter for whether the List dialog picks several rows instead of one, or the Open dialog
+    /// several files
+    pub fn with_multiple(mut self, multiple: bool) -> Self {
+        self.setup.multiple = multiple;
         self
     }
 
-    /// Getter for folder_name value
-    pub fn get_result(&self) -> String {
-        self.folder_name.clone()
+    /// Setter for where the Open and Save dialogs start: a folder, or a file chosen in its folder
+    pub fn with_start_path(mut self, path: PathBuf) -> Self {
+        self.setup.start_path = Some(path);
+        self
+    }
+
+    /// Setter for the filters the Open and Save dialogs offer, the first one applying
+    pub fn with_file_filters(mut self, filters: Vec<FileFilter>) -> Self {
+        self.setup.file_filters = filters;
+        self
+    }
+
+    /// Setter for the dialog heading
+    pub fn with_title(mut self, title: String) -> Self {
+        self.title = title;
+        self
+    }
+
+    /// Setter for the Unicode normalization form of created and renamed names
+    pub fn with_normalization(mut self, normalization: Normalization) -> Self {
+        self.setup.normalization = normalization;
+        self
+    }
+
+    /// Setter for the catalog the dialog strings are taken from
+    pub fn with_messages(mut self, messages: Messages) -> Self {
+        self.messages = messages;
+        self
+    }
+
+    /// Setter for the dark and light themes the dialog is drawn with
+    pub fn with_themes(mut self, themes: ThemeSet) -> Self {
+        self.themes = themes;
+        self
+    }
+
+    /// Setter for whether the dark or light theme is drawn, or the system decides
+    pub fn with_appearance(mut self, appearance: Appearance) -> Self {
+        self.appearance = appearance;
+        self.appearance_applied = false;
+        self
+    }
+
+    /// Setter for the time after which the dialog gives up waiting for an answer
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.deadline = Some(Instant::now() + timeout);
+        self
+    }
+
+    /// Setter for the channel the dialog outcome is sent to, as the type the dialog answers
+    /// with: a String, a [`Credential`] for the Login dialog, row indices for the List dialog or
+    /// paths for the Open and Save dialogs
+    pub fn with_outcome_sender<T>(mut self, sender: Sender<DialogOutcome<T>>) -> Self
+    where
+        T: TryFrom<DialogValue> + 'static,
+    {
+        self.outcome_sender = Some(Box::new(move |outcome: DialogOutcome<DialogValue>| {
+            let outcome = outcome.and_then(|value| match T::try_from(value) {
+                Ok(value) => DialogOutcome::Confirmed(value),
+                Err(_) => DialogOutcome::Error("The dialog answered with another type".to_owned()),
+            });
+            // The receiver may be gone already, there is nobody left to tell then
+            sender.send(outcome).ok();
+        }));
+        self
+    }
+
+    /// Label of the default button: Create, Yes or OK unless overridden
+    fn default_label(&self, kind: DialogKind) -> String {
+        let key = match kind {
+            DialogKind::NewFolder => "create",
+            DialogKind::Rename => "rename",
+            DialogKind::OpenFile | DialogKind::OpenFolder => "open",
+            DialogKind::SaveFile => "save",
+            DialogKind::Message(MessageKind::Question) => "yes",
+            _ => "ok",
+        };
+        self.ok_label
+            .clone()
+            .unwrap_or_else(|| self.messages.get(key).to_owned())
+    }
+
+    /// Label of the Cancel button, None for messages that can only be acknowledged
+    fn cancel_label(&self, kind: DialogKind) -> Option<String> {
+        let key = match kind {
+            DialogKind::Message(MessageKind::Question) => "no",
+            DialogKind::Message(_) => return None,
+            _ => "cancel",
+        };
+        Some(
+            self.cancel_label
+                .clone()
+                .unwrap_or_else(|| self.messages.get(key).to_owned()),
+        )
+    }
+
+    /// Send the outcome (only the first one counts) and close the window
+    fn finish(&mut self, ctx: &egui::Context, outcome: DialogOutcome<DialogValue>) {
+        if let Some(send) = self.outcome_sender.take() {
+            send(outcome);
+        }
+        // The secret now belongs to the receiver, only its copy remains
+        if let Some(dialog) = &mut self.dialog {
+            dialog.forget(ctx);
+        }
+        if !self.child {
+            ctx.send_viewport_cmd(egui::ViewportCommand::Close);
+        }
+    }
+
+    /// Lay the dialog out in the window `ctx` draws
+    fn show(&mut self, ctx: &egui::Context) {
+        let kind = self
+            .dialog
+            .get_or_insert_with(|| Dialog::start(std::mem::take(&mut self.setup)))
+            .kind();
+
+        // Give up once the timeout is reached, otherwise make sure we wake up for it
+        if let Some(deadline) = self.deadline {
+            let now = Instant::now();
+            if now >= deadline {
+                self.finish(ctx, DialogOutcome::TimedOut);
+            } else {
+                ctx.request_repaint_after(deadline - now);
+            }
+        }
+
+        // egui resolves the appearance against the system preference, which may change live
+        if !self.appearance_applied {
+            ctx.set_theme(self.appearance.theme_preference());
+            self.appearance_applied = true;
+        }
+        let theme = self
+            .themes
+            .for_dark_mode(ctx.theme() == egui::Theme::Dark)
+            .clone();
+
+        // Install the theme font sizes as text styles, again whenever the theme switches
+        if self.applied_theme.as_ref() != Some(&theme) {
+            apply_text_styles(ctx, &theme);
+            self.applied_theme = Some(theme.clone());
+        }
+
+        let mut chrome = Chrome {
+            ok_label: self.default_label(kind),
+            cancel_label: self.cancel_label(kind),
+            title: &self.title,
+            text: &self.text,
+            error_message: &mut self.error_message,
+            messages: &self.messages,
+            themes: &self.themes,
+            appearance: self.appearance,
+        };
+        let outcome = self
+            .dialog
+            .as_mut()
+            .and_then(|dialog| dialog.show(ctx, &theme, &mut chrome));
+        if let Some(outcome) = outcome {
+            self.finish(ctx, outcome);
+        }
     }
 }
 
@@ -215,330 +451,1002 @@ impl eframe::App for TemplateApp {
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
         // Put your widgets into a `SidePanel`, `TopBottomPanel`, `CentralPanel`, `Window` or `Area`.
         // For inspiration and more examples, go to https://emilk.github.io/egui
+        self.show(ctx);
+    }
+
+    fn clear_color(&self, visuals: &egui::Visuals) -> [f32; 4] {
+        // visuals.window_fill().to_normalized_gamma_f32()
+        // egui::Color32::from_gray(27).to_normalized_gamma_f32()
+        self.themes
+            .for_dark_mode(visuals.dark_mode)
+            .colors
+            .background
+            .to_normalized_gamma_f32()
+    }
+
+    // Reference: https://qiita.com/8bitTD/items/7d745bbf90a82aaffd7f
+    fn on_exit(&mut self, _: Option<&eframe::glow::Context>) {
+        // Closed without Create or Cancel (e.g. the title-bar close button)
+        if let Some(send) = self.outcome_sender.take() {
+            send(DialogOutcome::Dismissed);
+        }
+    }
+}
+
+/// Hands the outcome over to whoever opened the dialog, as the type they expect
+type OutcomeSender = Box<dyn FnOnce(DialogOutcome<DialogValue>)>;
+
+/// What the setters asked for. The dialog is built from it on the first frame, so the setters
+/// can be called in any order.
+#[derive(Default)]
+struct Setup {
+    kind: DialogKind,
+    parent_dir: Option<PathBuf>,
+    default_name: Option<String>,
+    entry_text: Option<String>,
+    rename_target: Option<PathBuf>,
+    normalization: Normalization,
+    buttons: Vec<MessageButton>,
+    progress: Progress,
+    table: Table,
+    multiple: bool,
+    start_path: Option<PathBuf>,
+    file_filters: Vec<FileFilter>,
+}
+
+/// What every dialog takes from the app: heading, text, button labels, strings and the last error
+struct Chrome<'a> {
+    title: &'a str,
+    text: &'a str,
+    ok_label: String,
+    /// None for messages that can only be acknowledged
+    cancel_label: Option<String>,
+    error_message: &'a mut Option<String>,
+    messages: &'a Messages,
+    /// For the New Folder dialog the Open and Save dialogs open
+    themes: &'a ThemeSet,
+    appearance: Appearance,
+}
+
+impl Chrome<'_> {
+    /// The last error as feedback under the field
+    fn feedback(&self) -> Feedback {
+        self.error_message
+            .clone()
+            .map_or(Feedback::None, Feedback::Failed)
+    }
+
+    /// The outcome of a dialog answered with `response`: Cancel, or the value `confirm` gives for
+    /// the default button; a failed confirmation is shown inline and leaves the dialog open
+    fn answer<V: Into<DialogValue>>(
+        &mut self,
+        response: &InputResponse,
+        confirm: impl FnOnce(&Messages) -> Result<V, String>,
+    ) -> Option<DialogOutcome<DialogValue>> {
+        // Editing the value dismisses the last error
+        if response.changed {
+            *self.error_message = None;
+        }
+        if response.cancelled {
+            return Some(DialogOutcome::Cancelled);
+        }
+        if !response.confirmed {
+            return None;
+        }
+        match confirm(self.messages) {
+            Ok(value) => {
+                *self.error_message = None;
+                Some(DialogOutcome::Confirmed(value.into()))
+            }
+            Err(message) => {
+                *self.error_message = Some(message);
+                None
+            }
+        }
+    }
+}
 
-        // Change CentralPanel default style with custom frame 
-        // Reference: https://github.com/emilk/egui/discussions/1286
-        let dialog_frame = egui::containers::Frame {
-            inner_margin: Margin {
-                left: 0.,
-                right: 0.,
-                top: 0.,
-                bottom: 0.,
-            },
-            outer_margin: Margin {
-                left: 20.5-3.5,
-                right: 20.5-3.5,
-                top: 19.5,
-                bottom: 20.,
-            },
-            rounding: egui::Rounding {
-                nw: 1.0,
-                ne: 1.0,
-                sw: 1.0,
-                se: 1.0,
-            },
-            shadow: eframe::epaint::Shadow::NONE,
-            fill: Color32::TRANSPARENT,
-            stroke: egui::Stroke::new(0.0, Color32::LIGHT_GRAY),
-            ..Default::default()
+/// The dialog shown, with the state of its widgets
+enum Dialog {
+    NewFolder(NewFolder),
+    Rename(Rename),
+    Entry(String),
+    Directory(Directory),
+    /// Wiped once it is delivered
+    Password(Zeroizing<String>),
+    Login(Credential),
+    Message(Message),
+    Progress(Progress),
+    List(List),
+    Files(Files),
+}
+
+impl Dialog {
+    /// The dialog `setup` asks for, in its initial state
+    fn start(setup: Setup) -> Self {
+        let parent_dir = setup.parent_dir.unwrap_or_else(|| PathBuf::from("."));
+        let entry_text = setup.entry_text.unwrap_or_default();
+        match setup.kind {
+            DialogKind::NewFolder => {
+                // A suggested name is numbered Finder-style ("untitled folder 2", …) if it is taken
+                let name = match setup.default_name {
+                    Some(name) => next_available_name(&parent_dir, &name),
+                    None if !entry_text.is_empty() => entry_text,
+                    None => next_available_name(&parent_dir, "untitled folder"),
+                };
+                Self::NewFolder(NewFolder {
+                    browser: DirectoryBrowser::new(&parent_dir),
+                    naming: Naming::new(parent_dir, name, None, setup.normalization),
+                })
+            }
+            DialogKind::Rename => {
+                let path = setup.rename_target.unwrap_or_default();
+                let name = path
+                    .file_name()
+                    .map(|name| name.to_string_lossy().into_owned())
+                    .unwrap_or_default();
+                let parent_dir = match path.parent() {
+                    Some(parent) if !parent.as_os_str().is_empty() => parent.to_owned(),
+                    _ => PathBuf::from("."),
+                };
+                Self::Rename(Rename {
+                    naming: Naming::new(parent_dir, name.clone(), Some(name), setup.normalization),
+                    extension_change: None,
+                })
+            }
+            DialogKind::Entry => Self::Entry(entry_text),
+            DialogKind::Directory => Self::Directory(Directory {
+                path: entry_text,
+                parent_dir,
+            }),
+            DialogKind::Password => Self::Password(Zeroizing::default()),
+            DialogKind::Login => Self::Login(Credential {
+                username: entry_text,
+                password: Zeroizing::default(),
+            }),
+            DialogKind::Message(kind) => Self::Message(Message {
+                kind,
+                buttons: setup.buttons,
+            }),
+            DialogKind::Progress => Self::Progress(setup.progress),
+            DialogKind::List => Self::List(List {
+                table: setup.table,
+                state: ListState::default(),
+                multiple: setup.multiple,
+            }),
+            DialogKind::OpenFile | DialogKind::OpenFolder | DialogKind::SaveFile => {
+                let mut browser = FileBrowser::new(&setup.start_path.unwrap_or(parent_dir));
+                browser.set_filters(setup.file_filters);
+                browser.set_folders_only(setup.kind == DialogKind::OpenFolder);
+                Self::Files(Files {
+                    browser,
+                    save: setup.kind == DialogKind::SaveFile,
+                    multiple: setup.multiple,
+                    normalization: setup.normalization,
+                    overwrite: None,
+                    new_folder: None,
+                })
+            }
+        }
+    }
+
+    /// What the dialog asks for
+    fn kind(&self) -> DialogKind {
+        match self {
+            Self::NewFolder(_) => DialogKind::NewFolder,
+            Self::Rename(_) => DialogKind::Rename,
+            Self::Entry(_) => DialogKind::Entry,
+            Self::Directory(_) => DialogKind::Directory,
+            Self::Password(_) => DialogKind::Password,
+            Self::Login(_) => DialogKind::Login,
+            Self::Message(message) => DialogKind::Message(message.kind),
+            Self::Progress(_) => DialogKind::Progress,
+            Self::List(_) => DialogKind::List,
+            Self::Files(files) if files.save => DialogKind::SaveFile,
+            Self::Files(files) if files.browser.folders_only() => DialogKind::OpenFolder,
+            Self::Files(_) => DialogKind::OpenFile,
+        }
+    }
+
+    /// Lay the dialog out and tell how it ended, if it did
+    fn show(
+        &mut self,
+        ctx: &egui::Context,
+        theme: &Theme,
+        chrome: &mut Chrome<'_>,
+    ) -> Option<DialogOutcome<DialogValue>> {
+        match self {
+            Self::NewFolder(new_folder) => new_folder.show(ctx, theme, chrome),
+            Self::Rename(rename) => rename.show(ctx, theme, chrome),
+            Self::Entry(text) => {
+                let response = InputDialog::new(chrome.title, text)
+                    .with_body(chrome.text)
+                    .with_ok_label(chrome.ok_label.clone())
+                    .with_cancel_label(chrome.cancel_label.clone())
+                    .with_feedback(chrome.feedback())
+                    .show(ctx, theme, chrome.messages);
+                chrome.answer(&response, |_| Ok(text.clone()))
+            }
+            Self::Directory(directory) => {
+                let response = InputDialog::new(chrome.title, &mut directory.path)
+                    .with_body(chrome.text)
+                    .with_ok_label(chrome.ok_label.clone())
+                    .with_cancel_label(chrome.cancel_label.clone())
+                    .with_feedback(chrome.feedback())
+                    .show(ctx, theme, chrome.messages);
+                chrome.answer(&response, |messages| directory.resolve(messages))
+            }
+            Self::Password(secret) => {
+                // A secret is edited in its own buffer, which is wiped and never serialized
+                let response = InputDialog::new(chrome.title, &mut *secret)
+                    .with_body(chrome.text)
+                    .with_ok_label(chrome.ok_label.clone())
+                    .with_cancel_label(chrome.cancel_label.clone())
+                    .with_feedback(chrome.feedback())
+                    .with_selection(Selection::End)
+                    .secret()
+                    .show(ctx, theme, chrome.messages);
+                chrome.answer(&response, |_| Ok(secret.to_string()))
+            }
+            Self::Login(login) => {
+                let response =
+                    LoginDialog::new(chrome.title, &mut login.username, &mut login.password)
+                        .with_body(chrome.text)
+                        .with_field_labels(
+                            chrome.messages.get("username_label"),
+                            chrome.messages.get("password_label"),
+                        )
+                        .with_ok_label(chrome.ok_label.clone())
+                        .with_cancel_label(chrome.cancel_label.clone().unwrap_or_default())
+                        .with_feedback(chrome.feedback())
+                        .show(ctx, theme, chrome.messages);
+                chrome.answer(&response, |_| Ok(login.clone()))
+            }
+            Self::Message(message) => message.show(ctx, theme, chrome),
+            Self::Progress(progress) => progress.show(ctx, theme, chrome),
+            Self::List(list) => {
+                let response = ListDialog::new(chrome.title, &list.table, &mut list.state)
+                    .with_body(chrome.text)
+                    .with_multiple(list.multiple)
+                    .with_placeholder(chrome.messages.get("search_placeholder"))
+                    .with_empty_text(chrome.messages.get("list_empty"))
+                    .with_ok_label(chrome.ok_label.clone())
+                    .with_cancel_label(chrome.cancel_label.clone().unwrap_or_default())
+                    .show(ctx, theme, chrome.messages);
+                chrome.answer(&response, |_| {
+                    Ok(list.state.selected.iter().copied().collect::<Vec<_>>())
+                })
+            }
+            Self::Files(files) => files.show(ctx, theme, chrome),
+        }
+    }
+
+    /// Wipe the secret and what egui remembers of it, once it was delivered
+    fn forget(&mut self, ctx: &egui::Context) {
+        match self {
+            Self::Password(secret) => {
+                secret.zeroize();
+                InputDialog::forget_field(ctx);
+            }
+            Self::Login(login) => {
+                login.password.zeroize();
+                LoginDialog::forget_fields(ctx);
+            }
+            _ => {}
+        }
+    }
+}
+
+/// A name typed for an entry of a folder, checked on every edit: New Folder and Rename
+struct Naming {
+    name: String,
+    parent_dir: PathBuf,           // Directory the entry is created or renamed in
+    current_folder: String,        // Displayed name of parent_dir
+    original_name: Option<String>, // Entry being renamed, which doesn't count as taken
+    original_is_folder: bool,      // Whether that entry is a folder, to name it if it goes away
+    normalization: Normalization,  // Unicode form the entry is created or renamed with
+    name_error: Option<NameError>, // Why the name can't be used
+    existing_entry: Option<String>, // Entry of parent_dir already using the name
+}
+
+impl Naming {
+    fn new(
+        parent_dir: PathBuf,
+        name: String,
+        original_name: Option<String>,
+        normalization: Normalization,
+    ) -> Self {
+        let original_is_folder = original_name
+            .as_ref()
+            .is_some_and(|original_name| parent_dir.join(original_name).is_dir());
+        let mut naming = Self {
+            name,
+            parent_dir: PathBuf::new(),
+            current_folder: String::new(),
+            original_name,
+            original_is_folder,
+            normalization,
+            name_error: None,
+            existing_entry: None,
+        };
+        naming.set_parent_dir(parent_dir);
+        naming
+    }
+
+    /// Change the directory the entry goes in, and its displayed name
+    fn set_parent_dir(&mut self, path: PathBuf) {
+        self.current_folder = match path.file_name() {
+            Some(name) => name.to_string_lossy().into_owned(),
+            None => path.display().to_string(), // e.g. "/" has no file name
+        };
+        self.parent_dir = path;
+        self.check();
+    }
+
+    /// Validate the name and look for an entry of parent_dir that already uses it
+    fn check(&mut self) {
+        let name = self.stored_name();
+        self.name_error = validate_folder_name(&name).err();
+        // The renamed entry itself doesn't count, e.g. changing only the case of its name
+        self.existing_entry = existing_entry(&self.parent_dir, &name)
+            .filter(|entry| Some(entry) != self.original_name.as_ref());
+    }
+
+    /// Number the name Finder-style ("untitled folder 2", …) if it is already taken
+    fn suggest_unique_name(&mut self) {
+        self.name = next_available_name(&self.parent_dir, &self.name);
+        self.check();
+    }
+
+    /// The name as it is written to the file system, in the normalization form
+    fn stored_name(&self) -> String {
+        self.normalization.apply(&self.name)
+    }
+
+    /// What the dialog shows under the field: why the name can't be used, the last error, the
+    /// entry it collides with, or an invisible character making it look like another
+    fn feedback(&self, chrome: &Chrome<'_>) -> Feedback {
+        let messages = chrome.messages;
+        if let Some(name_error) = self.name_error {
+            Feedback::Invalid(messages.name_error(name_error))
+        } else if let Some(error_message) = chrome.error_message.as_ref() {
+            Feedback::Failed(error_message.clone())
+        } else if let Some(existing_entry) = &self.existing_entry {
+            let message = messages.format(
+                "already_exists",
+                &[("name", &messages.quote(existing_entry))],
+            );
+            // Numbering suits new folders, a renamed file would become "report.pdf 2"
+            if self.original_name.is_some() {
+                return Feedback::Invalid(message);
+            }
+            Feedback::Conflict {
+                message,
+                action: messages.get("use_next_name").to_owned(),
+            }
+        } else if let Some(c) = zero_width_char(&self.name) {
+            Feedback::Warning(
+                messages.format("warning_zero_width", &[("character", &code_point(c))]),
+            )
+        } else {
+            Feedback::None
+        }
+    }
+
+    /// The field with the name, its byte count and feedback
+    fn input_dialog<'a>(
+        &'a mut self,
+        chrome: &Chrome<'_>,
+        body: &str,
+        context: &str,
+    ) -> InputDialog<'a> {
+        let feedback = self.feedback(chrome);
+        let normalization = self.normalization;
+        let messages = chrome.messages;
+        let context = messages.format("folder_label", &[("folder", &messages.quote(context))]);
+        InputDialog::new(chrome.title, &mut self.name)
+            .with_body(messages.get(body))
+            .with_context(context)
+            .with_ok_label(chrome.ok_label.clone())
+            .with_cancel_label(chrome.cancel_label.clone())
+            .with_feedback(feedback)
+            // The limit applies to the stored bytes, which NFD makes longer for accented names
+            .with_byte_counter(MAX_NAME_BYTES, move |name| normalization.apply(name).len())
+    }
+
+    /// Create the folder inside parent_dir, returning its absolute path
+    fn create(&self) -> io::Result<PathBuf> {
+        let path = self.parent_dir.join(self.stored_name());
+        std::fs::create_dir(&path)?;
+        std::fs::canonicalize(&path)
+    }
+
+    /// Rename the original entry of parent_dir to the name, returning its new absolute path
+    fn rename(&self, messages: &Messages) -> Result<String, String> {
+        let original_name = self.original_name.as_deref().unwrap_or_default();
+        let parent_dir = std::fs::canonicalize(&self.parent_dir)
+            .map_err(|error| self.describe_error(&error, messages))?;
+        let name = self.stored_name();
+        let target = parent_dir.join(&name);
+        if name != original_name {
+            // fs::rename replaces an existing file, check again in case one appeared meanwhile
+            if let Some(entry) = existing_entry(&parent_dir, &name) {
+                if entry != original_name {
+                    return Err(
+                        messages.format("error_taken", &[("name", &messages.quote(&entry))])
+                    );
+                }
+            }
+            std::fs::rename(parent_dir.join(original_name), &target)
+                .map_err(|error| self.describe_error(&error, messages))?;
+            log::info!("Renamed {} to {}", original_name, name);
+        }
+        Ok(target.display().to_string())
+    }
+
+    /// Human readable message for a failed creation or rename
+    fn describe_error(&self, error: &io::Error, messages: &Messages) -> String {
+        match (error.kind(), &self.original_name) {
+            (io::ErrorKind::AlreadyExists, _) => {
+                messages.format("error_taken", &[("name", &messages.quote(&self.name))])
+            }
+            (io::ErrorKind::PermissionDenied, _) => messages.get("error_permission").to_owned(),
+            // The renamed entry is gone, unless the whole parent_dir is
+            (io::ErrorKind::NotFound, Some(original_name)) if self.parent_dir.is_dir() => {
+                let key = if self.original_is_folder {
+                    "error_not_found"
+                } else {
+                    "error_file_not_found"
+                };
+                messages.format(key, &[("name", &messages.quote(original_name))])
+            }
+            (io::ErrorKind::NotFound, _) => messages.format(
+                "error_not_found",
+                &[("name", &messages.quote(&self.current_folder))],
+            ),
+            (_, Some(_)) => messages.format("error_rename", &[("error", &error.to_string())]),
+            (_, None) => messages.format("error_other", &[("error", &error.to_string())]),
+        }
+    }
+}
+
+/// State of the New Folder dialog
+struct NewFolder {
+    naming: Naming,
+    browser: DirectoryBrowser, // Where section, to choose another parent_dir
+}
+
+impl NewFolder {
+    fn show(
+        &mut self,
+        ctx: &egui::Context,
+        theme: &Theme,
+        chrome: &mut Chrome<'_>,
+    ) -> Option<DialogOutcome<DialogValue>> {
+        let messages = chrome.messages;
+        let current_folder = self.naming.current_folder.clone();
+        let browser = &mut self.browser;
+        let mut parent_changed = false;
+        let response = self
+            .naming
+            .input_dialog(chrome, "body", &current_folder)
+            .with_section(|ui| {
+                parent_changed = DirectoryPicker::new(browser).show(ui, theme, messages)
+            })
+            .show(ctx, theme, messages);
+
+        // A folder chosen in the Where section becomes the parent, the name is checked there
+        if parent_changed {
+            self.naming
+                .set_parent_dir(self.browser.selected().to_owned());
+            *chrome.error_message = None;
+        }
+        // Validate on every edit, which also dismisses the last creation error
+        if response.changed {
+            self.naming.check();
+        }
+        if response.action_clicked {
+            self.naming.suggest_unique_name();
+        }
+        let naming = &self.naming;
+        chrome.answer(&response, |messages| match naming.create() {
+            Ok(path) => {
+                log::info!("Folder created: {}", path.display());
+                Ok(path.display().to_string())
+            }
+            Err(error) => Err(naming.describe_error(&error, messages)),
+        })
+    }
+}
+
+/// State of the Rename dialog
+struct Rename {
+    naming: Naming,
+    extension_change: Option<ExtensionChange>, // Extension change waiting for confirmation
+}
+
+/// An extension edit of the Rename dialog, each side None when the name has no extension
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct ExtensionChange {
+    from: Option<String>,
+    to: Option<String>,
+}
+
+impl ExtensionChange {
+    /// The change renaming `original_name` to `name` makes to its extension, if any
+    fn between(original_name: &str, name: &str) -> Option<Self> {
+        let from = split_extension(original_name).1;
+        let to = split_extension(name).1;
+        (from != to).then(|| Self {
+            from: from.map(str::to_owned),
+            to: to.map(str::to_owned),
+        })
+    }
+}
+
+impl Rename {
+    fn show(
+        &mut self,
+        ctx: &egui::Context,
+        theme: &Theme,
+        chrome: &mut Chrome<'_>,
+    ) -> Option<DialogOutcome<DialogValue>> {
+        // A changed extension is confirmed in place of the field, Finder-style
+        if let Some(change) = self.extension_change.clone() {
+            return self.show_extension_change(ctx, theme, chrome, &change);
+        }
+
+        let original_name = self.naming.original_name.clone().unwrap_or_default();
+        let response = self
+            .naming
+            .input_dialog(chrome, "body_rename", &original_name)
+            .with_selection(Selection::stem(&original_name))
+            .show(ctx, theme, chrome.messages);
+        if response.changed {
+            self.naming.check();
+        }
+        if response.action_clicked {
+            self.naming.suggest_unique_name();
+        }
+        if response.confirmed {
+            // A different extension is confirmed first, the rename happens once it is accepted
+            self.extension_change = ExtensionChange::between(&original_name, &self.naming.name);
+            if self.extension_change.is_some() {
+                return None;
+            }
+        }
+        let naming = &self.naming;
+        chrome.answer(&response, |messages| naming.rename(messages))
+    }
+
+    /// Ask whether the extension really changes: the default button renames, Cancel keeps it
+    fn show_extension_change(
+        &mut self,
+        ctx: &egui::Context,
+        theme: &Theme,
+        chrome: &mut Chrome<'_>,
+        change: &ExtensionChange,
+    ) -> Option<DialogOutcome<DialogValue>> {
+        let messages = chrome.messages;
+        let quote =
+            |extension: &Option<String>| messages.quote(extension.as_deref().unwrap_or_default());
+        let extension = |key: &str, extension: &Option<String>| {
+            messages.format(
+                key,
+                &[("extension", extension.as_deref().unwrap_or_default())],
+            )
         };
-        let text_box_frame = egui::containers::Frame {
-            inner_margin: Margin {
-                left: 0.,
-                right: 0.,
-                top: 0.,
-                bottom: 0.,
-            },
-            outer_margin: Margin {
-                left: 0.2,
-                right: 0.2,
-                top: 0.2,
-                bottom: 0.2,
-            },
-            rounding: egui::Rounding {
-                nw: 0.0,
-                ne: 0.0,
-                sw: 0.0,
-                se: 0.0,
-            },
-            shadow: eframe::epaint::Shadow::NONE,
-            fill: Color32::from_rgb(44, 43, 40),
-            stroke: egui::Stroke::new(0.15, Color32::from_rgb(83, 82,82)),
-            ..Default::default()
+        let (heading, use_label, keep_label) = match (&change.from, &change.to) {
+            (Some(_), Some(_)) => (
+                messages.format(
+                    "extension_change",
+                    &[("from", &quote(&change.from)), ("to", &quote(&change.to))],
+                ),
+                extension("use_extension", &change.to),
+                extension("keep_extension", &change.from),
+            ),
+            (Some(_), None) => (
+                messages.format("extension_remove", &[("from", &quote(&change.from))]),
+                messages.get("remove_extension").to_owned(),
+                extension("keep_extension", &change.from),
+            ),
+            _ => (
+                messages.format("extension_add", &[("to", &quote(&change.to))]),
+                messages.get("add_extension").to_owned(),
+                messages.get("dont_add_extension").to_owned(),
+            ),
         };
-        let text_box_stroke = egui::containers::Frame {
-            inner_margin: Margin {
-                left: 3.5,
-                right: 3.5,
-                top: 3.5,
-                bottom: 3.5,
-            },
-            outer_margin: Margin {
-                left: 0.,
-                right: 0.,
-                top: 0.,
-                bottom: 0.,
-            },
-            rounding: egui::Rounding {
-                nw: 3.,
-                ne: 3.,
-                sw: 3.,
-                se: 3.,
-            },
-            shadow: eframe::epaint::Shadow::NONE,
-            fill: self.stroke_color, // fill: Color32::from_rgb(56, 100, 138),
-            stroke: egui::Stroke::new(0.0, Color32::WHITE),
-            ..Default::default()
+
+        let mut no_value = String::new();
+        let response = InputDialog::new(heading, &mut no_value)
+            .with_body(messages.get("extension_warning"))
+            .with_ok_label(use_label)
+            .with_cancel_label(Some(keep_label))
+            .without_field()
+            .show(ctx, theme, messages);
+        if response.confirmed || response.cancelled {
+            InputDialog::focus_field(ctx);
+            self.extension_change = None;
+        }
+        if response.cancelled {
+            self.keep_extension(change);
+            *chrome.error_message = None;
+            return None;
+        }
+        // Back to the field if the rename fails, the window closes otherwise
+        let naming = &self.naming;
+        chrome.answer(&response, |messages| naming.rename(messages))
+    }
+
+    /// Undo the extension edit that was not confirmed, keeping the rest of the new name
+    fn keep_extension(&mut self, change: &ExtensionChange) {
+        let stem = match change.to {
+            Some(_) => split_extension(&self.naming.name).0,
+            None => self.naming.name.as_str(),
         };
-        // Store a reference to our text edit output for later use
-        // let mut text_edit_output = None;
-        egui::CentralPanel::default()
-            .frame(dialog_frame)
-            .show(ctx, |ui| {
-                // egui::CentralPanel::default().show(ctx, |ui| {
-
-                ui.horizontal(|ui| {
-                    ui.add_space(3.5);
-                    // Reference: https://github.com/emilk/egui/discussions/3933 | https://docs.rs/egui/0.30.0/egui/struct.Ui.html#method.set_row_height | https://docs.rs/egui/0.30.0/egui/struct.Ui.html#method.horizontal
-                    // ui.set_row_height(30.0);
-                    // ui.style_mut().spacing.interact_size.y = 30.0; // horizontal layout and the spacing.interact_size.y modifies the height of the first row of layout and button widgets so NOT USEFUL
-                    // ui.horizontal(|ui| {
-
-                    // Reference: egui-0.30.0/src/ui.rs
-                    ui.allocate_ui_with_layout([f32::INFINITY, 16.].into(), egui::Layout::left_to_right(egui::Align::BOTTOM), |ui| {
-                        ui.add(
-                            egui::Label::new(
-                                egui::RichText::new("New Folder".to_owned())
-                                    .color(Color32::from_rgb(221, 221, 221))
-                                    .text_style(Name("DialogHeading".into()))
-                                    ,)
-                                .selectable(false)
-                          );
-                    });
-                });
-
-                // ui.label(
-                //     egui::RichText::new("I am Proportional")
-                //         .color(egui::Color32::YELLOW)
-                //         .font(FontId {
-                //             size: 12.0,
-                //             family: FontFamily::Proportional,
-                //         }),
-                // );
-                // ui.label(
-                //     egui::RichText::new("I am Monospace")
-                //         .color(egui::Color32::GREEN)
-                //         .font(FontId {
-                //             size: 12.0,
-                //             family: FontFamily::Monospace,
-                //         }),
-                // );
-                ui.horizontal(|ui| {
-                    ui.add_space(3.5);
-                    ui.allocate_ui_with_layout([f32::INFINITY, 35.].into(), egui::Layout::bottom_up(egui::Align::LEFT), |ui| {
-                        ui.add(
-                            egui::Label::new(
-                                egui::RichText::new("“".to_owned() + &self.current_folder + "”:")
-                                    .color(Color32::from_rgb(221, 221, 221))
-                                    .text_style(Name("DialogFolderName".into()))
-                                    ,)
-                                .selectable(false)
-                        );
-                        ui.add(
-                            egui::Label::new(
-                                egui::RichText::new("Name of new folder inside")
-                                    .color(Color32::from_rgb(221, 221, 221))
-                                    .text_style(Name("DialogBody".into()))
-                                    .line_height(Some(11.)) // Create space below the text
-                                    ,)
-                                .selectable(false)
-                        );
-                    });
-                });
-                ui.add_space(4.5);
-                // ui.add(
-                //     egui::TextEdit::singleline(&mut self.folder_name)
-                //         // .hint_text("Type something...") // Placeholder text
-                //         .desired_width(f32::INFINITY) // Make it take full width
-                //         .font(Name("TextInputBody".into()))
-                //         .margin(Margin::symmetric(4.0, 0.5))
-                //         .background_color(Color32::from_rgb(44, 43, 40))
-                //         .text_color(Color32::from_rgb(221, 221, 221))
-                //         .frame(false)
-                //         ,
-                // );
-                text_box_stroke.show(ui, |ui| {
-                    text_box_frame.show(ui, |ui| {
-                        let visuals = ui.visuals_mut();
-                        // visuals.selection.stroke = egui::Stroke::new(2.0, Color32::RED); // Change stroke color
-                        visuals.selection.bg_fill = self.selection_color; // Change background fill color
-                        // ui.add(
-                        //     egui::TextEdit::singleline(&mut self.folder_name)
-                        //         // .hint_text("Type something...") // Placeholder text
-                        //         .desired_width(f32::INFINITY) // Make it take full width
-                        //         .font(Name("TextInputBody".into()))
-                        //         .margin(Margin::symmetric(4.0, 1.))
-                        //         .background_color(Color32::from_rgb(44, 43, 40))
-                        //         .text_color(Color32::from_rgb(221, 221, 221))
-                        //         .frame(false)
-                        //         .lock_focus(true)
-                        //         ,
-                        // );
-                        // Use show() instead of ui.add() to get the output state
-                        let mut output = egui::TextEdit::singleline(&mut self.folder_name)
-                            .desired_width(f32::INFINITY)
-                            .font(Name("TextInputBody".into()))
-                            .margin(Margin::symmetric(3.0, 1.))
-                            .background_color(Color32::from_rgb(44, 43, 40))
-                            .text_color(Color32::from_rgb(221, 221, 221))
-                            .frame(false)
-                            .lock_focus(true)
-                            .show(ui);
-
-                        // Request focus on the TextEdit widget, once on first rendering
-                        output.response.request_focus();
-
-                        // Select all text by setting cursor range from 0 to end of text, on focus
-                        // Reference: https://stackoverflow.com/questions/74324236/select-the-text-of-a-textedit-object-in-egui
-                        use egui::{text::CCursor, text::CCursorRange};
-                        if output.response.gained_focus() { // If the widget is focused
-                        // if self.is_focused {
-                            output.state.cursor.set_char_range(Some(
-                                CCursorRange::two(
-                                    CCursor::new(0),
-                                    CCursor::new(self.folder_name.len())
-                                )
-                            ));
-                            // Apply the changes
-                            output.state.store(ui.ctx(), output.response.id);
-                        }
-
-                        // Store output for later use outside of this UI closure
-                        // text_edit_output = Some(output);
-                    });
-                });
-
-                ui.with_layout(egui::Layout::right_to_left(egui::Align::BOTTOM), |ui| {
-                    ui.add_space(3.);
-
-                    // Custom button styles
-                    // Reference: button.rs & style.rs
-                    let styles = ui.style_mut();
-                    styles.spacing.button_padding = egui::vec2(10.0, 2.0);
-                    styles.visuals.widgets.hovered.expansion = 0.0;
-                    styles.visuals.widgets.active.expansion = 0.0;
-
-                    // Primary button (Create) - Blue with white text
-                    let create_button = egui::Button::new(
-                        egui::RichText::new("Create")
-                        .color(egui::Color32::from_rgb(221, 221, 221))
-                        .text_style(Name("ButtonBody".into()))
-                        .extra_letter_spacing(0.2),
-                    )
-                    .fill(self.ok_button_color) // macOS blue
-                    .rounding(egui::Rounding::same(5.0)) // macOS rounded corners
-                    .frame(true)
-                    .min_size(Vec2::new(61., 20.))
-                    .stroke(Stroke::new(0.0, Color32::TRANSPARENT))
-                    ;
-
-                    if ui.add(create_button).clicked() {
-                        // Handle create button click
-                        println!("Create clicked. folder_name: {}", self.folder_name);
-                        // Reference: egui-0.30.0/src/ui.rs | https://github.com/emilk/egui/discussions/5340
-                        let style_height = ui.text_style_height(&Name("DialogHeading".into()));
-                        println!("Text Style Height = {}", style_height);
-                        // Window size
-                        let window_size = ctx.screen_rect().size();
-                        println!("Window size = {:?}", window_size);
-                        // Window position
-                        let window_pos = ctx.screen_rect().min;
-                        println!("Window position = {:?}", window_pos);
-                        // Window center
-                        let window_center = ctx.screen_rect().center();
-                        println!("Window center = {:?}", window_center);
-                        // Close the window
-                        ui.ctx().send_viewport_cmd(egui::ViewportCommand::Close);
-                    }
+        self.naming.name = format!("{}{}", stem, change.from.as_deref().unwrap_or_default());
+        self.naming.check();
+    }
+}
+
+/// State of the Directory dialog
+struct Directory {
+    path: String,
+    parent_dir: PathBuf, // Where relative paths start from
+}
 
-                    ui.add_space(0.1); // Space between buttons
-
-                    // Secondary button (Cancel) - Light gray with default text
-                    let cancel_button = egui::Button::new(
-                        egui::RichText::new("Cancel")
-                        .color(egui::Color32::from_rgb(221, 221, 221))
-                        .text_style(Name("ButtonBody".into()))
-                        .extra_letter_spacing(0.2),
-                    )
-                    .fill(Color32::from_rgb(89, 88, 86)) // macOS gray button color
-                    .rounding(egui::Rounding::same(5.0)) // macOS rounded corners
-                    .frame(true)
-                    // .min_size(Vec2::new(61., 20.))
-                    .stroke(Stroke::new(0.0, Color32::TRANSPARENT))
-                    ;
-
-
-                    if ui.add(cancel_button).clicked() {
-                        // Reset the input field
-                        self.folder_name = String::from("");
-                        // Handle cancel button click
-                        println!("Cancel clicked. folder_name: {}", self.folder_name);
-                        // Close the window
-                        ui.ctx().send_viewport_cmd(egui::ViewportCommand::Close);
+impl Directory {
+    /// Absolute path of the folder typed in the field, relative paths starting from parent_dir
+    fn resolve(&self, messages: &Messages) -> Result<String, String> {
+        let quoted_name = messages.quote(&self.path);
+        match std::fs::canonicalize(self.parent_dir.join(&self.path)) {
+            Ok(path) if path.is_dir() => Ok(path.display().to_string()),
+            Err(error) if error.kind() == io::ErrorKind::NotFound => {
+                Err(messages.format("error_not_found", &[("name", &quoted_name)]))
+            }
+            _ => Err(messages.format("error_not_directory", &[("name", &quoted_name)])),
+        }
+    }
+}
+
+/// State of a message box
+struct Message {
+    kind: MessageKind,
+    buttons: Vec<MessageButton>, // Buttons instead of the ones of the kind
+}
+
+impl Message {
+    /// Alerts and questions have buttons with roles instead of a field
+    fn show(
+        &self,
+        ctx: &egui::Context,
+        theme: &Theme,
+        chrome: &Chrome<'_>,
+    ) -> Option<DialogOutcome<DialogValue>> {
+        let buttons = self.buttons(chrome);
+        let response = MessageBox::new(self.kind, chrome.title)
+            .with_body(chrome.text)
+            .with_buttons(buttons.clone())
+            .show(ctx, theme, chrome.messages);
+        let button = response.pressed.and_then(|index| buttons.get(index))?;
+        // The label tells which answer was given, backing out is a plain Cancel
+        Some(match button.role {
+            ButtonRole::Cancel => DialogOutcome::Cancelled,
+            ButtonRole::Default | ButtonRole::Destructive => {
+                DialogOutcome::Confirmed(button.label.clone().into())
+            }
+        })
+    }
+
+    /// The given buttons, or Yes and No for questions and OK otherwise
+    fn buttons(&self, chrome: &Chrome<'_>) -> Vec<MessageButton> {
+        if !self.buttons.is_empty() {
+            return self.buttons.clone();
+        }
+        let mut buttons = vec![MessageButton::new(
+            chrome.ok_label.clone(),
+            ButtonRole::Default,
+        )];
+        buttons.extend(
+            chrome
+                .cancel_label
+                .clone()
+                .map(|label| MessageButton::new(label, ButtonRole::Cancel)),
+        );
+        buttons
+    }
+}
+
+/// State of the Progress dialog
+struct Progress {
+    updates: Option<Receiver<ProgressUpdate>>,
+    status: Option<String>, // Last status line, the dialog text until there is one
+    percentage: f32,
+    pulsate: bool,
+    done: bool,
+    auto_close: bool,
+    cancellable: bool,
+}
+
+impl Default for Progress {
+    fn default() -> Self {
+        Self {
+            updates: None,
+            status: None,
+            percentage: 0.0,
+            pulsate: false,
+            done: false,
+            auto_close: false,
+            cancellable: true,
+        }
+    }
+}
+
+impl Progress {
+    /// Apply the updates that arrived since the last frame and show the progress, closing once
+    /// the job is done if asked to
+    fn show(
+        &mut self,
+        ctx: &egui::Context,
+        theme: &Theme,
+        chrome: &mut Chrome<'_>,
+    ) -> Option<DialogOutcome<DialogValue>> {
+        while let Some(update) = self
+            .updates
+            .as_ref()
+            .and_then(|updates| updates.try_recv().ok())
+        {
+            match update {
+                ProgressUpdate::Percentage(percentage) => {
+                    self.percentage = percentage;
+                    self.done = percentage >= 100.0;
+                }
+                ProgressUpdate::Text(text) => self.status = Some(text),
+                ProgressUpdate::Pulsate(pulsate) => self.pulsate = pulsate,
+                ProgressUpdate::Finished => {
+                    // A job of unknown length is complete once its input ends
+                    if self.pulsate {
+                        self.pulsate = false;
+                        self.percentage = 100.0;
                     }
-                });
-            });
-        // Check if the egui window is focused.
-        // Reference: https://docs.rs/egui/0.30.0/egui/struct.Context.html#method.input
-        self.is_focused = ctx.input(|i| i.focused);
-        if self.is_focused {
-            // Update the stroke color based on focus
-            self.stroke_color = Color32::from_rgb(56, 100, 138);
-            // Update the selection color based on focus
-            self.selection_color = Color32::from_rgb(71,98,135); // macOS text selection color
-            // Select all text in TextEdit widget on a specific condition
-            // Reference: https://stackoverflow.com/questions/74324236/select-the-text-of-a-textedit-object-in-egui
-            // if let Some(mut output) = text_edit_output {
-            //     use egui::{text::CCursor, text::CCursorRange};
-            //     // Defining the selection of text if the widget is focused
-            //     if output.response.gained_focus() {
-            //         output.state.cursor.set_char_range(Some(
-            //             CCursorRange::two(
-            //                 CCursor::new(0),
-            //                 CCursor::new(self.folder_name.len())
-            //             )
-            //         ));
-            //         // Apply the changes
-            //         output.state.store(ctx, output.response.id);
-            //     }
-            //     // Request focus on the TextEdit widget
-            //     output.response.request_focus();
-            // }
-            // Update the color of the OK button based on focus
-            self.ok_button_color = Color32::from_rgb(48, 98, 212); // macOS blue button color
-        }
-        else {
-            // Update the stroke color based on focus
-            self.stroke_color = Color32::TRANSPARENT;
-            // Update the selection color based on focus
-            self.selection_color = Color32::from_rgb(70,70,70); // macOS unfocused text selection color
-            // Update the color of the OK button based on focus
-            self.ok_button_color = Color32::from_rgb(89, 88, 86); // macOS gray button color
-        }
-    }
-
-    fn clear_color(&self, _visuals: &egui::Visuals) -> [f32; 4] {
-        // _visuals.window_fill().to_normalized_gamma_f32()
-        // egui::Color32::from_gray(27).to_normalized_gamma_f32()
-        Color32::from_rgb(33, 32, 29).to_normalized_gamma_f32()
+                    self.done = true;
+                    self.updates = None;
+                }
+            }
+        }
+        if self.done && self.auto_close {
+            return Some(DialogOutcome::Confirmed(String::new().into()));
+        }
+
+        let fraction = (!self.pulsate).then_some(self.percentage / 100.0);
+        let cancel_label = chrome.cancel_label.clone().filter(|_| self.cancellable);
+        let response = ProgressDialog::new(chrome.title, fraction)
+            .with_status(self.status.as_deref().unwrap_or(chrome.text))
+            .with_done(self.done)
+            .with_ok_label(chrome.ok_label.clone())
+            .with_cancel_label(cancel_label)
+            .show(ctx, theme, chrome.messages);
+        chrome.answer(&response, |_| Ok(String::new()))
     }
+}
 
-    // Reference: https://qiita.com/8bitTD/items/7d745bbf90a82aaffd7f
-    fn on_exit(&mut self, _:Option<&eframe::glow::Context>) {
-        // Save the result when the app is closing
-        let mut result = self.result.lock().unwrap();
-        *result = self.get_result();
+/// State of the List dialog
+struct List {
+    table: Table,
+    state: ListState,
+    multiple: bool, // Whether several rows are picked, with checkboxes
+}
+
+/// State of the Open and Save dialogs
+struct Files {
+    browser: FileBrowser,
+    save: bool,
+    multiple: bool,               // Whether the Open dialog chooses several files
+    normalization: Normalization, // Unicode form of the saved name and created folders
+    overwrite: Option<PathBuf>,   // File the Save dialog would replace, waiting for confirmation
+    new_folder: Option<NewFolderWindow>, // New Folder dialog opened from the chooser
+}
+
+/// The New Folder dialog opened by the Open and Save dialogs, in a window of its own
+struct NewFolderWindow {
+    app: Box<TemplateApp>,
+    outcome: Receiver<DialogOutcome>,
+}
+
+impl Files {
+    fn show(
+        &mut self,
+        ctx: &egui::Context,
+        theme: &Theme,
+        chrome: &mut Chrome<'_>,
+    ) -> Option<DialogOutcome<DialogValue>> {
+        // Without windows of their own, the New Folder dialog takes the place of the chooser
+        if self.new_folder.is_some() && ctx.embed_viewports() {
+            self.show_new_folder(ctx);
+            return None;
+        }
+        let outcome = match self.overwrite.clone() {
+            Some(target) => self.show_overwrite(ctx, theme, chrome, &target),
+            None => self.show_chooser(ctx, theme, chrome),
+        };
+        if self.new_folder.is_some() {
+            self.show_new_folder(ctx);
+        }
+        outcome
     }
+
+    /// Show the Open or Save dialog; New Folder opens the New Folder dialog in the listed folder
+    fn show_chooser(
+        &mut self,
+        ctx: &egui::Context,
+        theme: &Theme,
+        chrome: &mut Chrome<'_>,
+    ) -> Option<DialogOutcome<DialogValue>> {
+        let response = FileChooser::new(chrome.title, &mut self.browser)
+            .with_save(self.save)
+            .with_multiple(self.multiple)
+            .with_ok_label(chrome.ok_label.clone())
+            .with_cancel_label(chrome.cancel_label.clone().unwrap_or_default())
+            .with_feedback(chrome.feedback())
+            .show(ctx, theme, chrome.messages);
+        if response.action_clicked {
+            self.open_new_folder(ctx, chrome);
+        }
+        // Editing the name dismisses the last error
+        if response.changed {
+            *chrome.error_message = None;
+        }
+        if response.cancelled {
+            Some(DialogOutcome::Cancelled)
+        } else if response.confirmed {
+            self.confirm(chrome)
+        } else {
+            None
+        }
+    }
+
+    /// The chosen files or folder, or the file to save to once replacing it was confirmed; a
+    /// name that can't be saved as is refused inline
+    fn confirm(&mut self, chrome: &mut Chrome<'_>) -> Option<DialogOutcome<DialogValue>> {
+        if self.browser.folders_only() {
+            return Some(DialogOutcome::Confirmed(
+                vec![self.browser.chosen_folder()].into(),
+            ));
+        }
+        if !self.save {
+            return Some(DialogOutcome::Confirmed(self.browser.chosen_files().into()));
+        }
+        // The name must not lead out of the listed folder, e.g. "../report.pdf"
+        let name = self.normalization.apply(self.browser.file_name().trim());
+        if let Err(error) = validate_folder_name(&name) {
+            *chrome.error_message = Some(chrome.messages.name_error(error));
+            return None;
+        }
+        let target = self.browser.directory().join(name);
+        // Replacing a file is confirmed first, the answer is given once it is accepted
+        if self.overwrite.take().as_ref() != Some(&target) && target.exists() {
+            self.overwrite = Some(target);
+            return None;
+        }
+        Some(DialogOutcome::Confirmed(vec![target].into()))
+    }
+
+    /// Ask whether the Save dialog replaces `target`: Replace answers with it, Cancel goes back
+    /// to the chooser
+    fn show_overwrite(
+        &mut self,
+        ctx: &egui::Context,
+        theme: &Theme,
+        chrome: &mut Chrome<'_>,
+        target: &Path,
+    ) -> Option<DialogOutcome<DialogValue>> {
+        let messages = chrome.messages;
+        let name = target
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let folder = self.browser.directory().file_name().map_or_else(
+            || self.browser.directory().display().to_string(),
+            |folder| folder.to_string_lossy().into_owned(),
+        );
+        let buttons = vec![
+            MessageButton::new(messages.get("replace"), ButtonRole::Destructive),
+            MessageButton::new(
+                chrome.cancel_label.clone().unwrap_or_default(),
+                ButtonRole::Cancel,
+            ),
+        ];
+        let response = MessageBox::new(
+            MessageKind::Warning,
+            messages.format("confirm_overwrite", &[("name", &messages.quote(&name))]),
+        )
+        .with_body(messages.format(
+            "confirm_overwrite_body",
+            &[("folder", &messages.quote(&folder))],
+        ))
+        .with_buttons(buttons.clone())
+        .show(ctx, theme, messages);
+        match response
+            .pressed
+            .and_then(|index| buttons.get(index))
+            .map(|button| button.role)
+        {
+            Some(ButtonRole::Cancel) => {
+                self.overwrite = None;
+                None
+            }
+            Some(_) => self.confirm(chrome),
+            None => None,
+        }
+    }
+
+    /// Open the New Folder dialog creating its folder in the listed folder, or bring it to the
+    /// front if it is open already
+    fn open_new_folder(&mut self, ctx: &egui::Context, chrome: &Chrome<'_>) {
+        if self.new_folder.is_some() {
+            ctx.send_viewport_cmd_to(new_folder_viewport(), egui::ViewportCommand::Focus);
+            return;
+        }
+        let messages = chrome.messages;
+        let (sender, outcome) = mpsc::channel();
+        let mut app = TemplateApp::default()
+            .with_messages(messages.clone())
+            .with_themes(chrome.themes.clone())
+            .with_appearance(chrome.appearance)
+            .with_normalization(self.normalization)
+            .with_title(messages.get("title").to_owned())
+            .with_parent_dir(self.browser.directory().to_owned())
+            .with_default_name(messages.get("default_folder_name").to_owned())
+            .with_outcome_sender(sender);
+        app.child = true;
+        self.new_folder = Some(NewFolderWindow {
+            app: Box::new(app),
+            outcome,
+        });
+    }
+
+    /// Show the New Folder dialog in its window until it has an outcome; the created folder is
+    /// listed and chosen
+    fn show_new_folder(&mut self, ctx: &egui::Context) {
+        let Some(window) = &mut self.new_folder else {
+            return;
+        };
+        if ctx.embed_viewports() {
+            window.app.show(ctx);
+        } else {
+            // Same size as when it is run on its own
+            let builder = egui::ViewportBuilder::default()
+                .with_title(window.app.title.clone())
+                .with_inner_size([321.0, 199.0])
+                .with_resizable(true)
+                .with_maximize_button(false)
+                .with_minimize_button(false);
+            ctx.show_viewport_immediate(new_folder_viewport(), builder, |ctx, _| {
+                if ctx.input(|i| i.viewport().close_requested()) {
+                    window.app.finish(ctx, DialogOutcome::Dismissed);
+                } else {
+                    window.app.show(ctx);
+                }
+            });
+        }
+        match window.outcome.try_recv() {
+            Ok(DialogOutcome::Confirmed(path)) => {
+                let path = PathBuf::from(path);
+                if let Some(parent) = path.parent() {
+                    self.browser.open(parent);
+                }
+                self.browser.select_only(&path);
+                self.new_folder = None;
+            }
+            Ok(_) | Err(mpsc::TryRecvError::Disconnected) => self.new_folder = None,
+            Err(mpsc::TryRecvError::Empty) => {}
+        }
+    }
+}
+
+/// Window of the New Folder dialog opened by the Open and Save dialogs
+fn new_folder_viewport() -> egui::ViewportId {
+    egui::ViewportId::from_hash_of("new_folder")
 }
 
 // fn powered_by_egui_and_eframe(ui: &mut egui::Ui) {
@@ -554,3 +1462,80 @@ impl eframe::App for TemplateApp {
 //         ui.label(".");
 //     });
 // }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn extension_changes() {
+        let change = |from: Option<&str>, to: Option<&str>| {
+            Some(ExtensionChange {
+                from: from.map(str::to_owned),
+                to: to.map(str::to_owned),
+            })
+        };
+        assert_eq!(ExtensionChange::between("report.pdf", "final.pdf"), None);
+        assert_eq!(
+            ExtensionChange::between("report.pdf", "report.txt"),
+            change(Some(".pdf"), Some(".txt"))
+        );
+        // Extensions compare as spelled, like Finder
+        assert_eq!(
+            ExtensionChange::between("photo.jpg", "photo.JPG"),
+            change(Some(".jpg"), Some(".JPG"))
+        );
+        assert_eq!(
+            ExtensionChange::between("report.pdf", "report"),
+            change(Some(".pdf"), None)
+        );
+        assert_eq!(
+            ExtensionChange::between("README", "README.md"),
+            change(None, Some(".md"))
+        );
+        assert_eq!(ExtensionChange::between("README", "NOTES"), None);
+        // A leading dot hides the name rather than starting an extension
+        assert_eq!(ExtensionChange::between(".bashrc", ".zshrc"), None);
+        assert_eq!(
+            ExtensionChange::between(".bashrc", ".bashrc.bak"),
+            change(None, Some(".bak"))
+        );
+    }
+
+    #[test]
+    fn missing_entry_is_named_by_its_kind() {
+        let dir = std::env::temp_dir().join(format!("eframe_test-{}-rename", std::process::id()));
+        std::fs::create_dir_all(dir.join("Photos")).unwrap();
+        std::fs::write(dir.join("notes.txt"), "").unwrap();
+        let messages = Messages::default();
+        let renaming = |original: &str| {
+            let mut naming = Naming::new(
+                dir.clone(),
+                "renamed".to_owned(),
+                Some(original.to_owned()),
+                Normalization::Nfc,
+            );
+            naming.name = "renamed".to_owned();
+            naming
+        };
+        let file = renaming("notes.txt");
+        let folder = renaming("Photos");
+        std::fs::remove_file(dir.join("notes.txt")).unwrap();
+        std::fs::remove_dir(dir.join("Photos")).unwrap();
+
+        assert_eq!(
+            file.rename(&messages),
+            Err("The file “notes.txt” doesn't exist.".to_owned())
+        );
+        assert_eq!(
+            folder.rename(&messages),
+            Err("The folder “Photos” doesn't exist.".to_owned())
+        );
+        std::fs::remove_dir(&dir).unwrap();
+        let folder_name = dir.file_name().unwrap().to_string_lossy();
+        assert_eq!(
+            file.rename(&messages),
+            Err(format!("The folder “{folder_name}” doesn't exist."))
+        );
+    }
+}
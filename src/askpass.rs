@@ -0,0 +1,104 @@
+use std::ffi::OsString;
+use std::path::Path;
+
+use crate::DialogOutcome;
+
+/// Set by OpenSSH 8.4 and later to tell what the prompt expects: "confirm" for a yes/no
+/// question, "none" for a notice, unset or anything else for a passphrase
+pub const PROMPT_VARIABLE: &str = "SSH_ASKPASS_PROMPT";
+
+/// What an askpass prompt asks for
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Prompt {
+    /// A password or passphrase, typed into a masked field
+    Secret,
+    /// A yes/no question, e.g. whether to trust an unknown host key, answered "yes" or with exit 1
+    Confirm,
+    /// Something to acknowledge, e.g. that a security key must be touched
+    Notice,
+}
+
+impl Prompt {
+    /// Classify `prompt`, with the value of [`PROMPT_VARIABLE`] if it is set
+    pub fn classify(prompt: &str, hint: Option<&str>) -> Self {
+        match hint {
+            Some("confirm") => Self::Confirm,
+            Some("none") => Self::Notice,
+            // Older ssh versions only ask host key questions as "… (yes/no)?" or
+            // "… (yes/no/[fingerprint])?"
+            _ if prompt.contains("(yes/no") => Self::Confirm,
+            _ => Self::Secret,
+        }
+    }
+}
+
+/// Whether the binary was started as an askpass helper, `ssh-askpass` or `sudo-askpass` for
+/// example: ssh and `sudo -A` run the program named by SSH_ASKPASS or SUDO_ASKPASS, a symlink
+pub fn is_invocation(args: &[OsString]) -> bool {
+    args.first()
+        .and_then(|program| Path::new(program).file_stem())
+        .is_some_and(|stem| stem.to_string_lossy().ends_with("askpass"))
+}
+
+/// Exit code for an outcome: ssh and sudo only tell success from failure
+pub fn exit_code(outcome: &DialogOutcome) -> i32 {
+    match outcome {
+        DialogOutcome::Confirmed(_) => 0,
+        _ => 1,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HOST_KEY: &str =
+        "The authenticity of host 'example.com (192.0.2.1)' can't be established.\n\
+        ED25519 key fingerprint is SHA256:abc.\n\
+        Are you sure you want to continue connecting (yes/no/[fingerprint])?";
+
+    #[test]
+    fn hint_decides() {
+        assert_eq!(
+            Prompt::classify("Allow use of key?", Some("confirm")),
+            Prompt::Confirm
+        );
+        assert_eq!(
+            Prompt::classify("Confirm user presence", Some("none")),
+            Prompt::Notice
+        );
+        // The hint wins over the wording
+        assert_eq!(Prompt::classify(HOST_KEY, Some("none")), Prompt::Notice);
+    }
+
+    #[test]
+    fn host_key_question_without_hint() {
+        assert_eq!(Prompt::classify(HOST_KEY, None), Prompt::Confirm);
+        assert_eq!(
+            Prompt::classify("Continue connecting (yes/no)?", None),
+            Prompt::Confirm
+        );
+    }
+
+    #[test]
+    fn password_prompt() {
+        assert_eq!(Prompt::classify("Password:", None), Prompt::Secret);
+        assert_eq!(
+            Prompt::classify(
+                "Enter passphrase for key '/home/me/.ssh/id_ed25519':",
+                Some("")
+            ),
+            Prompt::Secret
+        );
+        assert_eq!(
+            Prompt::classify("[sudo] password for me:", None),
+            Prompt::Secret
+        );
+    }
+
+    #[test]
+    fn called_as_askpass() {
+        assert!(is_invocation(&[OsString::from("/usr/lib/ssh/ssh-askpass")]));
+        assert!(!is_invocation(&[OsString::from("eframe_test")]));
+    }
+}
@@ -0,0 +1,531 @@
+use std::io::{self, BufRead, IsTerminal, Read, Write};
+use std::path::PathBuf;
+use std::time::Duration;
+
+use clap::{ArgGroup, Parser, ValueEnum};
+
+use crate::file_chooser::FileFilter;
+use crate::git_credential;
+use crate::i18n::detect_locale;
+use crate::list_dialog::Table;
+use crate::message_box::{ButtonRole, MessageButton};
+use crate::theme::Appearance;
+use crate::validation::Normalization;
+use crate::MessageKind;
+
+/// Command line arguments of the New Folder dialog
+#[derive(Debug, Parser)]
+#[command(
+    version,
+    about = "Ask for a folder name and create it inside a parent directory",
+    group(ArgGroup::new("prompt").args(["message", "secret", "list", "progress"])),
+    group(ArgGroup::new("chooser").args(["open", "save"])),
+    group(ArgGroup::new("several").args(["list", "open"]))
+)]
+pub struct Cli {
+    /// Directory the new folder is created in, or the file chooser starts in [default: the
+    /// working directory]
+    #[arg(long, value_name = "PATH", conflicts_with = "stdin")]
+    pub parent: Option<PathBuf>,
+
+    /// Read the parent directory path from the first line of stdin
+    #[arg(long)]
+    pub stdin: bool,
+
+    /// Rename this file or folder instead of creating a folder
+    #[arg(long, value_name = "PATH", conflicts_with_all = ["parent", "stdin", "default_name"])]
+    pub rename: Option<PathBuf>,
+
+    /// Show a message box of this kind instead of creating a folder, answered with the label of
+    /// the pressed button
+    #[arg(long, value_enum, value_name = "KIND", conflicts_with_all = ["parent", "stdin", "rename", "default_name"])]
+    pub message: Option<MessageKind>,
+
+    /// Ask for a password or another secret instead of creating a folder, printed on stdout but
+    /// never logged (--password is zenity's)
+    #[arg(long, conflicts_with_all = ["parent", "stdin", "rename", "default_name"])]
+    pub secret: bool,
+
+    /// Pick rows of a table instead of creating a folder, answered with the chosen rows: the
+    /// VALUES, or rows read from stdin in the --rows format
+    #[arg(long, conflicts_with_all = ["parent", "stdin", "rename", "default_name"])]
+    pub list: bool,
+
+    /// Name of a list column (repeatable) [default: the first row of TSV input, the keys of JSON
+    /// objects]
+    #[arg(long = "column", value_name = "NAME", requires = "list")]
+    pub columns: Vec<String>,
+
+    /// Let several rows of the list be picked, with checkboxes, or several files be opened
+    #[arg(long, requires = "several")]
+    pub multiple: bool,
+
+    /// Format of the list rows read from stdin
+    #[arg(long, value_enum, value_name = "FORMAT", default_value_t = RowFormat::Tsv, requires = "list")]
+    pub rows: RowFormat,
+
+    /// Cells of the list, filling the columns row after row, instead of reading stdin
+    #[arg(value_name = "VALUES", requires = "list")]
+    pub values: Vec<String>,
+
+    /// Show the progress of a job instead of creating a folder, fed on stdin with percentages and
+    /// "# status" lines, answered with the exit code (given first, it runs zenity's --progress)
+    #[arg(long, conflicts_with_all = ["parent", "stdin", "rename", "default_name"])]
+    pub progress: bool,
+
+    /// Percentage the progress starts at
+    #[arg(
+        long,
+        value_name = "PERCENTAGE",
+        requires = "progress",
+        default_value_t = 0.0
+    )]
+    pub percentage: f32,
+
+    /// Slide the progress bar back and forth, for jobs of unknown length
+    #[arg(long, requires = "progress")]
+    pub pulsate: bool,
+
+    /// Close the progress dialog once 100% is reached
+    #[arg(long, requires = "progress")]
+    pub auto_close: bool,
+
+    /// Send SIGTERM to the parent process (the script) when the progress is cancelled
+    #[arg(long, requires = "progress")]
+    pub auto_kill: bool,
+
+    /// Leave out the Cancel button of the progress dialog
+    #[arg(long, requires = "progress")]
+    pub no_cancel: bool,
+
+    /// Choose existing files instead of creating a folder, answered with their paths: the
+    /// chooser starts in the parent directory
+    #[arg(long, conflicts_with_all = ["rename", "prompt"])]
+    pub open: bool,
+
+    /// Choose where to save a file instead of creating a folder, answered with its path once an
+    /// existing file may be replaced: the default name is the file name
+    #[arg(long, conflicts_with_all = ["rename", "prompt"])]
+    pub save: bool,
+
+    /// Files the chooser lists, as NAME | PATTERN1 PATTERN2 (repeatable, the first one applies)
+    #[arg(long = "filter", value_name = "NAME | PATTERNS", requires = "chooser")]
+    pub filters: Vec<String>,
+
+    /// Act as a git credential helper, `credential.helper = "/path/to/eframe_test --git-credential"`:
+    /// git appends get, store or erase and sends the request on stdin
+    #[arg(long, value_enum, value_name = "ACTION", conflicts_with_all = ["parent", "stdin", "rename", "default_name", "prompt"])]
+    pub git_credential: Option<git_credential::Action>,
+
+    /// Body of the message box, secret prompt, list or progress [default: depends on the dialog, translated]
+    #[arg(long, requires = "prompt")]
+    pub text: Option<String>,
+
+    /// Message box button as LABEL or LABEL:ROLE, ROLE being default, cancel or destructive
+    /// (repeatable, up to 3) [default: the first button is the default one, the others cancel]
+    #[arg(long = "button", value_name = "LABEL[:ROLE]", requires = "message", value_parser = parse_button)]
+    pub buttons: Vec<ButtonArg>,
+
+    /// Name suggested in the text field [default: "untitled folder", or "Untitled" to save a
+    /// file, translated]
+    #[arg(long, value_name = "NAME")]
+    pub default_name: Option<String>,
+
+    /// Dialog heading, also used as the window title [default: "New Folder", translated]
+    #[arg(long)]
+    pub title: Option<String>,
+
+    /// Unicode normalization form the folder name is created with
+    #[arg(long, value_enum, default_value_t = Normalization::Nfc)]
+    pub normalization: Normalization,
+
+    /// Language of the dialog (en, es, ja, de, ar, he) [default: from LC_ALL, LC_MESSAGES or LANG]
+    #[arg(long, value_name = "LOCALE")]
+    pub lang: Option<String>,
+
+    /// How the outcome is printed on stdout, diagnostics always go to stderr
+    #[arg(long, alias = "output-format", value_enum, default_value_t = OutputFormat::Plain)]
+    pub output: OutputFormat,
+
+    /// Dismiss the dialog after this many seconds without an answer
+    #[arg(long, value_name = "SECONDS", value_parser = parse_seconds)]
+    pub timeout: Option<Duration>,
+
+    /// Built-in theme (macos, adwaita, breeze, or one variant like macos-dark) or a TOML/JSON theme file
+    #[arg(long, value_name = "PRESET|FILE", default_value = "macos")]
+    pub theme: String,
+
+    /// Draw the dark or light variant of the theme, or follow the system preference
+    #[arg(long, value_enum, env = "EFRAME_TEST_APPEARANCE", default_value_t = Appearance::Auto)]
+    pub appearance: Appearance,
+
+    /// Directory searched for the dialog fonts before the system ones (repeatable)
+    #[arg(long = "font-dir", value_name = "DIR")]
+    pub font_dirs: Vec<PathBuf>,
+}
+
+/// Format of the list rows read from stdin
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum RowFormat {
+    /// One row per line, cells separated by tabs
+    Tsv,
+    /// An array of rows, each an array of cells or an object of cells by column name
+    Json,
+}
+
+/// Format of the outcome printed once the dialog closes
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    /// Path of the created folder (or the pressed button) on one line, nothing otherwise
+    #[value(alias = "text")]
+    Plain,
+    /// One JSON object with the outcome, value, absolute path and timing
+    Json,
+    /// `DIALOG_*=value` lines, quoted for `eval`
+    Shell,
+    /// Path of the created folder (or the pressed button) terminated by a NUL byte, for `xargs -0`
+    Nul,
+}
+
+impl Cli {
+    /// Locale from --lang, or the environment
+    pub fn locale(&self) -> String {
+        self.lang.clone().unwrap_or_else(detect_locale)
+    }
+
+    /// The --button options in order, the first one without a role being the default button and
+    /// the others cancel buttons; an error if there are more than three or two of a role
+    pub fn message_buttons(&self) -> Result<Vec<MessageButton>, String> {
+        if self.buttons.len() > 3 {
+            return Err("A message box has at most 3 buttons".to_owned());
+        }
+        let buttons: Vec<_> = self
+            .buttons
+            .iter()
+            .enumerate()
+            .map(|(index, button)| {
+                let implied_role = if index == 0 {
+                    ButtonRole::Default
+                } else {
+                    ButtonRole::Cancel
+                };
+                MessageButton::new(button.label.clone(), button.role.unwrap_or(implied_role))
+            })
+            .collect();
+        for role in [ButtonRole::Default, ButtonRole::Cancel] {
+            if buttons.iter().filter(|button| button.role == role).count() > 1 {
+                let name = role
+                    .to_possible_value()
+                    .map(|value| value.get_name().to_owned())
+                    .unwrap_or_default();
+                return Err(format!("Only one button can have the {name} role"));
+            }
+        }
+        Ok(buttons)
+    }
+
+    /// The rows of the list: the VALUES, or rows read from stdin
+    pub fn list_table(&self) -> Result<Table, String> {
+        if !self.values.is_empty() {
+            let width = self.columns.len().max(1);
+            let rows = self.values.chunks(width).map(<[String]>::to_vec).collect();
+            return Ok(Table::new(self.columns.clone(), rows));
+        }
+        let mut input = String::new();
+        io::stdin()
+            .read_to_string(&mut input)
+            .map_err(|error| format!("Failed to read the rows: {error}"))?;
+        match self.rows {
+            RowFormat::Tsv => Ok(parse_tsv(&input, self.columns.clone())),
+            RowFormat::Json => parse_json(&input, self.columns.clone()),
+        }
+    }
+
+    /// The --filter options, in order
+    pub fn file_filters(&self) -> Vec<FileFilter> {
+        self.filters
+            .iter()
+            .map(|spec| FileFilter::parse(spec))
+            .collect()
+    }
+
+    /// Resolve the parent directory from --parent, stdin or the working directory
+    pub fn parent_dir(&self) -> io::Result<PathBuf> {
+        if self.stdin {
+            return read_parent_from_stdin();
+        }
+        match &self.parent {
+            Some(path) => Ok(path.clone()),
+            None => std::env::current_dir(),
+        }
+    }
+}
+
+/// Backwards compatible prompt: the parent path is the first line of stdin
+fn read_parent_from_stdin() -> io::Result<PathBuf> {
+    let stdin = io::stdin();
+    // Only prompt when a person is typing, scripts just pipe the path in
+    if stdin.is_terminal() {
+        eprint!("Enter parent folder path: ");
+        io::stderr().flush()?;
+    }
+
+    let mut line = String::new();
+    stdin.lock().read_line(&mut line)?;
+    // An empty answer means the working directory
+    match line.trim() {
+        "" => std::env::current_dir(),
+        path => Ok(PathBuf::from(path)),
+    }
+}
+
+/// Rows of tab separated cells, the first one naming the columns unless `columns` does
+fn parse_tsv(input: &str, mut columns: Vec<String>) -> Table {
+    let mut rows = input
+        .lines()
+        .map(|line| line.trim_end_matches('\r'))
+        .filter(|line| !line.is_empty())
+        .map(|line| line.split('\t').map(str::to_owned).collect::<Vec<_>>());
+    if columns.is_empty() {
+        columns = rows.next().unwrap_or_default();
+    }
+    Table::new(columns, rows.collect())
+}
+
+/// An array of rows, each an array of cells or an object whose keys are the column names.
+/// Objects fill `columns` by name, the keys not in it becoming new columns.
+fn parse_json(input: &str, mut columns: Vec<String>) -> Result<Table, String> {
+    let value: serde_json::Value =
+        serde_json::from_str(input).map_err(|error| format!("Invalid JSON rows: {error}"))?;
+    let serde_json::Value::Array(items) = value else {
+        return Err("JSON rows must be an array".to_owned());
+    };
+    // Strings are taken as they are, other values as their JSON text
+    let cell = |value: &serde_json::Value| match value {
+        serde_json::Value::String(text) => text.clone(),
+        serde_json::Value::Null => String::new(),
+        value => value.to_string(),
+    };
+    let mut rows = Vec::with_capacity(items.len());
+    for item in &items {
+        match item {
+            serde_json::Value::Array(cells) => {
+                rows.push(cells.iter().map(cell).collect::<Vec<_>>())
+            }
+            serde_json::Value::Object(fields) => {
+                for key in fields.keys() {
+                    if !columns.contains(key) {
+                        columns.push(key.clone());
+                    }
+                }
+                let row = columns
+                    .iter()
+                    .map(|column| fields.get(column).map(cell).unwrap_or_default())
+                    .collect();
+                rows.push(row);
+            }
+            _ => return Err("Each JSON row must be an array or an object".to_owned()),
+        }
+    }
+    Ok(Table::new(columns, rows))
+}
+
+/// A --button option, whose role may be left to its position
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ButtonArg {
+    pub label: String,
+    pub role: Option<ButtonRole>,
+}
+
+/// "Save" or "Don't Save:destructive"; a colon not followed by a role is part of the label
+fn parse_button(value: &str) -> Result<ButtonArg, String> {
+    if let Some((label, role)) = value.rsplit_once(':') {
+        if let Ok(role) = ButtonRole::from_str(role, true) {
+            return Ok(ButtonArg {
+                label: label.to_owned(),
+                role: Some(role),
+            });
+        }
+    }
+    Ok(ButtonArg {
+        label: value.to_owned(),
+        role: None,
+    })
+}
+
+fn parse_seconds(value: &str) -> Result<Duration, String> {
+    let seconds: f64 = value
+        .parse()
+        .map_err(|_| format!("`{value}` is not a number of seconds"))?;
+    Duration::try_from_secs_f64(seconds).map_err(|error| error.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn strings(cells: &[&str]) -> Vec<String> {
+        cells.iter().map(|cell| cell.to_string()).collect()
+    }
+
+    #[test]
+    fn tsv_rows_are_padded() {
+        let table = parse_tsv(
+            "Name\tSize\r\nnotes.txt\t12\n\nlonely\na\tb\tc\n",
+            Vec::new(),
+        );
+        assert_eq!(table.columns, strings(&["Name", "Size", "Column 3"]));
+        assert_eq!(
+            table.rows,
+            [
+                strings(&["notes.txt", "12", ""]),
+                strings(&["lonely", "", ""]),
+                strings(&["a", "b", "c"]),
+            ]
+        );
+
+        let table = parse_tsv("first\tline\n", strings(&["A", "B"]));
+        assert_eq!(table.rows, [strings(&["first", "line"])]);
+        assert_eq!(parse_tsv("", Vec::new()), Table::default());
+    }
+
+    #[test]
+    fn json_rows() {
+        let table = parse_json(
+            r#"[["a", 1, null, true], {"Size": 2, "Name": "b", "Kind": "file"}]"#,
+            strings(&["Name", "Size"]),
+        )
+        .unwrap();
+        assert_eq!(
+            table.columns,
+            strings(&["Name", "Size", "Kind", "Column 4"])
+        );
+        assert_eq!(
+            table.rows,
+            [
+                strings(&["a", "1", "", "true"]),
+                strings(&["b", "2", "file", ""]),
+            ]
+        );
+        assert_eq!(parse_json("[]", Vec::new()), Ok(Table::default()));
+    }
+
+    #[test]
+    fn malformed_json_rows() {
+        assert_eq!(
+            parse_json(r#"{"Name": "a"}"#, Vec::new()),
+            Err("JSON rows must be an array".to_owned())
+        );
+        assert_eq!(
+            parse_json(r#"[["a"], "b"]"#, Vec::new()),
+            Err("Each JSON row must be an array or an object".to_owned())
+        );
+        assert!(parse_json("[[", Vec::new())
+            .unwrap_err()
+            .starts_with("Invalid JSON rows: "));
+    }
+
+    #[test]
+    fn button_specs() {
+        let button = |label: &str, role| ButtonArg {
+            label: label.to_owned(),
+            role,
+        };
+        assert_eq!(parse_button("Save"), Ok(button("Save", None)));
+        assert_eq!(
+            parse_button("Don't Save:destructive"),
+            Ok(button("Don't Save", Some(ButtonRole::Destructive)))
+        );
+        assert_eq!(
+            parse_button("Keep:CANCEL"),
+            Ok(button("Keep", Some(ButtonRole::Cancel)))
+        );
+        // An unknown role is part of the label, like any other colon
+        assert_eq!(parse_button("Ratio 16:9"), Ok(button("Ratio 16:9", None)));
+        assert_eq!(parse_button("OK:bogus"), Ok(button("OK:bogus", None)));
+        assert_eq!(
+            parse_button("Time: 10:default"),
+            Ok(button("Time: 10", Some(ButtonRole::Default)))
+        );
+        assert_eq!(parse_button(""), Ok(button("", None)));
+    }
+
+    #[test]
+    fn seconds() {
+        assert_eq!(parse_seconds("30"), Ok(Duration::from_secs(30)));
+        assert_eq!(parse_seconds("1.5"), Ok(Duration::from_millis(1500)));
+        assert_eq!(parse_seconds("0"), Ok(Duration::ZERO));
+        assert_eq!(
+            parse_seconds("soon"),
+            Err("`soon` is not a number of seconds".to_owned())
+        );
+        assert_eq!(
+            parse_seconds(""),
+            Err("`` is not a number of seconds".to_owned())
+        );
+        for value in ["-1", "NaN", "inf"] {
+            assert!(parse_seconds(value).is_err(), "{value}");
+        }
+    }
+
+    #[test]
+    fn message_button_roles() {
+        let cli = |args: &[&str]| {
+            Cli::try_parse_from(["eframe_test", "--message", "warning"].iter().chain(args)).unwrap()
+        };
+        assert_eq!(cli(&[]).message_buttons(), Ok(Vec::new()));
+        assert_eq!(
+            cli(&[
+                "--button",
+                "Delete:destructive",
+                "--button",
+                "Keep",
+                "--button",
+                "Save:default"
+            ])
+            .message_buttons(),
+            Ok(vec![
+                MessageButton::new("Delete", ButtonRole::Destructive),
+                MessageButton::new("Keep", ButtonRole::Cancel),
+                MessageButton::new("Save", ButtonRole::Default),
+            ])
+        );
+        assert_eq!(
+            cli(&["--button", "Keep", "--button", "Save:default"]).message_buttons(),
+            Err("Only one button can have the default role".to_owned())
+        );
+        assert_eq!(
+            cli(&["--button", "A", "--button", "B", "--button", "C", "--button", "D"])
+                .message_buttons(),
+            Err("A message box has at most 3 buttons".to_owned())
+        );
+    }
+
+    #[test]
+    fn command_line_smoke_test() {
+        let cli = Cli::try_parse_from([
+            "eframe_test",
+            "--parent",
+            "/tmp",
+            "--default-name",
+            "Photos",
+            "--timeout",
+            "2.5",
+            "--output",
+            "json",
+            "--lang",
+            "de",
+        ])
+        .unwrap();
+        assert_eq!(cli.parent, Some(PathBuf::from("/tmp")));
+        assert_eq!(cli.default_name.as_deref(), Some("Photos"));
+        assert_eq!(cli.timeout, Some(Duration::from_millis(2500)));
+        assert_eq!(cli.output, OutputFormat::Json);
+        assert_eq!(cli.locale(), "de");
+        assert_eq!(cli.theme, "macos");
+
+        assert!(Cli::try_parse_from(["eframe_test", "--parent", "/tmp", "--stdin"]).is_err());
+        assert!(Cli::try_parse_from(["eframe_test", "--timeout", "-1"]).is_err());
+        assert!(Cli::try_parse_from(["eframe_test", "--button", "OK"]).is_err());
+    }
+}
@@ -0,0 +1,448 @@
+use std::collections::{BTreeSet, HashMap};
+use std::path::{Path, PathBuf};
+
+use egui::Color32;
+use egui::Stroke;
+
+use egui::TextStyle::*;
+
+use crate::i18n::Messages;
+use crate::input_dialog::{focus_colors, leading_layout, list_frame, mirrored_margin};
+use crate::places::{self, Place};
+use crate::theme::Theme;
+
+/// Height the window grows by while the Where section is open
+const EXPANDED_HEIGHT: f32 = 236.0;
+/// The open section needs a wider window for the breadcrumb and the tree
+const EXPANDED_MIN_WIDTH: f32 = 420.0;
+const TREE_HEIGHT: f32 = 140.0;
+const TREE_INDENT: f32 = 14.0;
+
+/// What the Where section of the New Folder dialog remembers between frames: the chosen folder,
+/// which folders of the tree are expanded and the listings it has read
+#[derive(Clone, Debug, Default)]
+pub struct DirectoryBrowser {
+    selected: PathBuf,
+    expanded: BTreeSet<PathBuf>,
+    show_hidden: bool,
+    open: bool,
+    /// Subfolders of the folders listed so far, read again when hidden folders are toggled
+    listings: HashMap<PathBuf, Vec<PathBuf>>,
+    /// Home and the XDG user directories, read the first time the section opens
+    shortcuts: Option<Vec<Place>>,
+    scroll_to_selected: bool,
+}
+
+impl DirectoryBrowser {
+    /// Browser at `path`, its ancestors expanded
+    pub fn new(path: &Path) -> Self {
+        let mut browser = Self::default();
+        browser.select(path);
+        browser
+    }
+
+    /// Choose `path`, expanding its ancestors so it shows in the tree. Relative paths are made
+    /// absolute, links resolved.
+    pub fn select(&mut self, path: &Path) {
+        let path = std::fs::canonicalize(path).unwrap_or_else(|_| path.to_owned());
+        for ancestor in path.ancestors().skip(1) {
+            self.expanded.insert(ancestor.to_owned());
+        }
+        self.selected = path;
+        self.scroll_to_selected = true;
+    }
+
+    /// The chosen folder
+    pub fn selected(&self) -> &Path {
+        &self.selected
+    }
+
+    /// Whether the section is open, showing the tree
+    pub fn is_open(&self) -> bool {
+        self.open
+    }
+
+    /// Subfolders of `path`, read once; a folder that can't be read has none
+    fn subdirectories(&mut self, path: &Path) -> &[PathBuf] {
+        let show_hidden = self.show_hidden;
+        self.listings.entry(path.to_owned()).or_insert_with(|| {
+            places::subdirectories(path, show_hidden).unwrap_or_else(|error| {
+                log::debug!("Failed to list {}: {error}", path.display());
+                Vec::new()
+            })
+        })
+    }
+
+    /// Rows of the tree from the file system root, with their depth: the expanded folders show
+    /// their subfolders
+    fn rows(&mut self) -> Vec<(usize, PathBuf)> {
+        let Some(root) = self.selected.ancestors().last().map(Path::to_owned) else {
+            return Vec::new();
+        };
+        let mut rows = Vec::new();
+        let mut pending = vec![(0, root)];
+        while let Some((depth, path)) = pending.pop() {
+            if self.expanded.contains(&path) {
+                // Reversed on the stack, so they come out in order
+                pending.extend(
+                    self.subdirectories(&path)
+                        .iter()
+                        .rev()
+                        .map(|child| (depth + 1, child.clone())),
+                );
+            }
+            rows.push((depth, path));
+        }
+        rows
+    }
+
+    /// Whether `path` may have subfolders: it does, or it wasn't read yet
+    fn may_have_children(&self, path: &Path) -> bool {
+        self.listings
+            .get(path)
+            .map_or(true, |children| !children.is_empty())
+    }
+}
+
+/// The Where section of the New Folder dialog: a disclosure row naming the parent folder that
+/// opens a breadcrumb of its path, shortcuts to Home and the XDG user directories, a folder tree
+/// and a toggle for hidden folders.
+///
+/// The tree takes keyboard focus with Tab: Up and Down move through it, Right expands a folder
+/// and Left collapses it or goes to its parent. Opening the section makes the window taller.
+pub struct DirectoryPicker<'a> {
+    browser: &'a mut DirectoryBrowser,
+}
+
+impl<'a> DirectoryPicker<'a> {
+    /// Section browsing with `browser`
+    pub fn new(browser: &'a mut DirectoryBrowser) -> Self {
+        Self { browser }
+    }
+
+    /// Lay the section out and tell whether another folder was chosen
+    pub fn show(self, ui: &mut egui::Ui, theme: &Theme, messages: &Messages) -> bool {
+        let browser = self.browser;
+        let rtl = messages.is_rtl();
+        let before = browser.selected.clone();
+        let tree_id = egui::Id::new("directory_picker_tree");
+        let (stroke_color, selection_color, _) = focus_colors(ui.ctx(), theme);
+
+        // Disclosure row: "▸ Where: Documents"
+        ui.horizontal(|ui| {
+            ui.add_space(theme.margins.content_indent);
+            let folder = browser.selected.file_name().map_or_else(
+                || browser.selected.display().to_string(),
+                |name| name.to_string_lossy().into_owned(),
+            );
+            let (triangle_rect, _) =
+                ui.allocate_exact_size(egui::vec2(10.0, 14.0), egui::Sense::hover());
+            let label = ui.add(
+                egui::Label::new(
+                    egui::RichText::new(messages.visual(&format!(
+                        "{} {}",
+                        messages.get("where_label"),
+                        folder
+                    )))
+                    .color(theme.colors.text)
+                    .text_style(Name("DialogBody".into())),
+                )
+                .selectable(false),
+            );
+            let toggle = ui.interact(
+                triangle_rect.union(label.rect),
+                tree_id.with("disclosure"),
+                egui::Sense::click(),
+            );
+            paint_disclosure(
+                ui.painter(),
+                triangle_rect,
+                browser.open,
+                rtl,
+                theme.colors.text,
+            );
+            if toggle.clicked() {
+                browser.open = !browser.open;
+                resize_window(ui.ctx(), browser.open);
+            }
+        });
+        if !browser.open {
+            return false;
+        }
+        if browser.shortcuts.is_none() {
+            let home = places::home_dir().map(|path| Place {
+                name: messages.get("home").to_owned(),
+                path,
+            });
+            browser.shortcuts = Some(home.into_iter().chain(places::user_dirs()).collect());
+        }
+
+        // Breadcrumb of the chosen folder, each part choosing that ancestor
+        ui.horizontal(|ui| {
+            ui.add_space(theme.margins.content_indent);
+            egui::ScrollArea::horizontal()
+                .id_salt("directory_picker_breadcrumb")
+                .stick_to_right(true)
+                .show(ui, |ui| {
+                    ui.with_layout(leading_layout(rtl, egui::Align::Center), |ui| {
+                        let ancestors: Vec<PathBuf> =
+                            browser.selected.ancestors().map(Path::to_owned).collect();
+                        for (index, ancestor) in ancestors.iter().rev().enumerate() {
+                            if index > 0 {
+                                ui.label(
+                                    egui::RichText::new(if rtl { "‹" } else { "›" })
+                                        .color(theme.colors.disabled_text),
+                                );
+                            }
+                            let name = ancestor.file_name().map_or_else(
+                                || ancestor.display().to_string(),
+                                |name| name.to_string_lossy().into_owned(),
+                            );
+                            let crumb = ui.add(
+                                egui::Button::new(
+                                    egui::RichText::new(messages.visual(&name))
+                                        .color(theme.colors.text)
+                                        .text_style(Name("DialogBody".into())),
+                                )
+                                .frame(false),
+                            );
+                            if crumb.clicked() {
+                                browser.select(ancestor);
+                            }
+                        }
+                    });
+                });
+        });
+
+        // Shortcuts
+        ui.horizontal_wrapped(|ui| {
+            ui.add_space(theme.margins.content_indent);
+            for place in browser.shortcuts.clone().unwrap_or_default() {
+                let chosen = browser.selected == place.path;
+                let shortcut = ui.selectable_label(
+                    chosen,
+                    egui::RichText::new(messages.visual(&place.name))
+                        .color(theme.colors.text)
+                        .text_style(Name("DialogBody".into())),
+                );
+                if shortcut.clicked() {
+                    browser.select(&place.path);
+                }
+            }
+        });
+
+        // The tree, in a frame like the text field's
+        let tree_frame = list_frame(
+            theme,
+            mirrored_margin(theme.margins.field, rtl),
+            theme.colors.field_fill,
+        );
+        let has_focus = ui.memory(|memory| memory.has_focus(tree_id));
+        let mut rows = browser.rows();
+        if has_focus {
+            let keys = ui.input_mut(|i| {
+                [
+                    egui::Key::ArrowUp,
+                    egui::Key::ArrowDown,
+                    egui::Key::ArrowLeft,
+                    egui::Key::ArrowRight,
+                ]
+                .map(|key| i.consume_key(egui::Modifiers::NONE, key))
+            });
+            // Left and Right follow the reading direction
+            let [up, down, collapse, expand] = if rtl {
+                [keys[0], keys[1], keys[3], keys[2]]
+            } else {
+                keys
+            };
+            let position = rows
+                .iter()
+                .position(|(_, path)| *path == browser.selected)
+                .unwrap_or(0);
+            let path = rows
+                .get(position)
+                .map(|(_, path)| path.clone())
+                .unwrap_or_default();
+            if up && position > 0 {
+                browser.select(&rows[position - 1].1);
+            } else if down && position + 1 < rows.len() {
+                browser.select(&rows[position + 1].1);
+            } else if expand && !browser.expanded.contains(&path) {
+                browser.expanded.insert(path);
+            } else if expand && position + 1 < rows.len() && rows[position + 1].0 > rows[position].0
+            {
+                browser.select(&rows[position + 1].1);
+            } else if collapse && browser.expanded.contains(&path) && path.parent().is_some() {
+                browser.expanded.remove(&path);
+            } else if collapse {
+                if let Some(parent) = path.parent() {
+                    browser.select(parent);
+                }
+            }
+            rows = browser.rows();
+        }
+
+        let frame_response = tree_frame.show(ui, |ui| {
+            ui.set_height(TREE_HEIGHT);
+            let font_id = Name("DialogBody".into()).resolve(ui.style());
+            let row_height = ui.fonts(|fonts| fonts.row_height(&font_id)) + 4.0;
+            egui::ScrollArea::vertical()
+                .id_salt("directory_picker_rows")
+                .auto_shrink([false, false])
+                .show(ui, |ui| {
+                    ui.spacing_mut().item_spacing.y = 0.0;
+                    for (depth, path) in &rows {
+                        let (rect, row_response) = ui.allocate_exact_size(
+                            egui::vec2(ui.available_width(), row_height),
+                            egui::Sense::click(),
+                        );
+                        let selected = *path == browser.selected;
+                        if selected {
+                            ui.painter().rect_filled(rect, 0.0, selection_color);
+                            if std::mem::take(&mut browser.scroll_to_selected) {
+                                ui.scroll_to_rect(rect, None);
+                            }
+                        }
+                        let indent = 4.0 + *depth as f32 * TREE_INDENT;
+                        let triangle_rect = if rtl {
+                            egui::Rect::from_min_size(
+                                egui::pos2(rect.right() - indent - 10.0, rect.top()),
+                                egui::vec2(10.0, rect.height()),
+                            )
+                        } else {
+                            egui::Rect::from_min_size(
+                                egui::pos2(rect.left() + indent, rect.top()),
+                                egui::vec2(10.0, rect.height()),
+                            )
+                        };
+                        let expanded = browser.expanded.contains(path);
+                        if browser.may_have_children(path) {
+                            paint_disclosure(
+                                ui.painter(),
+                                triangle_rect,
+                                expanded,
+                                rtl,
+                                theme.colors.field_text,
+                            );
+                        }
+                        let name = path.file_name().map_or_else(
+                            || path.display().to_string(),
+                            |name| name.to_string_lossy().into_owned(),
+                        );
+                        let (position, align) = if rtl {
+                            (
+                                egui::pos2(triangle_rect.left() - 4.0, rect.center().y),
+                                egui::Align2::RIGHT_CENTER,
+                            )
+                        } else {
+                            (
+                                egui::pos2(triangle_rect.right() + 4.0, rect.center().y),
+                                egui::Align2::LEFT_CENTER,
+                            )
+                        };
+                        let color = if places::is_hidden(path) {
+                            theme.colors.disabled_text
+                        } else {
+                            theme.colors.field_text
+                        };
+                        ui.painter().text(
+                            position,
+                            align,
+                            messages.visual(&name),
+                            font_id.clone(),
+                            color,
+                        );
+
+                        // The triangle expands, the rest of the row chooses
+                        let on_triangle =
+                            row_response.interact_pointer_pos().is_some_and(|pointer| {
+                                triangle_rect
+                                    .expand2(egui::vec2(3.0, 0.0))
+                                    .contains(pointer)
+                            });
+                        if row_response.clicked() || row_response.double_clicked() {
+                            ui.memory_mut(|memory| memory.request_focus(tree_id));
+                        }
+                        if (row_response.clicked() && on_triangle) || row_response.double_clicked()
+                        {
+                            if !browser.expanded.remove(path) {
+                                browser.expanded.insert(path.clone());
+                            }
+                        } else if row_response.clicked() {
+                            browser.selected = path.clone();
+                        }
+                    }
+                });
+        });
+        // One focus stop for the whole tree, ringed while it has it
+        let tree_rect = frame_response.response.rect;
+        ui.interact(tree_rect, tree_id, egui::Sense::focusable_noninteractive());
+        if has_focus {
+            ui.painter().rect_stroke(
+                tree_rect.expand(1.0),
+                egui::Rounding::same(theme.rounding.field_ring),
+                Stroke::new(2.0, stroke_color),
+            );
+        }
+
+        ui.horizontal(|ui| {
+            ui.add_space(theme.margins.content_indent);
+            let toggled = ui.checkbox(
+                &mut browser.show_hidden,
+                egui::RichText::new(messages.visual(messages.get("show_hidden_folders")))
+                    .color(theme.colors.text)
+                    .text_style(Name("DialogBody".into())),
+            );
+            if toggled.changed() {
+                browser.listings.clear();
+            }
+        });
+
+        browser.selected != before
+    }
+}
+
+/// Make room for the open section, or give it back
+fn resize_window(ctx: &egui::Context, open: bool) {
+    let size = ctx
+        .input(|i| i.viewport().inner_rect.map(|rect| rect.size()))
+        .unwrap_or_else(|| ctx.screen_rect().size());
+    let size = if open {
+        egui::vec2(size.x.max(EXPANDED_MIN_WIDTH), size.y + EXPANDED_HEIGHT)
+    } else {
+        egui::vec2(size.x, (size.y - EXPANDED_HEIGHT).max(0.0))
+    };
+    ctx.send_viewport_cmd(egui::ViewportCommand::InnerSize(size));
+}
+
+/// A triangle pointing down when open, else towards the reading direction
+fn paint_disclosure(
+    painter: &egui::Painter,
+    rect: egui::Rect,
+    open: bool,
+    rtl: bool,
+    color: Color32,
+) {
+    let center = rect.center();
+    let half = 3.5;
+    let points = if open {
+        vec![
+            center + egui::vec2(-half, -half / 2.0),
+            center + egui::vec2(half, -half / 2.0),
+            center + egui::vec2(0.0, half),
+        ]
+    } else if rtl {
+        vec![
+            center + egui::vec2(half / 2.0, -half),
+            center + egui::vec2(half / 2.0, half),
+            center + egui::vec2(-half, 0.0),
+        ]
+    } else {
+        vec![
+            center + egui::vec2(-half / 2.0, -half),
+            center + egui::vec2(-half / 2.0, half),
+            center + egui::vec2(half, 0.0),
+        ]
+    };
+    painter.add(egui::Shape::convex_polygon(points, color, Stroke::NONE));
+}
@@ -0,0 +1,1279 @@
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use egui::Color32;
+use egui::Stroke;
+
+use egui::epaint::Margin;
+use egui::TextStyle::*;
+
+use crate::i18n::Messages;
+use crate::input_dialog::{
+    button_style, cancel_button, dialog_frame, dialog_keys, draw_focus_ring, field_frame,
+    field_ring, focus_colors, leading_align, leading_layout, list_frame, ok_button, visual_lines,
+};
+use crate::places::{self, Place};
+use crate::theme::Theme;
+use crate::validation::validate_folder_name;
+use crate::{Feedback, InputResponse, Selection};
+
+const SIDEBAR_WIDTH: f32 = 130.0;
+const SIZE_COLUMN_WIDTH: f32 = 72.0;
+const MODIFIED_COLUMN_WIDTH: f32 = 118.0;
+const TILE_SIZE: egui::Vec2 = egui::vec2(88.0, 76.0);
+
+/// A named set of file name patterns the listing can be narrowed to, e.g. "Images" for
+/// `*.png *.jpg`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FileFilter {
+    pub name: String,
+    pub patterns: Vec<String>,
+}
+
+impl FileFilter {
+    /// zenity's --file-filter syntax: `NAME | PATTERN1 PATTERN2`, or the patterns alone, which
+    /// then also name the filter
+    pub fn parse(spec: &str) -> Self {
+        let (name, patterns) = match spec.rsplit_once('|') {
+            Some((name, patterns)) => (name.trim(), patterns),
+            None => (spec.trim(), spec),
+        };
+        Self {
+            name: name.to_owned(),
+            patterns: patterns.split_whitespace().map(str::to_owned).collect(),
+        }
+    }
+
+    /// Whether the file name matches one of the patterns, `*` and `?` being wildcards
+    pub fn matches(&self, name: &str) -> bool {
+        self.patterns
+            .iter()
+            .any(|pattern| matches_pattern(name, pattern))
+    }
+}
+
+/// Glob matching of a whole name, case-sensitive like GTK's file filters
+fn matches_pattern(name: &str, pattern: &str) -> bool {
+    let name: Vec<char> = name.chars().collect();
+    let pattern: Vec<char> = pattern.chars().collect();
+    let (mut n, mut p) = (0, 0);
+    // Where the last `*` was and how much of the name it took, to backtrack to
+    let mut star: Option<(usize, usize)> = None;
+    while n < name.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                star = Some((p, n));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == name[n] => {
+                n += 1;
+                p += 1;
+            }
+            _ => match star {
+                Some((star_p, star_n)) => {
+                    star = Some((star_p, star_n + 1));
+                    p = star_p + 1;
+                    n = star_n + 1;
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+/// What the listing is sorted by
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SortKey {
+    #[default]
+    Name,
+    Size,
+    Modified,
+}
+
+/// How the listing is shown
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum View {
+    /// Rows with the size and modification date
+    #[default]
+    List,
+    /// Tiles with a big icon
+    Grid,
+}
+
+/// A file or folder of the listed folder
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FileEntry {
+    pub path: PathBuf,
+    pub name: String,
+    pub is_dir: bool,
+    pub size: u64,
+    pub modified: Option<SystemTime>,
+}
+
+/// What the file chooser remembers between frames: the listed folder, the chosen entries, the
+/// name typed to save as and how the listing is shown
+#[derive(Clone, Debug, Default)]
+pub struct FileBrowser {
+    directory: PathBuf,
+    /// Every entry of the folder, hidden ones included, in no particular order
+    entries: Vec<FileEntry>,
+    /// Why the folder couldn't be listed
+    error: Option<String>,
+    selected: BTreeSet<PathBuf>,
+    file_name: String,
+    sort: SortKey,
+    descending: bool,
+    view: View,
+    show_hidden: bool,
+    filters: Vec<FileFilter>,
+    filter: usize,
+    /// Folders are chosen instead of files, which aren't listed
+    folders_only: bool,
+    /// Sidebar sections by catalog key, read the first time they are shown
+    places: Option<Vec<(&'static str, Vec<Place>)>>,
+    scroll_to_selected: bool,
+}
+
+impl FileBrowser {
+    /// Browser listing `start`, or its folder with `start` chosen when it is a file (or a file
+    /// to be created)
+    pub fn new(start: &Path) -> Self {
+        let mut browser = Self::default();
+        if start.is_dir() {
+            browser.open(start);
+        } else {
+            let directory = match start.parent() {
+                Some(parent) if !parent.as_os_str().is_empty() => parent.to_owned(),
+                _ => PathBuf::from("."),
+            };
+            browser.open(&directory);
+            if let Some(name) = start.file_name() {
+                browser.file_name = name.to_string_lossy().into_owned();
+                browser.select_only(&browser.directory.join(name));
+            }
+        }
+        browser
+    }
+
+    /// List `directory`, dropping the chosen entries. Relative paths are made absolute, links
+    /// resolved.
+    pub fn open(&mut self, directory: &Path) {
+        self.directory = std::fs::canonicalize(directory).unwrap_or_else(|_| directory.to_owned());
+        self.selected.clear();
+        self.refresh();
+    }
+
+    /// Read the listed folder again, e.g. after a folder was created in it
+    pub fn refresh(&mut self) {
+        self.entries.clear();
+        self.error = None;
+        match std::fs::read_dir(&self.directory) {
+            Ok(entries) => {
+                for entry in entries.filter_map(Result::ok) {
+                    let path = entry.path();
+                    // Links count as what they point to, broken ones as themselves
+                    let metadata = std::fs::metadata(&path).or_else(|_| entry.metadata());
+                    let Ok(metadata) = metadata else { continue };
+                    self.entries.push(FileEntry {
+                        name: entry.file_name().to_string_lossy().into_owned(),
+                        is_dir: metadata.is_dir(),
+                        size: metadata.len(),
+                        modified: metadata.modified().ok(),
+                        path,
+                    });
+                }
+            }
+            Err(error) => self.error = Some(error.to_string()),
+        }
+        // Entries that went away can't stay chosen
+        let entries = &self.entries;
+        self.selected
+            .retain(|path| entries.iter().any(|entry| entry.path == *path));
+    }
+
+    /// The listed folder
+    pub fn directory(&self) -> &Path {
+        &self.directory
+    }
+
+    /// Setter for the filters offered, the first one applying; none lists every file
+    pub fn set_filters(&mut self, filters: Vec<FileFilter>) {
+        self.filters = filters;
+        self.filter = 0;
+    }
+
+    /// Setter for whether a folder is chosen instead of files
+    pub fn set_folders_only(&mut self, folders_only: bool) {
+        self.folders_only = folders_only;
+    }
+
+    /// Whether a folder is chosen instead of files
+    pub fn folders_only(&self) -> bool {
+        self.folders_only
+    }
+
+    /// Choose `path` alone, scrolling to it
+    pub fn select_only(&mut self, path: &Path) {
+        self.selected = BTreeSet::from([path.to_owned()]);
+        self.scroll_to_selected = true;
+    }
+
+    /// The chosen files, folders left out, in name order
+    pub fn chosen_files(&self) -> Vec<PathBuf> {
+        self.visible()
+            .into_iter()
+            .filter(|entry| !entry.is_dir && self.selected.contains(&entry.path))
+            .map(|entry| entry.path.clone())
+            .collect()
+    }
+
+    /// The chosen folder, or the listed one when none or several are chosen
+    pub fn chosen_folder(&self) -> PathBuf {
+        let mut chosen = self
+            .visible()
+            .into_iter()
+            .filter(|entry| entry.is_dir && self.selected.contains(&entry.path));
+        match (chosen.next(), chosen.next()) {
+            (Some(entry), None) => entry.path.clone(),
+            _ => self.directory.clone(),
+        }
+    }
+
+    /// The name typed to save as
+    pub fn file_name(&self) -> &str {
+        &self.file_name
+    }
+
+    /// The entries shown: hidden ones only if asked for, files only if they pass the filter and
+    /// folders aren't chosen, folders first and then in the sort order
+    fn visible(&self) -> Vec<&FileEntry> {
+        let filter = self.filters.get(self.filter);
+        let mut visible: Vec<&FileEntry> = self
+            .entries
+            .iter()
+            .filter(|entry| self.show_hidden || !entry.name.starts_with('.'))
+            .filter(|entry| {
+                entry.is_dir
+                    || (!self.folders_only
+                        && filter.map_or(true, |filter| filter.matches(&entry.name)))
+            })
+            .collect();
+        visible.sort_by(|a, b| {
+            let ordering = match self.sort {
+                SortKey::Name => a.name.to_lowercase().cmp(&b.name.to_lowercase()),
+                SortKey::Size => a.size.cmp(&b.size),
+                SortKey::Modified => a.modified.cmp(&b.modified),
+            };
+            let ordering = if self.descending {
+                ordering.reverse()
+            } else {
+                ordering
+            };
+            b.is_dir.cmp(&a.is_dir).then(ordering)
+        });
+        visible
+    }
+
+    /// Sort by `key`, reversing the order when it already is
+    fn sort_by(&mut self, key: SortKey) {
+        self.descending = self.sort == key && !self.descending;
+        self.sort = key;
+    }
+}
+
+/// A file chooser filling the window, in the look of [`crate::InputDialog`]: places in a sidebar
+/// (Home, the XDG user directories, mounted volumes and GTK bookmarks), a breadcrumb of the
+/// listed folder, a sortable list or a grid of its entries, the filters and a New Folder button.
+///
+/// In save mode a field takes the name to save as. Double clicking a folder opens it, Backspace
+/// goes to the parent folder, Up and Down move through the listing.
+pub struct FileChooser<'a> {
+    browser: &'a mut FileBrowser,
+    heading: String,
+    save: bool,
+    multiple: bool,
+    ok_label: String,
+    cancel_label: String,
+    feedback: Feedback,
+}
+
+impl<'a> FileChooser<'a> {
+    /// Chooser titled `heading` browsing with `browser`, to open one file
+    pub fn new(heading: impl Into<String>, browser: &'a mut FileBrowser) -> Self {
+        Self {
+            browser,
+            heading: heading.into(),
+            save: false,
+            multiple: false,
+            ok_label: "Open".to_owned(),
+            cancel_label: "Cancel".to_owned(),
+            feedback: Feedback::None,
+        }
+    }
+
+    /// Setter for whether a name is typed to save as, instead of choosing existing files
+    pub fn with_save(mut self, save: bool) -> Self {
+        self.save = save;
+        self
+    }
+
+    /// Setter for whether several files can be opened, Ctrl/Cmd+click adding to the choice
+    pub fn with_multiple(mut self, multiple: bool) -> Self {
+        self.multiple = multiple;
+        self
+    }
+
+    /// Setter for the default button label
+    pub fn with_ok_label(mut self, label: impl Into<String>) -> Self {
+        self.ok_label = label.into();
+        self
+    }
+
+    /// Setter for the Cancel button label
+    pub fn with_cancel_label(mut self, label: impl Into<String>) -> Self {
+        self.cancel_label = label.into();
+        self
+    }
+
+    /// Setter for what to show under the name to save as, e.g. why it was refused
+    pub fn with_feedback(mut self, feedback: Feedback) -> Self {
+        self.feedback = feedback;
+        self
+    }
+
+    /// Lay the chooser out in the central panel and report what the user did: the New Folder
+    /// button is [`InputResponse::action_clicked`]
+    pub fn show(self, ctx: &egui::Context, theme: &Theme, messages: &Messages) -> InputResponse {
+        let mut response = InputResponse::default();
+        let rtl = messages.is_rtl();
+        let browser = self.browser;
+        let save = self.save;
+        let folders_only = browser.folders_only;
+        let multiple = self.multiple && !save && !folders_only;
+        let name_id = egui::Id::new("file_chooser_name");
+        let focus_requested_id = name_id.with("focus_requested");
+        let name_has_focus = ctx.memory(|memory| memory.has_focus(name_id));
+
+        let (return_pressed, cancel_pressed) = dialog_keys(ctx);
+        let (up_pressed, down_pressed, parent_pressed) = ctx.input_mut(|i| {
+            let up_pressed = i.consume_key(egui::Modifiers::NONE, egui::Key::ArrowUp);
+            let down_pressed = i.consume_key(egui::Modifiers::NONE, egui::Key::ArrowDown);
+            // Backspace edits the name while the field has focus
+            let parent_pressed = i.consume_key(egui::Modifiers::ALT, egui::Key::ArrowUp)
+                || (!name_has_focus && i.consume_key(egui::Modifiers::NONE, egui::Key::Backspace));
+            (up_pressed, down_pressed, parent_pressed)
+        });
+        let (stroke_color, selection_color, ok_button_color) = focus_colors(ctx, theme);
+
+        if parent_pressed {
+            if let Some(parent) = browser.directory.parent().map(Path::to_owned) {
+                let child = browser.directory.clone();
+                browser.open(&parent);
+                browser.select_only(&child);
+            }
+        }
+        let visible: Vec<FileEntry> = browser.visible().into_iter().cloned().collect();
+        if up_pressed || down_pressed {
+            let current = visible
+                .iter()
+                .position(|entry| browser.selected.contains(&entry.path));
+            let next = match current {
+                Some(position) if up_pressed => position.saturating_sub(1),
+                Some(position) => (position + 1).min(visible.len().saturating_sub(1)),
+                None => 0,
+            };
+            if let Some(entry) = visible.get(next) {
+                browser.select_only(&entry.path);
+                if save && !entry.is_dir {
+                    browser.file_name = entry.name.clone();
+                }
+            }
+        }
+
+        // A lone folder is opened by the default button, like in GTK and Finder, unless folders
+        // are chosen
+        let chosen_folder = {
+            let mut chosen = visible
+                .iter()
+                .filter(|entry| browser.selected.contains(&entry.path));
+            match (chosen.next(), chosen.next()) {
+                (Some(entry), None) if entry.is_dir => Some(entry.path.clone()),
+                _ => None,
+            }
+        };
+        // The name to save as is checked like a new folder name, it must stay in the listed folder
+        let name_error = save
+            .then(|| validate_folder_name(browser.file_name.trim()).err())
+            .flatten();
+        let can_confirm = if save {
+            name_error.is_none()
+        } else {
+            folders_only || chosen_folder.is_some() || !browser.chosen_files().is_empty()
+        };
+        // Nothing typed yet needs no explanation, the default button is disabled
+        let error_message = match (&self.feedback, name_error) {
+            (_, Some(error)) if !browser.file_name.trim().is_empty() => {
+                Some(messages.name_error(error))
+            }
+            (Feedback::Invalid(message) | Feedback::Failed(message), _) => Some(message.clone()),
+            _ => None,
+        };
+
+        let text_box_frame = field_frame(theme, rtl);
+        let text_box_stroke = field_ring(
+            theme,
+            rtl,
+            if name_has_focus {
+                stroke_color
+            } else {
+                Color32::TRANSPARENT
+            },
+        );
+        let list_frame = list_frame(theme, Margin::ZERO, theme.colors.background);
+
+        let mut double_clicked: Option<FileEntry> = None;
+        egui::CentralPanel::default()
+            .frame(dialog_frame(theme, rtl))
+            .show(ctx, |ui| {
+                ui.with_layout(egui::Layout::top_down(leading_align(rtl)), |ui| {
+                    ui.horizontal(|ui| {
+                        ui.add_space(theme.margins.content_indent);
+                        ui.add(
+                            egui::Label::new(
+                                egui::RichText::new(messages.visual(&self.heading))
+                                    .color(theme.colors.text)
+                                    .text_style(Name("DialogHeading".into())),
+                            )
+                            .selectable(false),
+                        );
+                    });
+                    ui.add_space(4.5);
+
+                    // The name to save as, in the field of the New Folder dialog, its stem
+                    // selected so typing keeps the extension
+                    if save {
+                        ui.horizontal(|ui| {
+                            ui.add_space(theme.margins.content_indent);
+                            ui.add(
+                                egui::Label::new(
+                                    egui::RichText::new(
+                                        messages.visual(messages.get("save_as_label")),
+                                    )
+                                    .color(theme.colors.text)
+                                    .text_style(Name("DialogBody".into())),
+                                )
+                                .selectable(false),
+                            );
+                            text_box_stroke.show(ui, |ui| {
+                                text_box_frame.show(ui, |ui| {
+                                    ui.visuals_mut().selection.bg_fill = selection_color;
+                                    let mut output =
+                                        egui::TextEdit::singleline(&mut browser.file_name)
+                                            .id(name_id)
+                                            .desired_width(f32::INFINITY)
+                                            .font(Name("TextInputBody".into()))
+                                            .margin(theme.margins.field_text)
+                                            .horizontal_align(leading_align(rtl))
+                                            .background_color(theme.colors.field_fill)
+                                            .text_color(theme.colors.field_text)
+                                            .frame(false)
+                                            .show(ui);
+                                    let focus_requested = ui.data_mut(|data| {
+                                        std::mem::replace(
+                                            data.get_temp_mut_or(focus_requested_id, false),
+                                            true,
+                                        )
+                                    });
+                                    if !focus_requested {
+                                        output.response.request_focus();
+                                    }
+                                    if output.response.gained_focus() {
+                                        use egui::{text::CCursor, text::CCursorRange};
+                                        let range = Selection::stem(&browser.file_name)
+                                            .char_range(&browser.file_name);
+                                        output.state.cursor.set_char_range(Some(
+                                            CCursorRange::two(
+                                                CCursor::new(range.start),
+                                                CCursor::new(range.end),
+                                            ),
+                                        ));
+                                        output.state.store(ui.ctx(), output.response.id);
+                                    }
+                                    response.changed |= output.response.changed();
+                                });
+                            });
+                        });
+                        // Kept inline so the user can fix the name and retry
+                        if let Some(message) = &error_message {
+                            ui.add_space(2.);
+                            ui.horizontal(|ui| {
+                                ui.add_space(theme.margins.content_indent);
+                                ui.add(
+                                    egui::Label::new(
+                                        egui::RichText::new(visual_lines(
+                                            ui,
+                                            messages,
+                                            message,
+                                            &Name("DialogBody".into()),
+                                        ))
+                                        .color(theme.colors.error)
+                                        .text_style(Name("DialogBody".into())),
+                                    )
+                                    .wrap()
+                                    .selectable(false),
+                                );
+                            });
+                        }
+                        ui.add_space(4.);
+                    }
+
+                    // Buttons first, from the bottom, so the listing takes the height left between
+                    ui.with_layout(egui::Layout::bottom_up(leading_align(rtl)), |ui| {
+                        ui.with_layout(leading_layout(!rtl, egui::Align::BOTTOM), |ui| {
+                            ui.add_space(3.);
+
+                            button_style(ui, theme);
+
+                            let ok_button = ok_button(
+                                &self.ok_label,
+                                ok_button_color,
+                                can_confirm,
+                                theme,
+                                messages,
+                            );
+                            let ok_response = ui.add_enabled(can_confirm, ok_button);
+                            draw_focus_ring(ui, &ok_response, stroke_color, theme);
+                            response.confirmed = ok_response.clicked();
+
+                            ui.add_space(0.1); // Space between buttons
+
+                            let cancel_response =
+                                ui.add(cancel_button(&self.cancel_label, theme, messages));
+                            draw_focus_ring(ui, &cancel_response, stroke_color, theme);
+                            response.cancelled = cancel_response.clicked();
+
+                            // New Folder sits on the other side
+                            ui.with_layout(leading_layout(rtl, egui::Align::BOTTOM), |ui| {
+                                let new_folder_button = cancel_button(
+                                    messages.get("new_folder_button"),
+                                    theme,
+                                    messages,
+                                );
+                                let new_folder_response = ui.add(new_folder_button);
+                                draw_focus_ring(ui, &new_folder_response, stroke_color, theme);
+                                response.action_clicked = new_folder_response.clicked();
+                            });
+                        });
+                        ui.add_space(6.);
+
+                        // How the listing is shown
+                        ui.horizontal(|ui| {
+                            ui.add_space(theme.margins.content_indent);
+                            for (view, key) in
+                                [(View::List, "view_list"), (View::Grid, "view_grid")]
+                            {
+                                let label = egui::RichText::new(messages.visual(messages.get(key)))
+                                    .color(theme.colors.text)
+                                    .text_style(Name("DialogBody".into()));
+                                if ui.selectable_label(browser.view == view, label).clicked() {
+                                    browser.view = view;
+                                }
+                            }
+                            ui.add_space(8.);
+                            if !browser.filters.is_empty() && !folders_only {
+                                let names: Vec<String> = browser
+                                    .filters
+                                    .iter()
+                                    .map(|filter| filter.name.clone())
+                                    .collect();
+                                egui::ComboBox::from_id_salt("file_chooser_filter")
+                                    .selected_text(
+                                        messages
+                                            .visual(&names[browser.filter.min(names.len() - 1)]),
+                                    )
+                                    .show_ui(ui, |ui| {
+                                        for (index, name) in names.iter().enumerate() {
+                                            ui.selectable_value(
+                                                &mut browser.filter,
+                                                index,
+                                                messages.visual(name),
+                                            );
+                                        }
+                                    });
+                                ui.add_space(8.);
+                            }
+                            ui.checkbox(
+                                &mut browser.show_hidden,
+                                egui::RichText::new(
+                                    messages.visual(messages.get("show_hidden_files")),
+                                )
+                                .color(theme.colors.text)
+                                .text_style(Name("DialogBody".into())),
+                            );
+                        });
+                        ui.add_space(4.);
+
+                        ui.with_layout(egui::Layout::top_down(leading_align(rtl)), |ui| {
+                            show_breadcrumb(ui, browser, theme, messages, rtl);
+                            ui.add_space(2.);
+                            ui.horizontal_top(|ui| {
+                                ui.add_space(theme.margins.content_indent);
+                                let height = ui.available_height();
+                                ui.allocate_ui_with_layout(
+                                    egui::vec2(SIDEBAR_WIDTH, height),
+                                    egui::Layout::top_down(leading_align(rtl)),
+                                    |ui| {
+                                        ui.set_min_size(egui::vec2(SIDEBAR_WIDTH, height));
+                                        show_places(ui, browser, theme, messages);
+                                    },
+                                );
+                                list_frame.show(ui, |ui| {
+                                    ui.set_min_size(ui.available_size());
+                                    if let Some(error) = &browser.error {
+                                        ui.label(
+                                            egui::RichText::new(messages.visual(
+                                                &messages.format(
+                                                    "error_list_folder",
+                                                    &[("error", error)],
+                                                ),
+                                            ))
+                                            .color(theme.colors.error)
+                                            .text_style(Name("DialogBody".into())),
+                                        );
+                                    } else if visible.is_empty() {
+                                        ui.label(
+                                            egui::RichText::new(
+                                                messages.visual(messages.get("empty_folder")),
+                                            )
+                                            .color(theme.colors.disabled_text)
+                                            .text_style(Name("DialogBody".into())),
+                                        );
+                                    } else {
+                                        let mut listing = Listing {
+                                            browser: &mut *browser,
+                                            visible: &visible,
+                                            multiple,
+                                            save,
+                                            selection_color,
+                                            rtl,
+                                            theme,
+                                            messages,
+                                        };
+                                        double_clicked = match listing.browser.view {
+                                            View::List => listing.show_list(ui),
+                                            View::Grid => listing.show_grid(ui),
+                                        };
+                                    }
+                                });
+                            });
+                        });
+                    });
+                });
+            });
+
+        // Double clicks open folders and choose files
+        if let Some(entry) = &double_clicked {
+            if entry.is_dir {
+                browser.open(&entry.path);
+            } else {
+                browser.select_only(&entry.path);
+                if save {
+                    browser.file_name = entry.name.clone();
+                }
+                response.confirmed = true;
+            }
+        }
+
+        if cancel_pressed {
+            response.cancelled = true;
+        } else if return_pressed && can_confirm {
+            response.confirmed = true;
+        }
+        // A lone folder, or a name of a folder to save in, is opened instead of chosen
+        let save_folder = (save && name_error.is_none())
+            .then(|| browser.directory.join(browser.file_name.trim()))
+            .filter(|path| path.is_dir());
+        if response.confirmed && double_clicked.is_none() {
+            // Choosing a folder takes it as it is
+            let open_folder = chosen_folder.filter(|_| !save && !folders_only);
+            if let Some(folder) = open_folder.or(save_folder) {
+                browser.open(&folder);
+                if save {
+                    browser.file_name.clear();
+                }
+                response.confirmed = false;
+            }
+        }
+        response
+    }
+}
+
+/// The listed folder's path, each part opening that folder
+fn show_breadcrumb(
+    ui: &mut egui::Ui,
+    browser: &mut FileBrowser,
+    theme: &Theme,
+    messages: &Messages,
+    rtl: bool,
+) {
+    ui.horizontal(|ui| {
+        ui.add_space(theme.margins.content_indent);
+        egui::ScrollArea::horizontal()
+            .id_salt("file_chooser_breadcrumb")
+            .stick_to_right(true)
+            .show(ui, |ui| {
+                ui.with_layout(leading_layout(rtl, egui::Align::Center), |ui| {
+                    let ancestors: Vec<PathBuf> =
+                        browser.directory.ancestors().map(Path::to_owned).collect();
+                    for (index, ancestor) in ancestors.iter().rev().enumerate() {
+                        if index > 0 {
+                            ui.label(
+                                egui::RichText::new(if rtl { "‹" } else { "›" })
+                                    .color(theme.colors.disabled_text),
+                            );
+                        }
+                        let crumb = ui.add(
+                            egui::Button::new(
+                                egui::RichText::new(messages.visual(&display_name(ancestor)))
+                                    .color(theme.colors.text)
+                                    .text_style(Name("DialogBody".into())),
+                            )
+                            .frame(false),
+                        );
+                        if crumb.clicked() {
+                            let child = browser.directory.clone();
+                            browser.open(ancestor);
+                            if child.parent() == Some(ancestor.as_path()) {
+                                browser.select_only(&child);
+                            }
+                        }
+                    }
+                });
+            });
+    });
+}
+
+/// The sidebar: Home and the user directories, mounted volumes and bookmarks, each opening
+/// its folder
+fn show_places(ui: &mut egui::Ui, browser: &mut FileBrowser, theme: &Theme, messages: &Messages) {
+    if browser.places.is_none() {
+        let home = places::home_dir().map(|path| Place {
+            name: messages.get("home").to_owned(),
+            path,
+        });
+        browser.places = Some(vec![
+            (
+                "places_heading",
+                home.into_iter().chain(places::user_dirs()).collect(),
+            ),
+            ("devices_heading", places::mounts()),
+            ("bookmarks_heading", places::bookmarks()),
+        ]);
+    }
+    let sections = browser.places.clone().unwrap_or_default();
+    egui::ScrollArea::vertical()
+        .id_salt("file_chooser_places")
+        .auto_shrink([false, false])
+        .show(ui, |ui| {
+            for (key, places) in sections.iter().filter(|(_, places)| !places.is_empty()) {
+                ui.add(
+                    egui::Label::new(
+                        egui::RichText::new(messages.visual(messages.get(key)))
+                            .color(theme.colors.disabled_text)
+                            .text_style(Name("DialogBody".into()))
+                            .small(),
+                    )
+                    .selectable(false),
+                );
+                for place in places {
+                    let label = egui::RichText::new(messages.visual(&place.name))
+                        .color(theme.colors.text)
+                        .text_style(Name("DialogBody".into()));
+                    if ui
+                        .selectable_label(browser.directory == place.path, label)
+                        .on_hover_text(place.path.display().to_string())
+                        .clicked()
+                    {
+                        browser.open(&place.path);
+                    }
+                }
+                ui.add_space(6.);
+            }
+        });
+}
+
+/// The entries of the listed folder, as rows or tiles
+struct Listing<'a> {
+    browser: &'a mut FileBrowser,
+    visible: &'a [FileEntry],
+    multiple: bool,
+    save: bool,
+    selection_color: Color32,
+    rtl: bool,
+    theme: &'a Theme,
+    messages: &'a Messages,
+}
+
+impl Listing<'_> {
+    const CELL_PADDING: f32 = 6.0;
+
+    /// Rows under a header sorting by a column on click, returning the double clicked entry
+    fn show_list(&mut self, ui: &mut egui::Ui) -> Option<FileEntry> {
+        let font_id = Name("DialogBody".into()).resolve(ui.style());
+        let row_height = ui.fonts(|fonts| fonts.row_height(&font_id)) + 6.0;
+        let width = ui.available_width();
+        let name_width = (width - SIZE_COLUMN_WIDTH - MODIFIED_COLUMN_WIDTH).max(80.0);
+        let widths = [name_width, SIZE_COLUMN_WIDTH, MODIFIED_COLUMN_WIDTH];
+
+        let (header_rect, _) =
+            ui.allocate_exact_size(egui::vec2(width, row_height), egui::Sense::hover());
+        ui.painter()
+            .rect_filled(header_rect, 0.0, self.theme.colors.field_fill);
+        let keys = [
+            (SortKey::Name, "column_name"),
+            (SortKey::Size, "column_size"),
+            (SortKey::Modified, "column_modified"),
+        ];
+        for ((key, label), rect) in keys.into_iter().zip(self.cell_rects(header_rect, &widths)) {
+            let cell_response = ui.interact(
+                rect,
+                ui.id().with(("file_chooser_header", label)),
+                egui::Sense::click(),
+            );
+            if cell_response.clicked() {
+                self.browser.sort_by(key);
+            }
+            let arrow = match (self.browser.sort == key, self.browser.descending) {
+                (true, false) => " ▲",
+                (true, true) => " ▼",
+                (false, _) => "",
+            };
+            self.paint_text(
+                ui,
+                rect,
+                &format!("{}{arrow}", self.messages.get(label)),
+                self.theme.colors.text,
+            );
+        }
+
+        let mut double_clicked = None;
+        egui::ScrollArea::vertical()
+            .id_salt("file_chooser_list")
+            .auto_shrink([false, false])
+            .show_rows(ui, row_height, self.visible.len(), |ui, range| {
+                ui.spacing_mut().item_spacing.y = 0.0;
+                for position in range {
+                    let entry = &self.visible[position];
+                    let (rect, row_response) =
+                        ui.allocate_exact_size(egui::vec2(width, row_height), egui::Sense::click());
+                    let selected = self.browser.selected.contains(&entry.path);
+                    let fill = if selected {
+                        self.selection_color
+                    } else if position % 2 == 1 {
+                        self.theme.colors.field_fill
+                    } else {
+                        Color32::TRANSPARENT
+                    };
+                    ui.painter().rect_filled(rect, 0.0, fill);
+                    if selected && std::mem::take(&mut self.browser.scroll_to_selected) {
+                        ui.scroll_to_rect(rect, None);
+                    }
+
+                    let cells = self.cell_rects(rect, &widths);
+                    let icon_size = egui::vec2(14.0, 12.0);
+                    let icon_rect = if self.rtl {
+                        egui::Rect::from_center_size(
+                            egui::pos2(
+                                cells[0].right() - Self::CELL_PADDING - icon_size.x / 2.0,
+                                rect.center().y,
+                            ),
+                            icon_size,
+                        )
+                    } else {
+                        egui::Rect::from_center_size(
+                            egui::pos2(
+                                cells[0].left() + Self::CELL_PADDING + icon_size.x / 2.0,
+                                rect.center().y,
+                            ),
+                            icon_size,
+                        )
+                    };
+                    paint_icon(ui.painter(), icon_rect, entry.is_dir, self.theme);
+                    let name_rect = if self.rtl {
+                        cells[0].with_max_x(icon_rect.left() - 2.0)
+                    } else {
+                        cells[0].with_min_x(icon_rect.right() + 2.0)
+                    };
+                    let color = if entry.name.starts_with('.') {
+                        self.theme.colors.disabled_text
+                    } else {
+                        self.theme.colors.field_text
+                    };
+                    self.paint_text(ui, name_rect, &entry.name, color);
+                    let size = if entry.is_dir {
+                        "—".to_owned()
+                    } else {
+                        format_size(entry.size)
+                    };
+                    self.paint_text(ui, cells[1], &size, self.theme.colors.disabled_text);
+                    let modified = entry.modified.map(format_time).unwrap_or_default();
+                    self.paint_text(ui, cells[2], &modified, self.theme.colors.disabled_text);
+
+                    self.handle_click(ui, &row_response, entry, &mut double_clicked);
+                }
+            });
+        double_clicked
+    }
+
+    /// Tiles wrapped in rows, returning the double clicked entry
+    fn show_grid(&mut self, ui: &mut egui::Ui) -> Option<FileEntry> {
+        let font_id = Name("DialogBody".into()).resolve(ui.style());
+        let mut double_clicked = None;
+        egui::ScrollArea::vertical()
+            .id_salt("file_chooser_grid")
+            .auto_shrink([false, false])
+            .show(ui, |ui| {
+                ui.with_layout(
+                    egui::Layout::left_to_right(egui::Align::TOP)
+                        .with_main_wrap(true)
+                        .with_main_align(if self.rtl {
+                            egui::Align::RIGHT
+                        } else {
+                            egui::Align::LEFT
+                        }),
+                    |ui| {
+                        for entry in self.visible {
+                            let (rect, tile_response) =
+                                ui.allocate_exact_size(TILE_SIZE, egui::Sense::click());
+                            let selected = self.browser.selected.contains(&entry.path);
+                            if selected {
+                                ui.painter().rect_filled(
+                                    rect.shrink(1.0),
+                                    egui::Rounding::same(4.0),
+                                    self.selection_color,
+                                );
+                                if std::mem::take(&mut self.browser.scroll_to_selected) {
+                                    ui.scroll_to_rect(rect, None);
+                                }
+                            }
+                            let icon_rect = egui::Rect::from_center_size(
+                                egui::pos2(rect.center().x, rect.top() + 22.0),
+                                egui::vec2(34.0, 28.0),
+                            );
+                            paint_icon(ui.painter(), icon_rect, entry.is_dir, self.theme);
+
+                            // The name on up to two lines under the icon
+                            let color = if entry.name.starts_with('.') {
+                                self.theme.colors.disabled_text
+                            } else {
+                                self.theme.colors.field_text
+                            };
+                            let mut job = egui::text::LayoutJob::simple(
+                                self.messages.visual(&entry.name),
+                                font_id.clone(),
+                                color,
+                                rect.width() - 6.0,
+                            );
+                            job.wrap.max_rows = 2;
+                            job.wrap.break_anywhere = true;
+                            job.halign = egui::Align::Center;
+                            let galley = ui.fonts(|fonts| fonts.layout_job(job));
+                            ui.painter().galley(
+                                egui::pos2(rect.center().x, rect.top() + 42.0),
+                                galley,
+                                color,
+                            );
+
+                            self.handle_click(ui, &tile_response, entry, &mut double_clicked);
+                        }
+                    },
+                );
+            });
+        double_clicked
+    }
+
+    /// Choose the entry on click, adding to the choice with Ctrl/Cmd when several files can be
+    /// opened; in save mode a file gives its name
+    fn handle_click(
+        &mut self,
+        ui: &egui::Ui,
+        response: &egui::Response,
+        entry: &FileEntry,
+        double_clicked: &mut Option<FileEntry>,
+    ) {
+        if response.double_clicked() {
+            *double_clicked = Some(entry.clone());
+        } else if response.clicked() {
+            let adding = self.multiple && ui.input(|i| i.modifiers.command);
+            if adding {
+                if !self.browser.selected.remove(&entry.path) {
+                    self.browser.selected.insert(entry.path.clone());
+                }
+            } else {
+                self.browser.selected = BTreeSet::from([entry.path.clone()]);
+            }
+            if self.save && !entry.is_dir {
+                self.browser.file_name = entry.name.clone();
+            }
+        }
+    }
+
+    /// Where each column of a row goes, from the leading edge
+    fn cell_rects(&self, row: egui::Rect, widths: &[f32]) -> Vec<egui::Rect> {
+        let mut offset = 0.0;
+        widths
+            .iter()
+            .map(|&width| {
+                let left = if self.rtl {
+                    row.right() - offset - width
+                } else {
+                    row.left() + offset
+                };
+                offset += width;
+                egui::Rect::from_min_size(
+                    egui::pos2(left, row.top()),
+                    egui::vec2(width, row.height()),
+                )
+            })
+            .collect()
+    }
+
+    /// Draw a cell's text, cut off at the cell edge
+    fn paint_text(&self, ui: &egui::Ui, rect: egui::Rect, text: &str, color: Color32) {
+        let font_id = Name("DialogBody".into()).resolve(ui.style());
+        let (position, align) = if self.rtl {
+            (
+                egui::pos2(rect.right() - Self::CELL_PADDING, rect.center().y),
+                egui::Align2::RIGHT_CENTER,
+            )
+        } else {
+            (
+                egui::pos2(rect.left() + Self::CELL_PADDING, rect.center().y),
+                egui::Align2::LEFT_CENTER,
+            )
+        };
+        ui.painter()
+            .with_clip_rect(
+                rect.shrink2(egui::vec2(Self::CELL_PADDING / 2.0, 0.0))
+                    .intersect(ui.clip_rect()),
+            )
+            .text(position, align, self.messages.visual(text), font_id, color);
+    }
+}
+
+/// A folder (a tab on a body, in the accent color) or a sheet of paper with a folded corner
+fn paint_icon(painter: &egui::Painter, rect: egui::Rect, is_dir: bool, theme: &Theme) {
+    if is_dir {
+        let tab = egui::Rect::from_min_size(
+            rect.min,
+            egui::vec2(rect.width() * 0.45, rect.height() * 0.3),
+        );
+        let body = egui::Rect::from_min_max(
+            egui::pos2(rect.left(), rect.top() + rect.height() * 0.18),
+            rect.max,
+        );
+        let rounding = egui::Rounding::same(rect.height() * 0.12);
+        painter.rect_filled(
+            tab,
+            rounding,
+            theme.colors.default_button.gamma_multiply(0.7),
+        );
+        painter.rect_filled(body, rounding, theme.colors.default_button);
+    } else {
+        let sheet = egui::Rect::from_center_size(
+            rect.center(),
+            egui::vec2(rect.height() * 0.78, rect.height()),
+        );
+        let fold = sheet.width() * 0.3;
+        let points = vec![
+            sheet.left_top(),
+            egui::pos2(sheet.right() - fold, sheet.top()),
+            egui::pos2(sheet.right(), sheet.top() + fold),
+            sheet.right_bottom(),
+            sheet.left_bottom(),
+        ];
+        painter.add(egui::Shape::convex_polygon(
+            points,
+            theme.colors.field_fill,
+            Stroke::new(1.0, theme.colors.field_border),
+        ));
+        painter.line_segment(
+            [
+                egui::pos2(sheet.right() - fold, sheet.top()),
+                egui::pos2(sheet.right() - fold, sheet.top() + fold),
+            ],
+            Stroke::new(1.0, theme.colors.field_border),
+        );
+        painter.line_segment(
+            [
+                egui::pos2(sheet.right() - fold, sheet.top() + fold),
+                egui::pos2(sheet.right(), sheet.top() + fold),
+            ],
+            Stroke::new(1.0, theme.colors.field_border),
+        );
+    }
+}
+
+/// The last part of a path, or the path itself for a root like "/"
+fn display_name(path: &Path) -> String {
+    path.file_name().map_or_else(
+        || path.display().to_string(),
+        |name| name.to_string_lossy().into_owned(),
+    )
+}
+
+/// "512 B", "1.2 kB", "3.4 MB": decimal units, like Finder and Files
+fn format_size(size: u64) -> String {
+    const UNITS: [&str; 5] = ["kB", "MB", "GB", "TB", "PB"];
+    if size < 1000 {
+        return format!("{size} B");
+    }
+    let mut value = size as f64 / 1000.0;
+    let mut unit = 0;
+    while value >= 1000.0 && unit + 1 < UNITS.len() {
+        value /= 1000.0;
+        unit += 1;
+    }
+    format!("{value:.1} {}", UNITS[unit])
+}
+
+/// "2026-10-18 14:05" in local time where the C library can tell it, UTC otherwise
+fn format_time(time: SystemTime) -> String {
+    // Whole seconds, rounded down also before 1970
+    let seconds = match time.duration_since(SystemTime::UNIX_EPOCH) {
+        Ok(since_epoch) => since_epoch.as_secs() as i64,
+        Err(before_epoch) => {
+            let before_epoch = before_epoch.duration();
+            -(before_epoch.as_secs() as i64) - i64::from(before_epoch.subsec_nanos() > 0)
+        }
+    };
+    #[cfg(unix)]
+    {
+        let mut local = std::mem::MaybeUninit::<libc::tm>::uninit();
+        let time = seconds as libc::time_t;
+        // SAFETY: localtime_r only writes the struct it is given, which is read once it succeeded
+        if !unsafe { libc::localtime_r(&time, local.as_mut_ptr()) }.is_null() {
+            let local = unsafe { local.assume_init() };
+            return format!(
+                "{:04}-{:02}-{:02} {:02}:{:02}",
+                local.tm_year + 1900,
+                local.tm_mon + 1,
+                local.tm_mday,
+                local.tm_hour,
+                local.tm_min
+            );
+        }
+    }
+    format_utc(seconds)
+}
+
+/// "1969-12-31 23:59" for -60: seconds since 1970 as a UTC date and time
+fn format_utc(seconds: i64) -> String {
+    // Days to a civil date, after Howard Hinnant's days_from_civil inverse
+    let (days, rest) = (seconds.div_euclid(86_400), seconds.rem_euclid(86_400));
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    format!(
+        "{year:04}-{month:02}-{day:02} {:02}:{:02}",
+        rest / 3600,
+        rest % 3600 / 60
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    #[test]
+    fn star_matches_dotfiles_too() {
+        // GTK's filters match the whole name, dots included
+        assert!(matches_pattern(".bashrc", "*"));
+        assert!(matches_pattern(".bashrc", "*rc"));
+        assert!(matches_pattern("", "*"));
+        assert!(!matches_pattern("", "?"));
+        assert!(matches_pattern("notes.txt", "*.txt"));
+        assert!(!matches_pattern("notes.txt.bak", "*.txt"));
+        assert!(!matches_pattern("Notes.TXT", "*.txt"));
+        assert!(matches_pattern("a.c", "?.?"));
+    }
+
+    #[test]
+    fn stars_backtrack() {
+        assert!(matches_pattern("abc", "a*b*c"));
+        assert!(matches_pattern("axxbyybzzc", "a*b*c"));
+        assert!(matches_pattern("abcbc", "a*b*c"));
+        assert!(matches_pattern("abcc", "a*b*c"));
+        assert!(!matches_pattern("abcb", "a*b*c"));
+        assert!(!matches_pattern("acb", "a*b*c"));
+        assert!(matches_pattern("a**c", "a**c"));
+    }
+
+    #[test]
+    fn filters_parse_like_zenity() {
+        assert_eq!(
+            FileFilter::parse("Images | *.png *.jpg"),
+            FileFilter {
+                name: "Images".into(),
+                patterns: vec!["*.png".into(), "*.jpg".into()],
+            }
+        );
+        assert_eq!(
+            FileFilter::parse(" *.txt  *.md "),
+            FileFilter {
+                name: "*.txt  *.md".into(),
+                patterns: vec!["*.txt".into(), "*.md".into()],
+            }
+        );
+        // The last bar separates the patterns
+        assert_eq!(FileFilter::parse("A | B | *.ab").patterns, ["*.ab"]);
+
+        let empty = FileFilter::parse("");
+        assert_eq!(empty.name, "");
+        assert!(empty.patterns.is_empty());
+        assert!(!empty.matches("anything"));
+        assert!(FileFilter::parse("Nothing |").patterns.is_empty());
+    }
+
+    #[test]
+    fn sizes_use_decimal_units() {
+        assert_eq!(format_size(0), "0 B");
+        assert_eq!(format_size(999), "999 B");
+        assert_eq!(format_size(1000), "1.0 kB");
+        assert_eq!(format_size(1023), "1.0 kB");
+        assert_eq!(format_size(1024), "1.0 kB");
+        assert_eq!(format_size(1_500_000), "1.5 MB");
+        assert_eq!(format_size(u64::MAX), "18446.7 PB");
+    }
+
+    #[test]
+    fn times_before_1970() {
+        assert_eq!(format_utc(0), "1970-01-01 00:00");
+        assert_eq!(format_utc(-60), "1969-12-31 23:59");
+        assert_eq!(format_utc(-1), "1969-12-31 23:59");
+        assert_eq!(format_utc(-86_400 * 365), "1969-01-01 00:00");
+        assert_eq!(format_utc(951_782_400), "2000-02-29 00:00");
+
+        // Shown in the local time zone, which may be ahead of UTC
+        let before = format_time(SystemTime::UNIX_EPOCH - Duration::from_millis(500));
+        assert!(
+            before.starts_with("1969-12-31") || before.starts_with("1970-01-01"),
+            "{before}"
+        );
+    }
+}
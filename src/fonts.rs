@@ -0,0 +1,236 @@
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::Arc;
+
+use egui::{FontData, FontDefinitions, FontFamily};
+
+/// Environment variable with extra font directories, separated like `PATH`
+pub const FONT_DIRS_ENV: &str = "EFRAME_TEST_FONT_DIRS";
+
+/// How deep font directories are searched, /usr/share/fonts nests a few levels
+const MAX_SEARCH_DEPTH: usize = 4;
+
+/// A font family used by the dialog text styles, and where its face may be found
+struct FamilySpec {
+    /// Name of the egui family, as referenced by the text styles
+    family: &'static str,
+    /// File names looked up in the font directories, preferred first
+    file_names: &'static [&'static str],
+    /// Families asked from fontconfig when no file was found
+    fontconfig_families: &'static [&'static str],
+    /// Fontconfig weight of the face, also used for the generic sans-serif fallback
+    fontconfig_weight: &'static str,
+    /// Face shipped in `assets/`, if any, with its file name
+    bundled: Option<(&'static str, &'static [u8])>,
+}
+
+/// Languages the dialog fonts (and the egui defaults) already cover
+const LATIN_LANGUAGES: [&str; 3] = ["en", "es", "de"];
+
+const FAMILIES: [FamilySpec; 3] = [
+    FamilySpec {
+        family: "System-Text-Heavy",
+        file_names: &["SF-Pro-Text-Heavy.otf", "SF-Pro-Display-Heavy.otf"],
+        fontconfig_families: &["SF Pro Text", "SF Pro Display", "Helvetica Neue"],
+        fontconfig_weight: "heavy",
+        bundled: Some((
+            "HelveticaNeueHeavy.otf",
+            include_bytes!("../assets/HelveticaNeueHeavy.otf"),
+        )),
+    },
+    FamilySpec {
+        family: "System-Text-Medium",
+        file_names: &["SF-Pro-Text-Medium.otf"],
+        fontconfig_families: &["SF Pro Text", "Helvetica Neue"],
+        fontconfig_weight: "medium",
+        bundled: None,
+    },
+    FamilySpec {
+        family: "System-Display-Medium",
+        file_names: &["SF-Pro-Display-Medium.otf"],
+        fontconfig_families: &["SF Pro Display", "Helvetica Neue"],
+        fontconfig_weight: "medium",
+        bundled: None,
+    },
+];
+
+/// Install the dialog font families, searching `extra_dirs` before the usual font directories.
+///
+/// Each family is backed by the first face found, in order: a file in `extra_dirs` or
+/// [`FONT_DIRS_ENV`], a file in the platform font directories, one of the wanted families known
+/// to fontconfig, the face bundled in `assets/`, fontconfig's sans-serif of the same weight.
+/// The egui default fonts come last, both as the final fallback and to cover glyphs missing
+/// from the chosen face. The chosen face is logged for each family.
+///
+/// Languages whose script the default fonts lack (e.g. "ja", "ar") also get the face fontconfig
+/// picks for them, right after the chosen face of every family.
+pub fn install(ctx: &egui::Context, extra_dirs: &[PathBuf], language: &str) {
+    let mut fonts = FontDefinitions::default();
+    let default_fonts = fonts
+        .families
+        .get(&FontFamily::Proportional)
+        .cloned()
+        .unwrap_or_default();
+    let search_dirs = search_dirs(extra_dirs);
+    let script_fallback = script_face(language).map(|(font_data, source)| {
+        log::info!("Text in {} falls back to {}", language, source);
+        let key = format!("script-{language}");
+        fonts.font_data.insert(key.clone(), Arc::new(font_data));
+        key
+    });
+
+    for spec in &FAMILIES {
+        let mut family_fonts = Vec::new();
+        match load_face(spec, &search_dirs) {
+            Some((font_data, source)) => {
+                log::info!("Font family {} is backed by {}", spec.family, source);
+                let key = spec.family.to_lowercase();
+                fonts.font_data.insert(key.clone(), Arc::new(font_data));
+                family_fonts.push(key);
+            }
+            None => log::info!(
+                "Font family {} is backed by the egui default fonts",
+                spec.family
+            ),
+        }
+        family_fonts.extend(script_fallback.iter().cloned());
+        family_fonts.extend(default_fonts.iter().cloned());
+        fonts
+            .families
+            .insert(FontFamily::Name(spec.family.into()), family_fonts);
+    }
+
+    ctx.set_fonts(fonts);
+}
+
+/// First face available for `spec`, with a description of where it came from
+fn load_face(spec: &FamilySpec, search_dirs: &[PathBuf]) -> Option<(FontData, String)> {
+    for file_name in spec.file_names {
+        for dir in search_dirs {
+            if let Some(path) = find_file(dir, file_name, MAX_SEARCH_DEPTH) {
+                match std::fs::read(&path) {
+                    Ok(bytes) => {
+                        return Some((FontData::from_owned(bytes), path.display().to_string()))
+                    }
+                    Err(error) => log::warn!("Failed to read font {}: {}", path.display(), error),
+                }
+            }
+        }
+    }
+
+    // fc-match always answers, so only take its answer when it is one of the wanted families
+    let pattern = format!(
+        "{}:weight={}",
+        spec.fontconfig_families.join(","),
+        spec.fontconfig_weight
+    );
+    if let Some(face) = fontconfig_face(&pattern, Some(spec.fontconfig_families)) {
+        return Some(face);
+    }
+
+    if let Some((file_name, bytes)) = spec.bundled {
+        return Some((
+            FontData::from_static(bytes),
+            format!("the bundled {file_name}"),
+        ));
+    }
+
+    fontconfig_face(
+        &format!("sans-serif:weight={}", spec.fontconfig_weight),
+        None,
+    )
+}
+
+/// Face covering the script of `language`, for languages not written in the Latin alphabet
+fn script_face(language: &str) -> Option<(FontData, String)> {
+    if LATIN_LANGUAGES.contains(&language) {
+        return None;
+    }
+    fontconfig_face(&format!(":lang={language}:weight=medium"), None)
+}
+
+/// Load the face fontconfig matches for `pattern`, if it belongs to one of `wanted_families`
+fn fontconfig_face(pattern: &str, wanted_families: Option<&[&str]>) -> Option<(FontData, String)> {
+    let (path, index, families) = fontconfig_match(pattern)?;
+    if let Some(wanted_families) = wanted_families {
+        if !families
+            .iter()
+            .any(|family| wanted_families.contains(&family.as_str()))
+        {
+            return None;
+        }
+    }
+    match std::fs::read(&path) {
+        Ok(bytes) => {
+            let mut font_data = FontData::from_owned(bytes);
+            font_data.index = index;
+            Some((font_data, format!("{} (fontconfig)", path.display())))
+        }
+        Err(error) => {
+            log::warn!("Failed to read font {}: {}", path.display(), error);
+            None
+        }
+    }
+}
+
+/// Directories searched for font files, most specific first
+fn search_dirs(extra_dirs: &[PathBuf]) -> Vec<PathBuf> {
+    let mut dirs = extra_dirs.to_vec();
+    if let Some(paths) = std::env::var_os(FONT_DIRS_ENV) {
+        dirs.extend(std::env::split_paths(&paths));
+    }
+
+    let home = std::env::var_os("HOME").map(PathBuf::from);
+    if let Some(home) = &home {
+        dirs.push(home.join("Library/Fonts"));
+        dirs.push(home.join(".local/share/fonts"));
+        dirs.push(home.join(".fonts"));
+    }
+    dirs.push(PathBuf::from("/Library/Fonts"));
+    dirs.push(PathBuf::from("/System/Library/Fonts"));
+    dirs.push(PathBuf::from("/usr/local/share/fonts"));
+    dirs.push(PathBuf::from("/usr/share/fonts"));
+    if let Some(windir) = std::env::var_os("WINDIR") {
+        dirs.push(PathBuf::from(windir).join("Fonts"));
+    }
+    if let Some(local_app_data) = std::env::var_os("LOCALAPPDATA") {
+        dirs.push(PathBuf::from(local_app_data).join("Microsoft/Windows/Fonts"));
+    }
+    dirs
+}
+
+/// Look for `file_name` in `dir` and its subdirectories
+fn find_file(dir: &Path, file_name: &str, depth: usize) -> Option<PathBuf> {
+    let path = dir.join(file_name);
+    if path.is_file() {
+        return Some(path);
+    }
+    if depth == 0 {
+        return None;
+    }
+    std::fs::read_dir(dir)
+        .ok()?
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_type().is_ok_and(|file_type| file_type.is_dir()))
+        .find_map(|entry| find_file(&entry.path(), file_name, depth - 1))
+}
+
+/// Ask fontconfig for the best face matching `pattern`: its file, index inside it and family names
+fn fontconfig_match(pattern: &str) -> Option<(PathBuf, u32, Vec<String>)> {
+    let output = Command::new("fc-match")
+        .args(["--format=%{file}\n%{index}\n%{family}", pattern])
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let stdout = String::from_utf8(output.stdout).ok()?;
+    let mut lines = stdout.lines();
+    let path = PathBuf::from(lines.next().filter(|line| !line.is_empty())?);
+    let index = lines.next().and_then(|line| line.parse().ok()).unwrap_or(0);
+    let families = lines
+        .next()
+        .map(|line| line.split(',').map(str::to_owned).collect())
+        .unwrap_or_default();
+    Some((path, index, families))
+}
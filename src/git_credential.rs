@@ -0,0 +1,194 @@
+use std::ffi::OsString;
+use std::io::{self, BufRead, Write};
+use std::path::Path;
+
+use zeroize::Zeroizing;
+
+pub use crate::login_dialog::Credential;
+use crate::DialogOutcome;
+
+/// Answer to use instead of showing the dialog, for scripts and tests: "USERNAME:PASSWORD",
+/// or "cancel"
+pub const ANSWER_VARIABLE: &str = "EFRAME_TEST_CREDENTIAL_ANSWER";
+
+/// What git asks the helper to do
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(clap::ValueEnum))]
+pub enum Action {
+    /// Ask the user for a user name and a password
+    Get,
+    /// Remember a credential that worked; nothing is stored, the dialog asks again next time
+    Store,
+    /// Forget a credential that was rejected; nothing is stored
+    Erase,
+}
+
+/// The `key=value` attributes git sends, in order
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Request {
+    pub attributes: Vec<(String, String)>,
+}
+
+impl Request {
+    /// Last value of the attribute `key`
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .rev()
+            .find(|(name, _)| name == key)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// What the credential is for: the url, or "protocol://host/path" from its parts
+    pub fn target(&self) -> String {
+        if let Some(url) = self.get("url") {
+            return url.to_owned();
+        }
+        let mut target = match (self.get("protocol"), self.get("host")) {
+            (Some(protocol), Some(host)) => format!("{protocol}://{host}"),
+            (None, Some(host)) => host.to_owned(),
+            (Some(protocol), None) => format!("{protocol}://"),
+            (None, None) => String::new(),
+        };
+        if let Some(path) = self.get("path") {
+            target = format!("{target}/{path}");
+        }
+        target
+    }
+}
+
+/// Whether the binary was started as a git credential helper through a symlink: git runs
+/// `git-credential-NAME` for `credential.helper = NAME`
+pub fn is_invocation(args: &[OsString]) -> bool {
+    args.first()
+        .and_then(|program| Path::new(program).file_stem())
+        .is_some_and(|stem| stem.to_string_lossy().starts_with("git-credential-"))
+}
+
+/// The answer set in [`ANSWER_VARIABLE`], if any
+pub fn canned_answer() -> Option<DialogOutcome<Credential>> {
+    let answer = Zeroizing::new(std::env::var(ANSWER_VARIABLE).ok()?);
+    Some(match answer.split_once(':') {
+        Some((username, password)) => DialogOutcome::Confirmed(Credential {
+            username: username.to_owned(),
+            password: Zeroizing::new(password.to_owned()),
+        }),
+        None if answer.as_str() == "cancel" => DialogOutcome::Cancelled,
+        None => DialogOutcome::Error(format!(
+            "{ANSWER_VARIABLE} is neither USERNAME:PASSWORD nor cancel"
+        )),
+    })
+}
+
+/// Read the attributes git sends on `input`, up to an empty line or the end of input
+pub fn read_request(input: impl BufRead) -> io::Result<Request> {
+    let mut request = Request::default();
+    for line in input.lines() {
+        let line = line?;
+        if line.is_empty() {
+            break;
+        }
+        match line.split_once('=') {
+            Some((key, value)) => request.attributes.push((key.to_owned(), value.to_owned())),
+            None => log::warn!("Ignoring credential line without '=': {line:?}"),
+        }
+    }
+    Ok(request)
+}
+
+/// Answer one helper invocation: read the request from `input` and, for `get`, call `ask` to
+/// show the dialog and write the credential on `output`. A cancelled dialog stops git from
+/// asking further; a failed one lets it fall back to its own prompt.
+pub fn serve(
+    action: Action,
+    input: impl BufRead,
+    output: &mut impl Write,
+    ask: impl FnOnce(&Request) -> DialogOutcome<Credential>,
+) -> io::Result<()> {
+    let request = read_request(input)?;
+    if action != Action::Get {
+        // Nothing is remembered, so there is nothing to store or erase
+        return Ok(());
+    }
+    if request.get("username").is_some() && request.get("password").is_some() {
+        return Ok(());
+    }
+    match ask(&request) {
+        DialogOutcome::Confirmed(credential) => {
+            writeln!(output, "username={}", credential.username)?;
+            // Piecewise, so no copy of the password is left behind
+            output.write_all(b"password=")?;
+            output.write_all(credential.password.as_bytes())?;
+            output.write_all(b"\n")?;
+        }
+        DialogOutcome::Error(message) => log::error!("{message}"),
+        DialogOutcome::Cancelled | DialogOutcome::Dismissed | DialogOutcome::TimedOut => {
+            writeln!(output, "quit=1")?;
+        }
+    }
+    output.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+
+    /// What `serve` writes for `action` and `input`, with `ask` answering the dialog
+    fn served(
+        action: Action,
+        input: &str,
+        ask: impl FnOnce(&Request) -> DialogOutcome<Credential>,
+    ) -> String {
+        let mut output = Vec::new();
+        serve(action, Cursor::new(input), &mut output, ask).unwrap();
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn get_answers_with_the_credential() {
+        let output = served(
+            Action::Get,
+            "protocol=https\nhost=example.com\n\n",
+            |request| {
+                assert_eq!(request.target(), "https://example.com");
+                DialogOutcome::Confirmed(Credential {
+                    username: "user".to_owned(),
+                    password: Zeroizing::new("secret".to_owned()),
+                })
+            },
+        );
+        assert_eq!(output, "username=user\npassword=secret\n");
+    }
+
+    #[test]
+    fn cancel_stops_git_asking() {
+        let output = served(Action::Get, "host=example.com\n\n", |_| {
+            DialogOutcome::Cancelled
+        });
+        assert_eq!(output, "quit=1\n");
+    }
+
+    #[test]
+    fn complete_credential_is_not_asked_for() {
+        let output = served(
+            Action::Get,
+            "host=example.com\nusername=user\npassword=secret\n\n",
+            |_| panic!("the dialog was shown"),
+        );
+        assert_eq!(output, "");
+    }
+
+    #[test]
+    fn store_and_erase_write_nothing() {
+        for action in [Action::Store, Action::Erase] {
+            let output = served(
+                action,
+                "host=example.com\nusername=user\npassword=secret\n\n",
+                |_| panic!("the dialog was shown"),
+            );
+            assert_eq!(output, "");
+        }
+    }
+}
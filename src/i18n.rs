@@ -0,0 +1,241 @@
+use std::collections::HashMap;
+
+use unicode_bidi::{BidiInfo, Level};
+
+use crate::validation::{code_point, NameError, MAX_NAME_BYTES};
+
+/// Languages with a message catalog, by their ISO 639-1 code
+pub const LOCALES: [&str; 6] = ["en", "es", "ja", "de", "ar", "he"];
+
+/// Catalogs compiled into the binary, English first
+const CATALOGS: [(&str, &str); 6] = [
+    ("en", include_str!("../assets/i18n/en.toml")),
+    ("es", include_str!("../assets/i18n/es.toml")),
+    ("ja", include_str!("../assets/i18n/ja.toml")),
+    ("de", include_str!("../assets/i18n/de.toml")),
+    ("ar", include_str!("../assets/i18n/ar.toml")),
+    ("he", include_str!("../assets/i18n/he.toml")),
+];
+
+/// The dialog strings in one language, falling back to English for missing keys
+#[derive(Clone, Debug)]
+pub struct Messages {
+    language: String,
+    catalog: HashMap<String, String>,
+    fallback: HashMap<String, String>,
+}
+
+impl Default for Messages {
+    fn default() -> Self {
+        Self::new("en")
+    }
+}
+
+impl Messages {
+    /// Messages for a locale such as "es", "de_DE.UTF-8" or "ja-JP", English if there is no catalog
+    pub fn new(locale: &str) -> Self {
+        let fallback = parse_catalog("en", CATALOGS[0].1);
+        let language = language_of(locale);
+        match CATALOGS.iter().find(|(code, _)| *code == language) {
+            Some((code, source)) => Self {
+                language: (*code).to_owned(),
+                catalog: parse_catalog(code, source),
+                fallback,
+            },
+            None => {
+                log::info!("No message catalog for locale {locale:?}, using English");
+                Self {
+                    language: "en".to_owned(),
+                    catalog: fallback.clone(),
+                    fallback,
+                }
+            }
+        }
+    }
+
+    /// ISO 639-1 code of the catalog in use
+    pub fn language(&self) -> &str {
+        &self.language
+    }
+
+    /// Whether the language is written right to left, so the dialog is mirrored
+    pub fn is_rtl(&self) -> bool {
+        self.get("direction") == "rtl"
+    }
+
+    /// `text` in display order, for labels: egui lays glyphs out left to right in the order
+    /// they are stored, so right-to-left runs are reversed here (mirroring brackets and
+    /// guillemets) following the Unicode bidirectional algorithm, in the direction of the language.
+    pub fn visual(&self, text: &str) -> String {
+        let base_level = if self.is_rtl() {
+            Level::rtl()
+        } else {
+            Level::ltr()
+        };
+        let info = BidiInfo::new(text, Some(base_level));
+        let mut visual = String::with_capacity(text.len());
+        for paragraph in &info.paragraphs {
+            let line = paragraph.range.clone();
+            let (levels, runs) = info.visual_runs(paragraph, line);
+            for run in runs {
+                let run_text = &text[run.clone()];
+                if levels[run.start].is_rtl() {
+                    visual.extend(run_text.chars().rev().map(mirrored));
+                } else {
+                    visual.push_str(run_text);
+                }
+            }
+        }
+        visual
+    }
+
+    /// The message called `key`, or the key itself if no catalog has it
+    pub fn get<'a>(&'a self, key: &'a str) -> &'a str {
+        self.catalog
+            .get(key)
+            .or_else(|| self.fallback.get(key))
+            .map_or(key, String::as_str)
+    }
+
+    /// The message called `key` with its `{placeholder}`s replaced
+    pub fn format(&self, key: &str, args: &[(&str, &str)]) -> String {
+        args.iter()
+            .fold(self.get(key).to_owned(), |message, (name, value)| {
+                message.replace(&format!("{{{name}}}"), value)
+            })
+    }
+
+    /// `text` between the quotation marks of the language, e.g. “text”, «text» or 「text」
+    pub fn quote(&self, text: &str) -> String {
+        format!(
+            "{}{}{}",
+            self.get("quote_open"),
+            text,
+            self.get("quote_close")
+        )
+    }
+
+    /// Why a folder name can't be used, in this language
+    pub fn name_error(&self, error: NameError) -> String {
+        match error {
+            NameError::Empty => self.get("error_empty").to_owned(),
+            NameError::Reserved => self.get("error_reserved").to_owned(),
+            NameError::Separator(separator) => self.format(
+                "error_separator",
+                &[("separator", &self.quote(&separator.to_string()))],
+            ),
+            NameError::Nul => self.get("error_nul").to_owned(),
+            NameError::BidiControl(control) => {
+                self.format("error_bidi_control", &[("character", &code_point(control))])
+            }
+            NameError::TooLong => {
+                self.format("error_too_long", &[("max", &MAX_NAME_BYTES.to_string())])
+            }
+        }
+    }
+}
+
+/// Locale of the user interface from the environment, like gettext: LC_ALL, LC_MESSAGES, LANG
+pub fn detect_locale() -> String {
+    ["LC_ALL", "LC_MESSAGES", "LANG"]
+        .iter()
+        .filter_map(|variable| std::env::var(variable).ok())
+        .find(|value| !value.is_empty())
+        .unwrap_or_else(|| "en".to_owned())
+}
+
+/// Language part of a locale: "de_DE.UTF-8@euro" -> "de", "C" and "POSIX" -> "en"
+fn language_of(locale: &str) -> String {
+    let language = locale
+        .split(['_', '-', '.', '@'])
+        .next()
+        .unwrap_or_default()
+        .to_lowercase();
+    match language.as_str() {
+        "" | "c" | "posix" => "en".to_owned(),
+        _ => language,
+    }
+}
+
+/// The glyph a bidi-mirrored character shows in right-to-left text
+fn mirrored(c: char) -> char {
+    match c {
+        '(' => ')',
+        ')' => '(',
+        '[' => ']',
+        ']' => '[',
+        '{' => '}',
+        '}' => '{',
+        '<' => '>',
+        '>' => '<',
+        '«' => '»',
+        '»' => '«',
+        '‹' => '›',
+        '›' => '‹',
+        _ => c,
+    }
+}
+
+fn parse_catalog(code: &str, source: &str) -> HashMap<String, String> {
+    toml::from_str(source).unwrap_or_else(|error| {
+        // The catalogs are compiled in, so this is a bug rather than a user error
+        log::error!("Invalid {code} message catalog: {error}");
+        HashMap::new()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use super::*;
+
+    /// The `{placeholder}` names in a message
+    fn placeholders(message: &str) -> BTreeSet<&str> {
+        message
+            .split('{')
+            .skip(1)
+            .filter_map(|rest| rest.split_once('}'))
+            .map(|(name, _)| name)
+            .collect()
+    }
+
+    #[test]
+    fn catalogs_have_the_english_keys() {
+        let parse = |code: &str, source: &str| -> HashMap<String, String> {
+            toml::from_str(source).unwrap_or_else(|error| panic!("{code} catalog: {error}"))
+        };
+        let english = parse("en", CATALOGS[0].1);
+        for (code, source) in CATALOGS {
+            let catalog = parse(code, source);
+            let missing: Vec<_> = english
+                .keys()
+                .filter(|key| !catalog.contains_key(*key))
+                .collect();
+            let unknown: Vec<_> = catalog
+                .keys()
+                .filter(|key| !english.contains_key(*key))
+                .collect();
+            assert!(missing.is_empty(), "{code} is missing {missing:?}");
+            assert!(unknown.is_empty(), "{code} has unknown keys {unknown:?}");
+            for (key, message) in &catalog {
+                assert_eq!(
+                    placeholders(message),
+                    placeholders(&english[key]),
+                    "placeholders of {key} in {code}"
+                );
+            }
+            let rtl = matches!(code, "ar" | "he");
+            assert_eq!(
+                catalog["direction"],
+                if rtl { "rtl" } else { "ltr" },
+                "{code}"
+            );
+        }
+        assert_eq!(
+            CATALOGS.map(|(code, _)| code),
+            LOCALES,
+            "every language is listed"
+        );
+    }
+}
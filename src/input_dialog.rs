@@ -0,0 +1,866 @@
+use egui::Color32;
+use egui::Stroke;
+use egui::{FontFamily, FontId};
+use std::collections::BTreeMap;
+use std::ops::Range;
+
+use egui::epaint::Margin;
+use egui::TextStyle::*;
+
+use crate::i18n::Messages;
+use crate::theme::Theme;
+use crate::validation::split_extension;
+
+/// Which part of the value is selected when the field gets keyboard focus
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub enum Selection {
+    /// The whole value, so typing replaces it
+    #[default]
+    All,
+    /// These characters (not bytes), e.g. the stem of a file name
+    Range(Range<usize>),
+    /// Nothing, the cursor is after the last character
+    End,
+}
+
+impl Selection {
+    /// The stem of a file name, "report" of "report.pdf", so typing keeps the extension
+    pub fn stem(name: &str) -> Self {
+        Self::Range(0..split_extension(name).0.chars().count())
+    }
+
+    /// The selected characters of `value`, which is how egui's cursors count
+    pub fn char_range(&self, value: &str) -> Range<usize> {
+        // A byte length would overshoot non-ASCII names
+        let end = value.chars().count();
+        match self {
+            Self::All => 0..end,
+            Self::Range(range) => range.start.min(end)..range.end.min(end),
+            Self::End => end..end,
+        }
+    }
+}
+
+/// What the owner of the dialog has to say about the value, shown under the field
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub enum Feedback {
+    /// The value can be used
+    #[default]
+    None,
+    /// The value can't be used, the default button is disabled until it is edited
+    Invalid(String),
+    /// Using the value failed, the user may edit it or try again
+    Failed(String),
+    /// The value can be used but may not be what the user meant
+    Warning(String),
+    /// The value clashes with something; the default button is disabled and `action` is offered
+    /// as a link, reported by [`InputResponse::action_clicked`]
+    Conflict { message: String, action: String },
+}
+
+impl Feedback {
+    /// Whether the default button is enabled
+    fn can_confirm(&self) -> bool {
+        !matches!(self, Self::Invalid(_) | Self::Conflict { .. })
+    }
+}
+
+/// What happened in the dialog during a frame
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct InputResponse {
+    /// The value was edited
+    pub changed: bool,
+    /// The default button was clicked, or Return pressed while it is enabled
+    pub confirmed: bool,
+    /// The Cancel button was clicked, or Escape or Cmd/Ctrl+W pressed
+    pub cancelled: bool,
+    /// The action link of [`Feedback::Conflict`] was clicked
+    pub action_clicked: bool,
+}
+
+/// A titled single-line input with a body text, an optional quoted context line and
+/// OK/Cancel buttons: the layout of the New Folder dialog, for "Rename", "New Tag", … prompts.
+///
+/// Like egui widgets it is built every frame around the value it edits, the focus state lives
+/// in egui memory. It fills the central panel of the window with the colors, margins and font
+/// sizes of a [`Theme`], mirrored for right-to-left languages.
+pub struct InputDialog<'a> {
+    value: &'a mut String,
+    heading: String,
+    body: String,
+    context: Option<String>,
+    placeholder: String,
+    selection: Selection,
+    ok_label: String,
+    cancel_label: Option<String>,
+    field: bool,
+    secret: bool,
+    feedback: Feedback,
+    byte_counter: Option<ByteCounter<'a>>,
+    section: Option<Section<'a>>,
+}
+
+/// Extra content drawn under the field, see [`InputDialog::with_section`]
+type Section<'a> = Box<dyn FnOnce(&mut egui::Ui) + 'a>;
+
+/// Limit shown under the field, with how to count the bytes of a value
+struct ByteCounter<'a> {
+    max: usize,
+    count: Box<dyn Fn(&str) -> usize + 'a>,
+}
+
+impl<'a> InputDialog<'a> {
+    /// Dialog titled `heading` editing `value`, whose content is the initial value
+    pub fn new(heading: impl Into<String>, value: &'a mut String) -> Self {
+        Self {
+            value,
+            heading: heading.into(),
+            body: String::new(),
+            context: None,
+            placeholder: String::new(),
+            selection: Selection::default(),
+            ok_label: "OK".to_owned(),
+            cancel_label: Some("Cancel".to_owned()),
+            field: true,
+            secret: false,
+            feedback: Feedback::None,
+            byte_counter: None,
+            section: None,
+        }
+    }
+
+    /// Setter for the text under the heading
+    pub fn with_body(mut self, body: impl Into<String>) -> Self {
+        self.body = body.into();
+        self
+    }
+
+    /// Setter for the line between the body and the field, e.g. the quoted parent folder
+    pub fn with_context(mut self, context: impl Into<String>) -> Self {
+        self.context = Some(context.into());
+        self
+    }
+
+    /// Setter for the hint shown while the field is empty
+    pub fn with_placeholder(mut self, placeholder: impl Into<String>) -> Self {
+        self.placeholder = placeholder.into();
+        self
+    }
+
+    /// Setter for what is selected when the field gets focus
+    pub fn with_selection(mut self, selection: Selection) -> Self {
+        self.selection = selection;
+        self
+    }
+
+    /// Setter for the default button label
+    pub fn with_ok_label(mut self, label: impl Into<String>) -> Self {
+        self.ok_label = label.into();
+        self
+    }
+
+    /// Setter for the Cancel button label, None for a dialog that can only be acknowledged
+    pub fn with_cancel_label(mut self, label: Option<String>) -> Self {
+        self.cancel_label = label;
+        self
+    }
+
+    /// Leave out the field, for messages and questions
+    pub fn without_field(mut self) -> Self {
+        self.field = false;
+        self
+    }
+
+    /// Mask the value, e.g. for passwords, with a checkbox to show it. Its earlier versions are
+    /// not kept for undo, call [`Self::forget_field`] once it has been used.
+    pub fn secret(mut self) -> Self {
+        self.secret = true;
+        self
+    }
+
+    /// Setter for what the owner has to say about the current value
+    pub fn with_feedback(mut self, feedback: Feedback) -> Self {
+        self.feedback = feedback;
+        self
+    }
+
+    /// Show how many of `max` bytes the value takes, as counted by `count` (e.g. after
+    /// normalization), updated while typing
+    pub fn with_byte_counter(mut self, max: usize, count: impl Fn(&str) -> usize + 'a) -> Self {
+        self.byte_counter = Some(ByteCounter {
+            max,
+            count: Box::new(count),
+        });
+        self
+    }
+
+    /// Add a section drawn by `section` under the field and its feedback, e.g. a browser for
+    /// where the value goes
+    pub fn with_section(mut self, section: impl FnOnce(&mut egui::Ui) + 'a) -> Self {
+        self.section = Some(Box::new(section));
+        self
+    }
+
+    /// Give the field keyboard focus again on the next frame, selecting the initial selection,
+    /// e.g. after a confirmation was shown in place of the dialog
+    pub fn focus_field(ctx: &egui::Context) {
+        ctx.data_mut(|data| data.remove::<bool>(focus_requested_id()));
+    }
+
+    /// Drop what egui remembers of the field, e.g. once a secret was delivered
+    pub fn forget_field(ctx: &egui::Context) {
+        egui::text_edit::TextEditState::default().store(ctx, field_id());
+    }
+
+    /// Lay the dialog out in the central panel and report what the user did
+    pub fn show(self, ctx: &egui::Context, theme: &Theme, messages: &Messages) -> InputResponse {
+        let mut response = InputResponse::default();
+        let value = self.value;
+        let can_confirm = self.feedback.can_confirm();
+        let rtl = messages.is_rtl();
+        let field_id = field_id();
+        let focus_requested_id = focus_requested_id();
+        let revealed_id = field_id.with("revealed");
+        let mut revealed =
+            self.secret && ctx.data(|data| data.get_temp(revealed_id).unwrap_or(false));
+
+        // Dialog keys are consumed before the widgets see them. Space activates the focused button.
+        let (return_pressed, cancel_pressed) = dialog_keys(ctx);
+
+        // Focus rings, selection and the default button fade while the window is in the background
+        let (stroke_color, selection_color, ok_button_color) = focus_colors(ctx, theme);
+        let field_has_focus = ctx.memory(|memory| memory.has_focus(field_id));
+
+        let text_box_frame = field_frame(theme, rtl);
+        // Red while the value can't be used, focus ring while the field has focus
+        let text_box_stroke = field_ring(
+            theme,
+            rtl,
+            if !can_confirm {
+                theme.colors.error
+            } else if field_has_focus {
+                stroke_color
+            } else {
+                Color32::TRANSPARENT
+            },
+        );
+
+        egui::CentralPanel::default()
+            .frame(dialog_frame(theme, rtl))
+            .show(ctx, |ui| {
+                // Right-to-left languages mirror the dialog: rows started with `ui.horizontal` grow
+                // from the right edge of a right-aligned layout, explicit layouts below flip too
+                ui.with_layout(egui::Layout::top_down(leading_align(rtl)), |ui| {
+                    ui.horizontal(|ui| {
+                        ui.add_space(theme.margins.content_indent);
+                        // Reference: https://github.com/emilk/egui/discussions/3933 | https://docs.rs/egui/0.30.0/egui/struct.Ui.html#method.horizontal
+                        // Reference: egui-0.30.0/src/ui.rs
+                        ui.allocate_ui_with_layout(
+                            [ui.available_width(), 16.].into(),
+                            leading_layout(rtl, egui::Align::BOTTOM),
+                            |ui| {
+                                ui.add(
+                                    egui::Label::new(
+                                        egui::RichText::new(messages.visual(&self.heading))
+                                            .color(theme.colors.text)
+                                            .text_style(Name("DialogHeading".into())),
+                                    )
+                                    .selectable(false),
+                                );
+                            },
+                        );
+                    });
+
+                    if let Some(context) = &self.context {
+                        // Body and context line sit on the field, like the New Folder sheet
+                        ui.horizontal(|ui| {
+                            ui.add_space(theme.margins.content_indent);
+                            ui.allocate_ui_with_layout(
+                                [ui.available_width(), 35.].into(),
+                                egui::Layout::bottom_up(leading_align(rtl)),
+                                |ui| {
+                                    ui.add(
+                                        egui::Label::new(
+                                            egui::RichText::new(messages.visual(context))
+                                                .color(theme.colors.text)
+                                                .text_style(Name("DialogFolderName".into())),
+                                        )
+                                        .selectable(false),
+                                    );
+                                    ui.add(
+                                        egui::Label::new(
+                                            egui::RichText::new(messages.visual(&self.body))
+                                                .color(theme.colors.text)
+                                                .text_style(Name("DialogBody".into()))
+                                                .line_height(Some(11.)), // Create space below the text
+                                        )
+                                        .selectable(false),
+                                    );
+                                },
+                            );
+                        });
+                    } else {
+                        // A longer question or message, wrapped to the dialog width
+                        ui.horizontal(|ui| {
+                            ui.add_space(theme.margins.content_indent);
+                            ui.add(
+                                egui::Label::new(
+                                    egui::RichText::new(visual_lines(
+                                        ui,
+                                        messages,
+                                        &self.body,
+                                        &Name("DialogBody".into()),
+                                    ))
+                                    .color(theme.colors.text)
+                                    .text_style(Name("DialogBody".into())),
+                                )
+                                .wrap()
+                                .selectable(false),
+                            );
+                        });
+                    }
+                    ui.add_space(4.5);
+
+                    if self.field {
+                        text_box_stroke.show(ui, |ui| {
+                            text_box_frame.show(ui, |ui| {
+                                let visuals = ui.visuals_mut();
+                                visuals.selection.bg_fill = selection_color; // Change background fill color
+                                                                             // Use show() instead of ui.add() to get the output state
+                                let mut output = egui::TextEdit::singleline(&mut *value)
+                                    .id(field_id)
+                                    .hint_text(
+                                        egui::RichText::new(&self.placeholder)
+                                            .color(theme.colors.disabled_text),
+                                    )
+                                    .desired_width(f32::INFINITY)
+                                    .font(Name("TextInputBody".into()))
+                                    .margin(theme.margins.field_text)
+                                    .horizontal_align(leading_align(rtl))
+                                    .background_color(theme.colors.field_fill)
+                                    .text_color(theme.colors.field_text)
+                                    .frame(false)
+                                    .password(self.secret && !revealed)
+                                    .show(ui);
+
+                                // Request focus on the TextEdit widget, once on first rendering, so Tab can move it
+                                let focus_requested = ui.data_mut(|data| {
+                                    std::mem::replace(
+                                        data.get_temp_mut_or(focus_requested_id, false),
+                                        true,
+                                    )
+                                });
+                                if !focus_requested {
+                                    output.response.request_focus();
+                                }
+
+                                response.changed = output.response.changed();
+
+                                // Select the initial selection by setting the cursor range, on focus
+                                // Reference: https://stackoverflow.com/questions/74324236/select-the-text-of-a-textedit-object-in-egui
+                                use egui::{text::CCursor, text::CCursorRange};
+                                if output.response.gained_focus() {
+                                    let range = self.selection.char_range(value);
+                                    output.state.cursor.set_char_range(Some(CCursorRange::two(
+                                        CCursor::new(range.start),
+                                        CCursor::new(range.end),
+                                    )));
+                                    // Apply the changes
+                                    output.state.store(ui.ctx(), output.response.id);
+                                } else if self.secret {
+                                    // Undo history keeps copies of the text, a secret must not linger there
+                                    output.state.clear_undoer();
+                                    output.state.store(ui.ctx(), output.response.id);
+                                }
+                            });
+                        });
+
+                        if self.secret {
+                            ui.add_space(2.);
+                            ui.horizontal(|ui| {
+                                ui.add_space(theme.margins.content_indent);
+                                let toggled = ui.checkbox(
+                                    &mut revealed,
+                                    egui::RichText::new(
+                                        messages.visual(messages.get("show_secret")),
+                                    )
+                                    .color(theme.colors.text)
+                                    .text_style(Name("DialogBody".into())),
+                                );
+                                if toggled.changed() {
+                                    ui.data_mut(|data| data.insert_temp(revealed_id, revealed));
+                                }
+                            });
+                        }
+                    }
+
+                    if let Some(counter) = &self.byte_counter {
+                        // Counted after the field, so it follows what was just typed
+                        let count = (counter.count)(value);
+                        let text = messages.format(
+                            "byte_counter",
+                            &[
+                                ("count", &count.to_string()),
+                                ("max", &counter.max.to_string()),
+                            ],
+                        );
+                        ui.with_layout(leading_layout(!rtl, egui::Align::TOP), |ui| {
+                            ui.add_space(theme.margins.content_indent);
+                            ui.add(
+                                egui::Label::new(
+                                    egui::RichText::new(messages.visual(&text))
+                                        .color(if count > counter.max {
+                                            theme.colors.error
+                                        } else {
+                                            theme.colors.disabled_text
+                                        })
+                                        .text_style(Name("DialogBody".into())),
+                                )
+                                .selectable(false),
+                            );
+                        });
+                    }
+
+                    // Feedback is kept inline so the user can fix the value and retry
+                    match &self.feedback {
+                        Feedback::None => {}
+                        Feedback::Invalid(message) | Feedback::Failed(message) => {
+                            ui.add_space(2.);
+                            ui.horizontal(|ui| {
+                                ui.add_space(theme.margins.content_indent);
+                                ui.add(
+                                    egui::Label::new(
+                                        egui::RichText::new(visual_lines(
+                                            ui,
+                                            messages,
+                                            message,
+                                            &Name("DialogBody".into()),
+                                        ))
+                                        .color(theme.colors.error)
+                                        .text_style(Name("DialogBody".into())),
+                                    )
+                                    .wrap()
+                                    .selectable(false),
+                                );
+                            });
+                        }
+                        Feedback::Warning(message) => {
+                            ui.add_space(2.);
+                            ui.horizontal(|ui| {
+                                ui.add_space(theme.margins.content_indent);
+                                ui.add(
+                                    egui::Label::new(
+                                        egui::RichText::new(visual_lines(
+                                            ui,
+                                            messages,
+                                            message,
+                                            &Name("DialogBody".into()),
+                                        ))
+                                        .color(theme.colors.text)
+                                        .text_style(Name("DialogBody".into())),
+                                    )
+                                    .wrap()
+                                    .selectable(false),
+                                );
+                            });
+                        }
+                        Feedback::Conflict { message, action } => {
+                            // A clash, with a way out that keeps what was typed
+                            ui.add_space(2.);
+                            ui.horizontal_wrapped(|ui| {
+                                ui.add_space(theme.margins.content_indent);
+                                ui.add(
+                                    egui::Label::new(
+                                        egui::RichText::new(messages.visual(message))
+                                            .color(theme.colors.error)
+                                            .text_style(Name("DialogBody".into())),
+                                    )
+                                    .selectable(false),
+                                );
+                                response.action_clicked = ui
+                                    .add(egui::Link::new(
+                                        egui::RichText::new(messages.visual(action))
+                                            .color(theme.colors.link)
+                                            .text_style(Name("DialogBody".into())),
+                                    ))
+                                    .clicked();
+                            });
+                        }
+                    }
+
+                    if let Some(section) = self.section {
+                        ui.add_space(2.);
+                        section(ui);
+                    }
+
+                    ui.with_layout(leading_layout(!rtl, egui::Align::BOTTOM), |ui| {
+                        ui.add_space(3.);
+
+                        button_style(ui, theme);
+
+                        // Primary button (OK), greyed out while the value can't be used
+                        let ok_button = ok_button(
+                            &self.ok_label,
+                            ok_button_color,
+                            can_confirm,
+                            theme,
+                            messages,
+                        );
+                        let ok_response = ui.add_enabled(can_confirm, ok_button);
+                        draw_focus_ring(ui, &ok_response, stroke_color, theme);
+                        if ok_response.clicked() {
+                            // Reference: egui-0.30.0/src/ui.rs | https://github.com/emilk/egui/discussions/5340
+                            log::debug!(
+                                "{} clicked. Window rect = {:?}",
+                                self.ok_label,
+                                ctx.screen_rect()
+                            );
+                            response.confirmed = true;
+                        }
+
+                        ui.add_space(0.1); // Space between buttons
+
+                        // Secondary button (Cancel) - Light gray with default text, absent from plain messages
+                        if let Some(cancel_label) = &self.cancel_label {
+                            let cancel_button = cancel_button(cancel_label, theme, messages);
+                            let cancel_response = ui.add(cancel_button);
+                            draw_focus_ring(ui, &cancel_response, stroke_color, theme);
+                            if cancel_response.clicked() {
+                                log::debug!("{} clicked", cancel_label);
+                                response.cancelled = true;
+                            }
+                        }
+                    });
+                });
+            });
+
+        // Keyboard shortcuts, after the widgets so a click and a key in the same frame count once
+        if cancel_pressed {
+            log::debug!("Cancel pressed");
+            response.cancelled = true;
+        } else if return_pressed && can_confirm {
+            response.confirmed = true;
+        }
+        response
+    }
+}
+
+/// Id of the text field, whose focus egui remembers between frames
+fn field_id() -> egui::Id {
+    egui::Id::new("input_dialog_field")
+}
+
+/// Id of the flag telling the field got its initial focus
+fn focus_requested_id() -> egui::Id {
+    field_id().with("focus_requested")
+}
+
+/// Install the dialog text styles, sized by the theme
+pub fn apply_text_styles(ctx: &egui::Context, theme: &Theme) {
+    // Redefine text_styles adding new text styles
+    // Reference: https://docs.rs/egui/latest/egui/style/struct.Style.html#structfield.text_styles
+    let text_styles: BTreeMap<_, _> = [
+        (Heading, FontId::new(30.0, FontFamily::Proportional)),
+        (Body, FontId::new(12.0, FontFamily::Proportional)),
+        (Monospace, FontId::new(14.0, FontFamily::Proportional)),
+        (Button, FontId::new(14.0, FontFamily::Proportional)),
+        (Small, FontId::new(10.0, FontFamily::Proportional)),
+        (
+            Name("DialogHeading".into()),
+            FontId::new(
+                theme.font_sizes.heading,
+                FontFamily::Name("System-Text-Heavy".into()),
+            ),
+        ),
+        (
+            Name("DialogBody".into()),
+            FontId::new(
+                theme.font_sizes.body,
+                FontFamily::Name("System-Text-Medium".into()),
+            ),
+        ),
+        (
+            Name("DialogFolderName".into()),
+            FontId::new(
+                theme.font_sizes.folder_name,
+                FontFamily::Name("System-Text-Medium".into()),
+            ),
+        ),
+        (
+            Name("TextInputBody".into()),
+            FontId::new(
+                theme.font_sizes.text_input,
+                FontFamily::Name("System-Text-Medium".into()),
+            ),
+        ),
+        (
+            Name("ButtonBody".into()),
+            FontId::new(
+                theme.font_sizes.button,
+                FontFamily::Name("System-Display-Medium".into()),
+            ),
+        ),
+    ]
+    .into();
+
+    // Mutate global styles with new text styles
+    ctx.all_styles_mut(move |style| style.text_styles = text_styles.clone());
+}
+
+/// Layout of a row that starts at the leading edge of the text direction
+pub(crate) fn leading_layout(rtl: bool, align: egui::Align) -> egui::Layout {
+    if rtl {
+        egui::Layout::right_to_left(align)
+    } else {
+        egui::Layout::left_to_right(align)
+    }
+}
+
+/// Horizontal alignment of text in the text direction
+pub(crate) fn leading_align(rtl: bool) -> egui::Align {
+    if rtl {
+        egui::Align::RIGHT
+    } else {
+        egui::Align::LEFT
+    }
+}
+
+/// Swap the left and right sides of a theme margin for right-to-left layouts
+pub(crate) fn mirrored_margin(margin: Margin, rtl: bool) -> Margin {
+    if rtl {
+        Margin {
+            left: margin.right,
+            right: margin.left,
+            ..margin
+        }
+    } else {
+        margin
+    }
+}
+
+/// `text` in display order, reordered line by line where egui wraps it in the available width,
+/// so the first line of right-to-left text is still the one at the top
+pub(crate) fn visual_lines(
+    ui: &egui::Ui,
+    messages: &Messages,
+    text: &str,
+    text_style: &egui::TextStyle,
+) -> String {
+    let font_id = text_style.resolve(ui.style());
+    let galley = ui.fonts(|fonts| {
+        fonts.layout(
+            text.to_owned(),
+            font_id,
+            Color32::PLACEHOLDER,
+            ui.available_width(),
+        )
+    });
+    let mut chars = text.chars();
+    galley
+        .rows
+        .iter()
+        .map(|row| {
+            let line: String = chars
+                .by_ref()
+                .take(row.char_count_including_newline())
+                .collect();
+            messages.visual(line.trim_end())
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Draw a focus ring around a button that has keyboard focus (reached with Tab)
+pub(crate) fn draw_focus_ring(
+    ui: &egui::Ui,
+    response: &egui::Response,
+    color: Color32,
+    theme: &Theme,
+) {
+    if response.has_focus() {
+        ui.painter().rect_stroke(
+            response.rect.expand(1.5),
+            egui::Rounding::same(theme.rounding.focus_ring),
+            Stroke::new(theme.margins.focus_ring_width, color),
+        );
+    }
+}
+
+/// Consume Return, and Escape or Cmd+W, before the widgets see them, so Return on a focused
+/// Cancel button still means the default button: (Return, Escape or Cmd+W) pressed
+pub(crate) fn dialog_keys(ctx: &egui::Context) -> (bool, bool) {
+    ctx.input_mut(|i| {
+        let return_pressed = i.consume_key(egui::Modifiers::NONE, egui::Key::Enter);
+        let escape_pressed = i.consume_key(egui::Modifiers::NONE, egui::Key::Escape);
+        let close_pressed = i.consume_key(egui::Modifiers::COMMAND, egui::Key::W);
+        (return_pressed, escape_pressed || close_pressed)
+    })
+}
+
+/// Colours of the focus ring, selection and default button, which fade while the window is in
+/// the background
+// Reference: https://docs.rs/egui/0.30.0/egui/struct.Context.html#method.input
+pub(crate) fn focus_colors(ctx: &egui::Context, theme: &Theme) -> (Color32, Color32, Color32) {
+    if ctx.input(|i| i.focused) {
+        (
+            theme.colors.focus_ring,
+            theme.colors.selection,
+            theme.colors.default_button,
+        )
+    } else {
+        (
+            Color32::TRANSPARENT,
+            theme.colors.selection_unfocused,
+            theme.colors.default_button_inactive,
+        )
+    }
+}
+
+/// Frame of the central panel a dialog is laid out in
+// Reference: https://github.com/emilk/egui/discussions/1286
+pub(crate) fn dialog_frame(theme: &Theme, rtl: bool) -> egui::Frame {
+    egui::Frame {
+        inner_margin: Margin::ZERO,
+        outer_margin: mirrored_margin(theme.margins.dialog, rtl),
+        rounding: egui::Rounding::same(theme.rounding.dialog),
+        shadow: eframe::epaint::Shadow::NONE,
+        fill: Color32::TRANSPARENT,
+        stroke: egui::Stroke::new(0.0, Color32::LIGHT_GRAY),
+    }
+}
+
+/// Frame of a text field, shown inside its [`field_ring`]
+pub(crate) fn field_frame(theme: &Theme, rtl: bool) -> egui::Frame {
+    egui::Frame {
+        inner_margin: Margin::ZERO,
+        outer_margin: mirrored_margin(theme.margins.field, rtl),
+        rounding: egui::Rounding::same(theme.rounding.field),
+        shadow: eframe::epaint::Shadow::NONE,
+        fill: theme.colors.field_fill,
+        stroke: egui::Stroke::new(theme.margins.field_border_width, theme.colors.field_border),
+    }
+}
+
+/// Ring around a text field, in `color`: the focus ring, an error or transparent
+pub(crate) fn field_ring(theme: &Theme, rtl: bool, color: Color32) -> egui::Frame {
+    egui::Frame {
+        inner_margin: mirrored_margin(theme.margins.field_ring, rtl),
+        outer_margin: Margin::ZERO,
+        rounding: egui::Rounding::same(theme.rounding.field_ring),
+        shadow: eframe::epaint::Shadow::NONE,
+        fill: color,
+        stroke: egui::Stroke::new(0.0, Color32::WHITE),
+    }
+}
+
+/// Frame of a table, file listing or folder tree, bordered like a text field
+pub(crate) fn list_frame(theme: &Theme, outer_margin: Margin, fill: Color32) -> egui::Frame {
+    egui::Frame {
+        inner_margin: Margin::same(1.0),
+        outer_margin,
+        rounding: egui::Rounding::same(theme.rounding.field),
+        shadow: eframe::epaint::Shadow::NONE,
+        fill,
+        stroke: egui::Stroke::new(
+            theme.margins.field_border_width.max(1.0),
+            theme.colors.field_border,
+        ),
+    }
+}
+
+/// Style the dialog buttons laid out in `ui`: theme padding, no growing on hover or press
+// Reference: button.rs & style.rs
+pub(crate) fn button_style(ui: &mut egui::Ui, theme: &Theme) {
+    let styles = ui.style_mut();
+    styles.spacing.button_padding = theme.margins.button_padding;
+    styles.visuals.widgets.hovered.expansion = 0.0;
+    styles.visuals.widgets.active.expansion = 0.0;
+}
+
+/// A dialog button with this text colour and fill
+pub(crate) fn dialog_button(
+    label: &str,
+    text_color: Color32,
+    fill: Color32,
+    theme: &Theme,
+    messages: &Messages,
+) -> egui::Button<'static> {
+    egui::Button::new(
+        egui::RichText::new(messages.visual(label))
+            .color(text_color)
+            .text_style(Name("ButtonBody".into()))
+            .extra_letter_spacing(0.2),
+    )
+    .fill(fill)
+    .rounding(egui::Rounding::same(theme.rounding.button)) // macOS rounded corners
+    .frame(true)
+    .stroke(Stroke::new(0.0, Color32::TRANSPARENT))
+}
+
+/// The default button (OK), filled with `fill` (macOS blue), greyed out unless `enabled`
+pub(crate) fn ok_button(
+    label: &str,
+    fill: Color32,
+    enabled: bool,
+    theme: &Theme,
+    messages: &Messages,
+) -> egui::Button<'static> {
+    let (text_color, fill) = if enabled {
+        (theme.colors.default_button_text, fill)
+    } else {
+        (
+            theme.colors.disabled_text,
+            theme.colors.default_button_inactive,
+        )
+    };
+    dialog_button(label, text_color, fill, theme, messages)
+        .min_size(theme.margins.default_button_min_size)
+}
+
+/// A secondary button (Cancel, New Folder), light gray with the default text colour
+pub(crate) fn cancel_button(
+    label: &str,
+    theme: &Theme,
+    messages: &Messages,
+) -> egui::Button<'static> {
+    dialog_button(
+        label,
+        theme.colors.button_text,
+        theme.colors.button,
+        theme,
+        messages,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The selected text, taking the range in characters like egui does
+    fn selected(selection: &Selection, value: &str) -> String {
+        let range = selection.char_range(value);
+        value.chars().skip(range.start).take(range.len()).collect()
+    }
+
+    #[test]
+    fn stem_selection_counts_characters() {
+        assert_eq!(selected(&Selection::stem("café.pdf"), "café.pdf"), "café");
+        assert_eq!(
+            selected(&Selection::stem("🎉party.txt"), "🎉party.txt"),
+            "🎉party"
+        );
+        assert_eq!(
+            selected(&Selection::stem("日本語.tar.gz"), "日本語.tar.gz"),
+            "日本語.tar"
+        );
+        assert_eq!(selected(&Selection::stem(".bashrc"), ".bashrc"), ".bashrc");
+    }
+
+    #[test]
+    fn whole_and_end_selections_count_characters() {
+        assert_eq!(Selection::All.char_range("naïve"), 0..5);
+        assert_eq!(Selection::End.char_range("👨‍👩‍👧"), 5..5);
+        // A range past the value is clamped rather than overshooting
+        assert_eq!(Selection::Range(0..10).char_range("é"), 0..1);
+    }
+}
@@ -1,4 +1,37 @@
 #![warn(clippy::all, rust_2018_idioms)]
 
 mod app;
-pub use app::TemplateApp;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod askpass;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod cli;
+pub mod directory_picker;
+pub mod file_chooser;
+pub mod fonts;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod git_credential;
+pub mod i18n;
+pub mod input_dialog;
+pub mod list_dialog;
+pub mod login_dialog;
+pub mod message_box;
+mod outcome;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod output;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod pinentry;
+pub mod places;
+pub mod progress_dialog;
+pub mod theme;
+pub mod validation;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod zenity;
+pub use app::{DialogKind, MessageKind, TemplateApp};
+pub use directory_picker::{DirectoryBrowser, DirectoryPicker};
+pub use file_chooser::{FileBrowser, FileChooser, FileFilter};
+pub use input_dialog::{Feedback, InputDialog, InputResponse, Selection};
+pub use list_dialog::{ListDialog, ListState, SortOrder, Table};
+pub use login_dialog::{Credential, LoginDialog};
+pub use message_box::{ButtonRole, MessageBox, MessageButton, MessageResponse};
+pub use outcome::{DialogOutcome, DialogValue};
+pub use progress_dialog::{ProgressDialog, ProgressUpdate};
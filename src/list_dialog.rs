@@ -0,0 +1,686 @@
+use std::cmp::Ordering;
+use std::collections::BTreeSet;
+
+use egui::Color32;
+use egui::Stroke;
+
+use egui::TextStyle::*;
+
+use crate::i18n::Messages;
+use crate::input_dialog::{
+    button_style, cancel_button, dialog_frame, dialog_keys, draw_focus_ring, field_frame,
+    field_ring, focus_colors, leading_align, leading_layout, list_frame, mirrored_margin,
+    ok_button, visual_lines,
+};
+use crate::theme::Theme;
+use crate::InputResponse;
+
+/// Rows to pick from, with a name for each column
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Table {
+    pub columns: Vec<String>,
+    /// Each row has as many cells as there are columns
+    pub rows: Vec<Vec<String>>,
+}
+
+impl Table {
+    /// Table of `rows`, padding short rows with empty cells and naming missing columns
+    /// "Column 2", "Column 3", …
+    pub fn new(mut columns: Vec<String>, mut rows: Vec<Vec<String>>) -> Self {
+        let width = rows
+            .iter()
+            .map(Vec::len)
+            .chain([columns.len()])
+            .max()
+            .unwrap_or(0);
+        for index in columns.len()..width {
+            columns.push(format!("Column {}", index + 1));
+        }
+        for row in &mut rows {
+            row.resize(width, String::new());
+        }
+        Self { columns, rows }
+    }
+}
+
+/// Column the rows are sorted by
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SortOrder {
+    pub column: usize,
+    pub descending: bool,
+}
+
+/// What the user did to the list: typed filter, chosen rows and sort order
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ListState {
+    pub filter: String,
+    /// Indices into [`Table::rows`]
+    pub selected: BTreeSet<usize>,
+    pub sort: Option<SortOrder>,
+}
+
+impl ListState {
+    /// Indices of the rows matching the filter, in the sort order
+    pub fn visible_rows(&self, table: &Table) -> Vec<usize> {
+        let filter = self.filter.to_lowercase();
+        let mut visible: Vec<usize> = (0..table.rows.len())
+            .filter(|&index| {
+                filter.is_empty()
+                    || table.rows[index]
+                        .iter()
+                        .any(|cell| cell.to_lowercase().contains(&filter))
+            })
+            .collect();
+        if let Some(sort) = self.sort {
+            visible.sort_by(|&a, &b| {
+                let ordering =
+                    compare_cells(&table.rows[a][sort.column], &table.rows[b][sort.column]);
+                if sort.descending {
+                    ordering.reverse()
+                } else {
+                    ordering
+                }
+            });
+        }
+        visible
+    }
+}
+
+/// Numbers first by value, then everything else alphabetically ignoring case
+fn compare_cells(a: &str, b: &str) -> Ordering {
+    match (parse_number(a), parse_number(b)) {
+        (Some(a), Some(b)) => a.total_cmp(&b),
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => a.to_lowercase().cmp(&b.to_lowercase()),
+    }
+}
+
+/// The value of a cell written as a decimal numeral: an optional sign, digits, an optional
+/// fraction and an optional exponent, e.g. "-3", "1.5" or " 2e3 ". Unlike `f64`'s parser this
+/// leaves words like "NaN" and "inf" to be sorted as text.
+fn parse_number(cell: &str) -> Option<f64> {
+    let cell = cell.trim();
+    let digits = |text: &str| text.bytes().take_while(u8::is_ascii_digit).count();
+    let mut rest = cell.strip_prefix(['+', '-']).unwrap_or(cell);
+    let integer = digits(rest);
+    if integer == 0 {
+        return None;
+    }
+    rest = &rest[integer..];
+    if let Some(fraction) = rest.strip_prefix('.') {
+        rest = &fraction[digits(fraction)..];
+    }
+    if let Some(exponent) = rest.strip_prefix(['e', 'E']) {
+        let exponent = exponent.strip_prefix(['+', '-']).unwrap_or(exponent);
+        let length = digits(exponent);
+        if length == 0 {
+            return None;
+        }
+        rest = &exponent[length..];
+    }
+    if !rest.is_empty() {
+        return None;
+    }
+    cell.parse().ok()
+}
+
+/// A titled, filterable table to pick one or several rows from, with OK/Cancel buttons, in the
+/// look of [`crate::InputDialog`].
+///
+/// Typing filters the rows, Up and Down move a single selection, a double click or Return picks
+/// it; with several selectable rows each one has a checkbox. Clicking a column header sorts by it.
+pub struct ListDialog<'a> {
+    table: &'a Table,
+    state: &'a mut ListState,
+    heading: String,
+    body: String,
+    multiple: bool,
+    placeholder: String,
+    empty_text: String,
+    ok_label: String,
+    cancel_label: String,
+}
+
+impl<'a> ListDialog<'a> {
+    /// Dialog titled `heading` picking from `table`, remembering what was done in `state`
+    pub fn new(heading: impl Into<String>, table: &'a Table, state: &'a mut ListState) -> Self {
+        Self {
+            table,
+            state,
+            heading: heading.into(),
+            body: String::new(),
+            multiple: false,
+            placeholder: String::new(),
+            empty_text: String::new(),
+            ok_label: "OK".to_owned(),
+            cancel_label: "Cancel".to_owned(),
+        }
+    }
+
+    /// Setter for the text under the heading
+    pub fn with_body(mut self, body: impl Into<String>) -> Self {
+        self.body = body.into();
+        self
+    }
+
+    /// Setter for whether several rows can be picked, with a checkbox column
+    pub fn with_multiple(mut self, multiple: bool) -> Self {
+        self.multiple = multiple;
+        self
+    }
+
+    /// Setter for the hint of the filter field
+    pub fn with_placeholder(mut self, placeholder: impl Into<String>) -> Self {
+        self.placeholder = placeholder.into();
+        self
+    }
+
+    /// Setter for the text shown when no row matches the filter
+    pub fn with_empty_text(mut self, text: impl Into<String>) -> Self {
+        self.empty_text = text.into();
+        self
+    }
+
+    /// Setter for the default button label
+    pub fn with_ok_label(mut self, label: impl Into<String>) -> Self {
+        self.ok_label = label.into();
+        self
+    }
+
+    /// Setter for the Cancel button label
+    pub fn with_cancel_label(mut self, label: impl Into<String>) -> Self {
+        self.cancel_label = label.into();
+        self
+    }
+
+    /// Lay the dialog out in the central panel and report what the user did
+    pub fn show(self, ctx: &egui::Context, theme: &Theme, messages: &Messages) -> InputResponse {
+        let mut response = InputResponse::default();
+        let rtl = messages.is_rtl();
+        let table = self.table;
+        let state = self.state;
+        let filter_id = egui::Id::new("list_dialog_filter");
+        let focus_requested_id = filter_id.with("focus_requested");
+
+        let (return_pressed, cancel_pressed) = dialog_keys(ctx);
+        let (up_pressed, down_pressed) = ctx.input_mut(|i| {
+            (
+                i.consume_key(egui::Modifiers::NONE, egui::Key::ArrowUp),
+                i.consume_key(egui::Modifiers::NONE, egui::Key::ArrowDown),
+            )
+        });
+        let (stroke_color, selection_color, ok_button_color) = focus_colors(ctx, theme);
+
+        let mut visible = state.visible_rows(table);
+        if !self.multiple {
+            // A single selection follows the keys, and stays among the rows that match
+            let current = visible.iter().position(|row| state.selected.contains(row));
+            let next = match current {
+                Some(position) if up_pressed => Some(position.saturating_sub(1)),
+                Some(position) if down_pressed => Some((position + 1).min(visible.len() - 1)),
+                Some(position) => Some(position),
+                None if !visible.is_empty()
+                    && (up_pressed || down_pressed || !state.filter.is_empty()) =>
+                {
+                    Some(0)
+                }
+                None => None,
+            };
+            if next != current {
+                state.selected = next.map(|position| visible[position]).into_iter().collect();
+                response.changed = true;
+            }
+        }
+        let can_confirm = !state.selected.is_empty();
+
+        let text_box_frame = field_frame(theme, rtl);
+        let filter_has_focus = ctx.memory(|memory| memory.has_focus(filter_id));
+        let text_box_stroke = field_ring(
+            theme,
+            rtl,
+            if filter_has_focus {
+                stroke_color
+            } else {
+                Color32::TRANSPARENT
+            },
+        );
+
+        egui::CentralPanel::default()
+            .frame(dialog_frame(theme, rtl))
+            .show(ctx, |ui| {
+                ui.with_layout(egui::Layout::top_down(leading_align(rtl)), |ui| {
+                    ui.horizontal(|ui| {
+                        ui.add_space(theme.margins.content_indent);
+                        ui.add(
+                            egui::Label::new(
+                                egui::RichText::new(messages.visual(&self.heading))
+                                    .color(theme.colors.text)
+                                    .text_style(Name("DialogHeading".into())),
+                            )
+                            .selectable(false),
+                        );
+                    });
+                    if !self.body.is_empty() {
+                        ui.horizontal(|ui| {
+                            ui.add_space(theme.margins.content_indent);
+                            ui.add(
+                                egui::Label::new(
+                                    egui::RichText::new(visual_lines(
+                                        ui,
+                                        messages,
+                                        &self.body,
+                                        &Name("DialogBody".into()),
+                                    ))
+                                    .color(theme.colors.text)
+                                    .text_style(Name("DialogBody".into())),
+                                )
+                                .wrap()
+                                .selectable(false),
+                            );
+                        });
+                    }
+                    ui.add_space(4.5);
+
+                    // Type-ahead filter, focused from the start
+                    text_box_stroke.show(ui, |ui| {
+                        text_box_frame.show(ui, |ui| {
+                            ui.visuals_mut().selection.bg_fill = selection_color;
+                            let output = egui::TextEdit::singleline(&mut state.filter)
+                                .id(filter_id)
+                                .hint_text(
+                                    egui::RichText::new(&self.placeholder)
+                                        .color(theme.colors.disabled_text),
+                                )
+                                .desired_width(f32::INFINITY)
+                                .font(Name("TextInputBody".into()))
+                                .margin(theme.margins.field_text)
+                                .horizontal_align(leading_align(rtl))
+                                .background_color(theme.colors.field_fill)
+                                .text_color(theme.colors.field_text)
+                                .frame(false)
+                                .show(ui);
+                            let focus_requested = ui.data_mut(|data| {
+                                std::mem::replace(
+                                    data.get_temp_mut_or(focus_requested_id, false),
+                                    true,
+                                )
+                            });
+                            if !focus_requested {
+                                output.response.request_focus();
+                            }
+                            if output.response.changed() {
+                                visible = state.visible_rows(table);
+                                response.changed = true;
+                            }
+                        });
+                    });
+                    ui.add_space(6.);
+
+                    // Buttons first, from the bottom, so the table takes the height left between
+                    ui.with_layout(egui::Layout::bottom_up(leading_align(rtl)), |ui| {
+                        ui.with_layout(leading_layout(!rtl, egui::Align::BOTTOM), |ui| {
+                            ui.add_space(3.);
+
+                            button_style(ui, theme);
+
+                            let ok_button = ok_button(
+                                &self.ok_label,
+                                ok_button_color,
+                                can_confirm,
+                                theme,
+                                messages,
+                            );
+                            let ok_response = ui.add_enabled(can_confirm, ok_button);
+                            draw_focus_ring(ui, &ok_response, stroke_color, theme);
+                            response.confirmed |= ok_response.clicked();
+
+                            ui.add_space(0.1); // Space between buttons
+
+                            let cancel_button = cancel_button(&self.cancel_label, theme, messages);
+                            let cancel_response = ui.add(cancel_button);
+                            draw_focus_ring(ui, &cancel_response, stroke_color, theme);
+                            response.cancelled = cancel_response.clicked();
+                        });
+                        ui.add_space(8.);
+
+                        ui.with_layout(egui::Layout::top_down(leading_align(rtl)), |ui| {
+                            let table_frame = list_frame(
+                                theme,
+                                mirrored_margin(theme.margins.field_ring, rtl),
+                                theme.colors.background,
+                            );
+                            table_frame.show(ui, |ui| {
+                                ui.set_min_size(ui.available_size());
+                                let mut table_view = TableView {
+                                    table,
+                                    visible: &visible,
+                                    state: &mut *state,
+                                    multiple: self.multiple,
+                                    selection_color,
+                                    rtl,
+                                    theme,
+                                    messages,
+                                };
+                                let table_response = table_view.show(ui, &self.empty_text);
+                                response.changed |= table_response.changed;
+                                response.confirmed |= table_response.confirmed;
+                            });
+                        });
+                    });
+                });
+            });
+
+        if cancel_pressed {
+            response.cancelled = true;
+        } else if return_pressed && can_confirm {
+            response.confirmed = true;
+        }
+        response.confirmed &= !state.selected.is_empty();
+        response
+    }
+}
+
+/// The header and rows of a [`ListDialog`], in columns as wide as their widest cell
+struct TableView<'a> {
+    table: &'a Table,
+    visible: &'a [usize],
+    state: &'a mut ListState,
+    multiple: bool,
+    selection_color: Color32,
+    rtl: bool,
+    theme: &'a Theme,
+    messages: &'a Messages,
+}
+
+impl TableView<'_> {
+    const CELL_PADDING: f32 = 6.0;
+    const CHECKBOX_WIDTH: f32 = 22.0;
+    const MAX_COLUMN_WIDTH: f32 = 240.0;
+
+    fn show(&mut self, ui: &mut egui::Ui, empty_text: &str) -> InputResponse {
+        let mut response = InputResponse::default();
+        let font_id = Name("DialogBody".into()).resolve(ui.style());
+        let row_height = ui.fonts(|fonts| fonts.row_height(&font_id)) + 6.0;
+        let text_width = |ui: &egui::Ui, text: &str| {
+            ui.fonts(|fonts| {
+                fonts
+                    .layout_no_wrap(text.to_owned(), font_id.clone(), Color32::PLACEHOLDER)
+                    .size()
+                    .x
+            })
+        };
+
+        // Room for the sort arrow in the header
+        let widths: Vec<f32> = (0..self.table.columns.len())
+            .map(|column| {
+                let header = text_width(ui, &self.table.columns[column]) + 14.0;
+                let widest_cell = self
+                    .table
+                    .rows
+                    .iter()
+                    .map(|row| text_width(ui, &row[column]))
+                    .fold(header, f32::max);
+                widest_cell.min(Self::MAX_COLUMN_WIDTH) + 2.0 * Self::CELL_PADDING
+            })
+            .collect();
+        let leading_width = if self.multiple {
+            Self::CHECKBOX_WIDTH
+        } else {
+            0.0
+        };
+        let row_width = (leading_width + widths.iter().sum::<f32>()).max(ui.available_width());
+
+        // Header, sorting by a column on click and reversing on the next one
+        let (header_rect, _) =
+            ui.allocate_exact_size(egui::vec2(row_width, row_height), egui::Sense::hover());
+        ui.painter()
+            .rect_filled(header_rect, 0.0, self.theme.colors.field_fill);
+        for (column, rect) in self.cell_rects(header_rect, leading_width, &widths) {
+            let cell_response = ui.interact(
+                rect,
+                ui.id().with(("list_header", column)),
+                egui::Sense::click(),
+            );
+            if cell_response.clicked() {
+                self.state.sort = match self.state.sort {
+                    Some(sort) if sort.column == column => Some(SortOrder {
+                        column,
+                        descending: !sort.descending,
+                    }),
+                    _ => Some(SortOrder {
+                        column,
+                        descending: false,
+                    }),
+                };
+                response.changed = true;
+            }
+            let arrow = match self.state.sort {
+                Some(SortOrder {
+                    column: sorted,
+                    descending,
+                }) if sorted == column => {
+                    if descending {
+                        " ▼"
+                    } else {
+                        " ▲"
+                    }
+                }
+                _ => "",
+            };
+            let title = format!("{}{arrow}", self.table.columns[column]);
+            self.paint_cell(ui, rect, &title, self.theme.colors.text);
+        }
+
+        if self.visible.is_empty() {
+            ui.add_space(8.);
+            ui.horizontal(|ui| {
+                ui.add_space(Self::CELL_PADDING);
+                ui.label(
+                    egui::RichText::new(self.messages.visual(empty_text))
+                        .color(self.theme.colors.disabled_text)
+                        .text_style(Name("DialogBody".into())),
+                );
+            });
+            return response;
+        }
+
+        egui::ScrollArea::both()
+            .auto_shrink([false, false])
+            .show_rows(ui, row_height, self.visible.len(), |ui, range| {
+                for position in range {
+                    let row = self.visible[position];
+                    let (rect, row_response) = ui.allocate_exact_size(
+                        egui::vec2(row_width, row_height),
+                        egui::Sense::click(),
+                    );
+                    let selected = self.state.selected.contains(&row);
+                    let fill = if selected {
+                        self.selection_color
+                    } else if position % 2 == 1 {
+                        self.theme.colors.field_fill
+                    } else {
+                        Color32::TRANSPARENT
+                    };
+                    ui.painter().rect_filled(rect, 0.0, fill);
+
+                    if self.multiple {
+                        let checkbox_rect = if self.rtl {
+                            egui::Rect::from_min_size(
+                                egui::pos2(rect.right() - Self::CHECKBOX_WIDTH, rect.top()),
+                                egui::vec2(Self::CHECKBOX_WIDTH, rect.height()),
+                            )
+                        } else {
+                            egui::Rect::from_min_size(
+                                rect.min,
+                                egui::vec2(Self::CHECKBOX_WIDTH, rect.height()),
+                            )
+                        };
+                        let box_rect = egui::Rect::from_center_size(
+                            checkbox_rect.center(),
+                            egui::vec2(12.0, 12.0),
+                        );
+                        ui.painter().rect(
+                            box_rect,
+                            2.0,
+                            self.theme.colors.field_fill,
+                            Stroke::new(1.0, self.theme.colors.field_border),
+                        );
+                        if selected {
+                            ui.painter().text(
+                                box_rect.center(),
+                                egui::Align2::CENTER_CENTER,
+                                "✔",
+                                egui::FontId::proportional(10.0),
+                                self.theme.colors.field_text,
+                            );
+                        }
+                    }
+                    for (column, cell_rect) in self.cell_rects(rect, leading_width, &widths) {
+                        self.paint_cell(
+                            ui,
+                            cell_rect,
+                            &self.table.rows[row][column],
+                            self.theme.colors.field_text,
+                        );
+                    }
+
+                    if row_response.clicked() {
+                        if self.multiple && !self.state.selected.remove(&row) {
+                            self.state.selected.insert(row);
+                        } else if !self.multiple {
+                            self.state.selected = BTreeSet::from([row]);
+                        }
+                        response.changed = true;
+                    }
+                    if row_response.double_clicked() && !self.multiple {
+                        response.confirmed = true;
+                    }
+                }
+            });
+        response
+    }
+
+    /// Where each column of a row goes, from the leading edge
+    fn cell_rects(
+        &self,
+        row: egui::Rect,
+        leading_width: f32,
+        widths: &[f32],
+    ) -> Vec<(usize, egui::Rect)> {
+        let mut offset = leading_width;
+        widths
+            .iter()
+            .enumerate()
+            .map(|(column, &width)| {
+                let left = if self.rtl {
+                    row.right() - offset - width
+                } else {
+                    row.left() + offset
+                };
+                offset += width;
+                (
+                    column,
+                    egui::Rect::from_min_size(
+                        egui::pos2(left, row.top()),
+                        egui::vec2(width, row.height()),
+                    ),
+                )
+            })
+            .collect()
+    }
+
+    /// Draw a cell's text, cut off at the cell edge
+    fn paint_cell(&self, ui: &egui::Ui, rect: egui::Rect, text: &str, color: Color32) {
+        let font_id = Name("DialogBody".into()).resolve(ui.style());
+        let (position, align) = if self.rtl {
+            (
+                egui::pos2(rect.right() - Self::CELL_PADDING, rect.center().y),
+                egui::Align2::RIGHT_CENTER,
+            )
+        } else {
+            (
+                egui::pos2(rect.left() + Self::CELL_PADDING, rect.center().y),
+                egui::Align2::LEFT_CENTER,
+            )
+        };
+        ui.painter()
+            .with_clip_rect(
+                rect.shrink2(egui::vec2(Self::CELL_PADDING / 2.0, 0.0))
+                    .intersect(ui.clip_rect()),
+            )
+            .text(position, align, self.messages.visual(text), font_id, color);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mixed_column_sorts_numbers_before_text() {
+        let cells = ["10", "b", "2", "A", "1.5", "-3"];
+        let table = Table::new(
+            Vec::new(),
+            cells.iter().map(|cell| vec![cell.to_string()]).collect(),
+        );
+        let mut state = ListState {
+            sort: Some(SortOrder {
+                column: 0,
+                descending: false,
+            }),
+            ..ListState::default()
+        };
+        let sorted = |state: &ListState| -> Vec<&str> {
+            state
+                .visible_rows(&table)
+                .into_iter()
+                .map(|index| cells[index])
+                .collect()
+        };
+        assert_eq!(sorted(&state), ["-3", "1.5", "2", "10", "A", "b"]);
+
+        state.sort = Some(SortOrder {
+            column: 0,
+            descending: true,
+        });
+        assert_eq!(sorted(&state), ["b", "A", "10", "2", "1.5", "-3"]);
+    }
+
+    #[test]
+    fn only_numerals_count_as_numbers() {
+        for cell in [
+            "0", "-3", "+4", "1.5", "2.", " 42 ", "\t7\n", "1e3", "2.5E-2",
+        ] {
+            assert!(parse_number(cell).is_some(), "{cell:?} is a number");
+        }
+        for cell in [
+            "", " ", "NaN", "nan", "inf", "-inf", "Infinity", ".5", "1e", "1..2", "0x1F",
+        ] {
+            assert_eq!(parse_number(cell), None, "{cell:?} is text");
+        }
+        assert_eq!(parse_number(" 2e3 "), Some(2000.0));
+    }
+
+    #[test]
+    fn words_sort_as_text_among_numbers() {
+        let cells = ["inf", " 10", "", "NaN", "9 ", "apple", "-1e1"];
+        let table = Table::new(
+            Vec::new(),
+            cells.iter().map(|cell| vec![cell.to_string()]).collect(),
+        );
+        let state = ListState {
+            sort: Some(SortOrder {
+                column: 0,
+                descending: false,
+            }),
+            ..ListState::default()
+        };
+        let sorted: Vec<&str> = state
+            .visible_rows(&table)
+            .into_iter()
+            .map(|index| cells[index])
+            .collect();
+        assert_eq!(sorted, ["-1e1", "9 ", " 10", "", "apple", "inf", "NaN"]);
+    }
+}
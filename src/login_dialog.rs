@@ -0,0 +1,342 @@
+use egui::Color32;
+use egui::TextStyle::*;
+use zeroize::Zeroizing;
+
+use crate::i18n::Messages;
+use crate::input_dialog::{
+    button_style, cancel_button, dialog_frame, dialog_keys, draw_focus_ring, field_frame,
+    field_ring, focus_colors, leading_align, leading_layout, ok_button, visual_lines,
+};
+use crate::theme::Theme;
+use crate::{Feedback, InputResponse};
+
+/// A user name and password typed into the dialog.
+///
+/// Its `Debug` output leaves the password out, so it can't end up in logs.
+#[derive(Clone, Default)]
+pub struct Credential {
+    pub username: String,
+    pub password: Zeroizing<String>,
+}
+
+impl std::fmt::Debug for Credential {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Credential")
+            .field("username", &self.username)
+            .field("password", &"<redacted>")
+            .finish()
+    }
+}
+
+/// A titled user name and password form with OK/Cancel buttons, in the look of
+/// [`crate::InputDialog`]: for git, proxy or server sign-in prompts.
+///
+/// The password is masked, with a checkbox to show it, and left out of the undo history.
+pub struct LoginDialog<'a> {
+    username: &'a mut String,
+    password: &'a mut String,
+    heading: String,
+    body: String,
+    username_label: String,
+    password_label: String,
+    ok_label: String,
+    cancel_label: String,
+    feedback: Feedback,
+}
+
+impl<'a> LoginDialog<'a> {
+    /// Dialog titled `heading` editing `username` and `password`
+    pub fn new(
+        heading: impl Into<String>,
+        username: &'a mut String,
+        password: &'a mut String,
+    ) -> Self {
+        Self {
+            username,
+            password,
+            heading: heading.into(),
+            body: String::new(),
+            username_label: "User name:".to_owned(),
+            password_label: "Password:".to_owned(),
+            ok_label: "OK".to_owned(),
+            cancel_label: "Cancel".to_owned(),
+            feedback: Feedback::None,
+        }
+    }
+
+    /// Setter for the text under the heading, e.g. what the credentials are for
+    pub fn with_body(mut self, body: impl Into<String>) -> Self {
+        self.body = body.into();
+        self
+    }
+
+    /// Setter for the labels in front of the fields
+    pub fn with_field_labels(
+        mut self,
+        username: impl Into<String>,
+        password: impl Into<String>,
+    ) -> Self {
+        self.username_label = username.into();
+        self.password_label = password.into();
+        self
+    }
+
+    /// Setter for the default button label
+    pub fn with_ok_label(mut self, label: impl Into<String>) -> Self {
+        self.ok_label = label.into();
+        self
+    }
+
+    /// Setter for the Cancel button label
+    pub fn with_cancel_label(mut self, label: impl Into<String>) -> Self {
+        self.cancel_label = label.into();
+        self
+    }
+
+    /// Setter for what the owner has to say, shown under the fields
+    pub fn with_feedback(mut self, feedback: Feedback) -> Self {
+        self.feedback = feedback;
+        self
+    }
+
+    /// Lay the dialog out in the central panel and report what the user did
+    pub fn show(self, ctx: &egui::Context, theme: &Theme, messages: &Messages) -> InputResponse {
+        let mut response = InputResponse::default();
+        let rtl = messages.is_rtl();
+        let username_id = egui::Id::new("login_dialog_username");
+        let password_id = egui::Id::new("login_dialog_password");
+        let focus_requested_id = username_id.with("focus_requested");
+        let revealed_id = password_id.with("revealed");
+        let mut revealed = ctx.data(|data| data.get_temp(revealed_id).unwrap_or(false));
+
+        let (return_pressed, cancel_pressed) = dialog_keys(ctx);
+        let (stroke_color, selection_color, ok_button_color) = focus_colors(ctx, theme);
+
+        let text_box_frame = field_frame(theme, rtl);
+        let text_box_stroke = |has_focus: bool| {
+            field_ring(
+                theme,
+                rtl,
+                if has_focus {
+                    stroke_color
+                } else {
+                    Color32::TRANSPARENT
+                },
+            )
+        };
+
+        egui::CentralPanel::default()
+            .frame(dialog_frame(theme, rtl))
+            .show(ctx, |ui| {
+                ui.with_layout(egui::Layout::top_down(leading_align(rtl)), |ui| {
+                    ui.horizontal(|ui| {
+                        ui.add_space(theme.margins.content_indent);
+                        ui.add(
+                            egui::Label::new(
+                                egui::RichText::new(messages.visual(&self.heading))
+                                    .color(theme.colors.text)
+                                    .text_style(Name("DialogHeading".into())),
+                            )
+                            .selectable(false),
+                        );
+                    });
+                    if !self.body.is_empty() {
+                        ui.horizontal(|ui| {
+                            ui.add_space(theme.margins.content_indent);
+                            ui.add(
+                                egui::Label::new(
+                                    egui::RichText::new(visual_lines(
+                                        ui,
+                                        messages,
+                                        &self.body,
+                                        &Name("DialogBody".into()),
+                                    ))
+                                    .color(theme.colors.text)
+                                    .text_style(Name("DialogBody".into())),
+                                )
+                                .wrap()
+                                .selectable(false),
+                            );
+                        });
+                    }
+                    ui.add_space(4.5);
+
+                    // Labels share a column, so both fields start at the same place
+                    let label_width = [&self.username_label, &self.password_label]
+                        .iter()
+                        .map(|label| {
+                            let font_id = Name("DialogBody".into()).resolve(ui.style());
+                            ui.fonts(|fonts| {
+                                fonts
+                                    .layout_no_wrap(
+                                        messages.visual(label),
+                                        font_id,
+                                        Color32::PLACEHOLDER,
+                                    )
+                                    .size()
+                                    .x
+                            })
+                        })
+                        .fold(0.0, f32::max);
+                    let fields = [
+                        (
+                            &self.username_label,
+                            username_id,
+                            &mut *self.username,
+                            false,
+                        ),
+                        (
+                            &self.password_label,
+                            password_id,
+                            &mut *self.password,
+                            !revealed,
+                        ),
+                    ];
+                    for (label, id, value, masked) in fields {
+                        ui.horizontal(|ui| {
+                            ui.add_space(theme.margins.content_indent);
+                            ui.allocate_ui_with_layout(
+                                [label_width, 20.].into(),
+                                leading_layout(rtl, egui::Align::Center),
+                                |ui| {
+                                    ui.add(
+                                        egui::Label::new(
+                                            egui::RichText::new(messages.visual(label))
+                                                .color(theme.colors.text)
+                                                .text_style(Name("DialogBody".into())),
+                                        )
+                                        .selectable(false),
+                                    );
+                                },
+                            );
+                            let has_focus = ui.memory(|memory| memory.has_focus(id));
+                            text_box_stroke(has_focus).show(ui, |ui| {
+                                text_box_frame.show(ui, |ui| {
+                                    ui.visuals_mut().selection.bg_fill = selection_color;
+                                    let mut output = egui::TextEdit::singleline(value)
+                                        .id(id)
+                                        .desired_width(f32::INFINITY)
+                                        .font(Name("TextInputBody".into()))
+                                        .margin(theme.margins.field_text)
+                                        .horizontal_align(leading_align(rtl))
+                                        .background_color(theme.colors.field_fill)
+                                        .text_color(theme.colors.field_text)
+                                        .frame(false)
+                                        .password(masked)
+                                        .show(ui);
+                                    response.changed |= output.response.changed();
+                                    if id == password_id {
+                                        // Undo history keeps copies of the text, a password must not linger there
+                                        output.state.clear_undoer();
+                                        output.state.store(ui.ctx(), output.response.id);
+                                    }
+                                });
+                            });
+                        });
+                    }
+
+                    // The first empty field gets focus once, so Tab can move it
+                    let focus_requested = ctx.data_mut(|data| {
+                        std::mem::replace(data.get_temp_mut_or(focus_requested_id, false), true)
+                    });
+                    if !focus_requested {
+                        let id = if self.username.is_empty() {
+                            username_id
+                        } else {
+                            password_id
+                        };
+                        ctx.memory_mut(|memory| memory.request_focus(id));
+                    }
+
+                    ui.horizontal(|ui| {
+                        ui.add_space(
+                            theme.margins.content_indent
+                                + label_width
+                                + ui.spacing().item_spacing.x,
+                        );
+                        let toggled = ui.checkbox(
+                            &mut revealed,
+                            egui::RichText::new(messages.visual(messages.get("show_secret")))
+                                .color(theme.colors.text)
+                                .text_style(Name("DialogBody".into())),
+                        );
+                        if toggled.changed() {
+                            ui.data_mut(|data| data.insert_temp(revealed_id, revealed));
+                        }
+                    });
+
+                    if let Feedback::Invalid(message) | Feedback::Failed(message) = &self.feedback {
+                        ui.add_space(2.);
+                        ui.horizontal(|ui| {
+                            ui.add_space(theme.margins.content_indent);
+                            ui.add(
+                                egui::Label::new(
+                                    egui::RichText::new(visual_lines(
+                                        ui,
+                                        messages,
+                                        message,
+                                        &Name("DialogBody".into()),
+                                    ))
+                                    .color(theme.colors.error)
+                                    .text_style(Name("DialogBody".into())),
+                                )
+                                .wrap()
+                                .selectable(false),
+                            );
+                        });
+                    }
+
+                    ui.with_layout(leading_layout(!rtl, egui::Align::BOTTOM), |ui| {
+                        ui.add_space(3.);
+
+                        button_style(ui, theme);
+
+                        let ok_button =
+                            ok_button(&self.ok_label, ok_button_color, true, theme, messages);
+                        let ok_response = ui.add(ok_button);
+                        draw_focus_ring(ui, &ok_response, stroke_color, theme);
+                        response.confirmed = ok_response.clicked();
+
+                        ui.add_space(0.1); // Space between buttons
+
+                        let cancel_button = cancel_button(&self.cancel_label, theme, messages);
+                        let cancel_response = ui.add(cancel_button);
+                        draw_focus_ring(ui, &cancel_response, stroke_color, theme);
+                        response.cancelled = cancel_response.clicked();
+                    });
+                });
+            });
+
+        if cancel_pressed {
+            response.cancelled = true;
+        } else if return_pressed {
+            response.confirmed = true;
+        }
+        response
+    }
+
+    /// Drop what egui remembers of the fields, once the credentials were delivered
+    pub fn forget_fields(ctx: &egui::Context) {
+        for id in ["login_dialog_username", "login_dialog_password"] {
+            egui::text_edit::TextEditState::default().store(ctx, egui::Id::new(id));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::DialogValue;
+
+    #[test]
+    fn debug_redacts_the_password() {
+        let credential = Credential {
+            username: "ana".to_owned(),
+            password: Zeroizing::new("hunter2".to_owned()),
+        };
+        let debug = format!("{credential:?}");
+        assert!(debug.contains("\"ana\""), "{debug}");
+        assert!(!debug.contains("hunter2"), "{debug}");
+        assert!(!format!("{:?}", DialogValue::from(credential)).contains("hunter2"));
+    }
+}
@@ -1,40 +1,741 @@
 #![warn(clippy::all, rust_2018_idioms)]
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")] // hide console window on Windows in release
 
-use std::io::{self, Write};
-use std::sync::{Arc, Mutex};
+#[cfg(not(target_arch = "wasm32"))]
+use std::path::PathBuf;
+#[cfg(not(target_arch = "wasm32"))]
+use std::sync::mpsc;
+
+#[cfg(not(target_arch = "wasm32"))]
+use clap::Parser as _;
+#[cfg(not(target_arch = "wasm32"))]
+use eframe_test::askpass::{self, Prompt};
+#[cfg(not(target_arch = "wasm32"))]
+use eframe_test::cli::{Cli, OutputFormat};
+#[cfg(not(target_arch = "wasm32"))]
+use eframe_test::git_credential;
+#[cfg(not(target_arch = "wasm32"))]
+use eframe_test::i18n::{detect_locale, Messages};
+#[cfg(not(target_arch = "wasm32"))]
+use eframe_test::output::{write_outcome, write_rows, Timing, ValueKind};
+#[cfg(not(target_arch = "wasm32"))]
+use eframe_test::pinentry::{self, Request};
+#[cfg(not(target_arch = "wasm32"))]
+use eframe_test::theme::ThemeSet;
+#[cfg(not(target_arch = "wasm32"))]
+use eframe_test::zenity::{self, ZenityCli};
+#[cfg(not(target_arch = "wasm32"))]
+use eframe_test::{ButtonRole, DialogKind, DialogOutcome, MessageButton, MessageKind};
+#[cfg(not(target_arch = "wasm32"))]
+use zeroize::Zeroize as _;
 
 // When compiling natively:
 #[cfg(not(target_arch = "wasm32"))]
-fn main() -> eframe::Result {
+fn main() {
     env_logger::init(); // Log to stderr (if you run with `RUST_LOG=debug`).
 
+    let args: Vec<_> = std::env::args_os().collect();
+    if askpass::is_invocation(&args) {
+        askpass_main(args);
+    }
+    if pinentry::is_invocation(&args) {
+        pinentry_main(args);
+    }
+    if git_credential::is_invocation(&args) {
+        // git runs `git-credential-NAME ACTION`, through a symlink
+        let action = args.get(1).and_then(|action| {
+            <git_credential::Action as clap::ValueEnum>::from_str(&action.to_string_lossy(), false)
+                .ok()
+        });
+        match action {
+            Some(action) => git_credential_main(action),
+            // Unknown actions are to be ignored, so later versions of git can add some
+            None => std::process::exit(0),
+        }
+    }
+    if zenity::is_invocation(&args) {
+        zenity_main(args);
+    }
+
+    let timing = Timing::start();
+    let cli = Cli::parse_from(args);
+    let output_format = cli.output;
+    if let Some(action) = cli.git_credential {
+        git_credential_main(action);
+    }
+    if let Some(kind) = cli.message {
+        message_main(kind, cli, &timing);
+    }
+    if cli.secret {
+        secret_main(cli, &timing);
+    }
+    if cli.list {
+        list_main(cli, &timing);
+    }
+    if cli.progress {
+        progress_main(cli, &timing);
+    }
+    if cli.open || cli.save {
+        file_main(cli, &timing);
+    }
+
+    let parent_dir = match cli.parent_dir() {
+        Ok(path) => path,
+        Err(error) => report(
+            DialogOutcome::Error(format!("Failed to read the parent folder: {error}")),
+            output_format,
+            ValueKind::Path,
+            &timing,
+        ),
+    };
+
+    // Without a valid parent there is nothing the dialog could create
+    if !parent_dir.is_dir() {
+        report(
+            DialogOutcome::Error(format!("{} is not a directory", parent_dir.display())),
+            output_format,
+            ValueKind::Path,
+            &timing,
+        );
+    }
+
+    // Renaming needs something to rename, links included
+    if let Some(path) = &cli.rename {
+        if path.symlink_metadata().is_err() {
+            report(
+                DialogOutcome::Error(format!("{} doesn't exist", path.display())),
+                output_format,
+                ValueKind::Path,
+                &timing,
+            );
+        }
+    }
+
+    let themes = match ThemeSet::load(&cli.theme) {
+        Ok(themes) => themes,
+        Err(error) => report(
+            DialogOutcome::Error(format!("Failed to load theme {}: {error}", cli.theme)),
+            output_format,
+            ValueKind::Path,
+            &timing,
+        ),
+    };
+
+    // Strings the user didn't give come from the catalog of their language
+    let messages = Messages::new(&cli.locale());
+    let title_key = if cli.rename.is_some() {
+        "title_rename"
+    } else {
+        "title"
+    };
+    let title = cli
+        .title
+        .unwrap_or_else(|| messages.get(title_key).to_owned());
+    let default_name = cli
+        .default_name
+        .unwrap_or_else(|| messages.get("default_folder_name").to_owned());
+
+    // New Folder has a row more, the Where section
+    let inner_size = if cli.rename.is_some() {
+        [321.0, 179.0]
+    } else {
+        [321.0, 199.0]
+    };
+    let outcome = run_dialog(&title, inner_size, |cc| {
+        let mut app =
+            eframe_test::TemplateApp::new_with_fonts(cc, &cli.font_dirs, messages.language())
+                .with_messages(messages);
+        app = match cli.rename {
+            Some(path) => app.with_rename_target(path),
+            None => app
+                .with_parent_dir(parent_dir)
+                .with_default_name(default_name),
+        };
+        app = app
+            .with_title(title.clone())
+            .with_themes(themes)
+            .with_appearance(cli.appearance)
+            .with_normalization(cli.normalization);
+        if let Some(timeout) = cli.timeout {
+            app = app.with_timeout(timeout);
+        }
+        app
+    });
+    report(outcome, output_format, ValueKind::Path, &timing);
+}
+
+/// Message box mode: a question or alert answered with the label of the pressed button
+#[cfg(not(target_arch = "wasm32"))]
+fn message_main(kind: MessageKind, cli: Cli, timing: &Timing) -> ! {
+    let output_format = cli.output;
+    let buttons = match cli.message_buttons() {
+        Ok(buttons) => buttons,
+        Err(message) => report(
+            DialogOutcome::Error(message),
+            output_format,
+            ValueKind::Text,
+            timing,
+        ),
+    };
+    let themes = match ThemeSet::load(&cli.theme) {
+        Ok(themes) => themes,
+        Err(error) => report(
+            DialogOutcome::Error(format!("Failed to load theme {}: {error}", cli.theme)),
+            output_format,
+            ValueKind::Text,
+            timing,
+        ),
+    };
+
+    let messages = Messages::new(&cli.locale());
+    let title = cli
+        .title
+        .clone()
+        .unwrap_or_else(|| messages.get(&format!("title_{}", kind.name())).to_owned());
+    let text = cli
+        .text
+        .clone()
+        .unwrap_or_else(|| messages.get(&format!("text_{}", kind.name())).to_owned());
+
+    let outcome = run_dialog(&title, [372.0, 165.0], |cc| {
+        let mut app =
+            eframe_test::TemplateApp::new_with_fonts(cc, &cli.font_dirs, messages.language())
+                .with_messages(messages)
+                .with_kind(DialogKind::Message(kind))
+                .with_title(title.clone())
+                .with_text(text)
+                .with_buttons(buttons)
+                .with_themes(themes)
+                .with_appearance(cli.appearance);
+        if let Some(timeout) = cli.timeout {
+            app = app.with_timeout(timeout);
+        }
+        app
+    });
+    report(outcome, output_format, ValueKind::Text, timing);
+}
+
+/// Secret mode: a masked field, the secret is printed but never logged
+#[cfg(not(target_arch = "wasm32"))]
+fn secret_main(cli: Cli, timing: &Timing) -> ! {
+    let output_format = cli.output;
+    let themes = match ThemeSet::load(&cli.theme) {
+        Ok(themes) => themes,
+        Err(error) => report(
+            DialogOutcome::Error(format!("Failed to load theme {}: {error}", cli.theme)),
+            output_format,
+            ValueKind::Secret,
+            timing,
+        ),
+    };
+
+    let messages = Messages::new(&cli.locale());
+    let title = cli
+        .title
+        .clone()
+        .unwrap_or_else(|| messages.get("title_password").to_owned());
+    let text = cli
+        .text
+        .clone()
+        .unwrap_or_else(|| messages.get("text_password").to_owned());
+
+    let outcome = run_dialog(&title, [321.0, 179.0], |cc| {
+        let mut app =
+            eframe_test::TemplateApp::new_with_fonts(cc, &cli.font_dirs, messages.language())
+                .with_messages(messages)
+                .with_kind(DialogKind::Password)
+                .with_title(title.clone())
+                .with_text(text)
+                .with_themes(themes)
+                .with_appearance(cli.appearance);
+        if let Some(timeout) = cli.timeout {
+            app = app.with_timeout(timeout);
+        }
+        app
+    });
+    report(outcome, output_format, ValueKind::Secret, timing);
+}
+
+/// Progress mode: a job feeding stdin moves the bar, Cancel is told by the exit code
+#[cfg(not(target_arch = "wasm32"))]
+fn progress_main(cli: Cli, timing: &Timing) -> ! {
+    let output_format = cli.output;
+    let themes = match ThemeSet::load(&cli.theme) {
+        Ok(themes) => themes,
+        Err(error) => report(
+            DialogOutcome::Error(format!("Failed to load theme {}: {error}", cli.theme)),
+            output_format,
+            ValueKind::Text,
+            timing,
+        ),
+    };
+
+    let messages = Messages::new(&cli.locale());
+    let title = cli
+        .title
+        .clone()
+        .unwrap_or_else(|| messages.get("title_progress").to_owned());
+    let text = cli
+        .text
+        .clone()
+        .unwrap_or_else(|| messages.get("text_progress").to_owned());
+
+    let outcome = run_dialog(&title, [321.0, 165.0], |cc| {
+        let updates = eframe_test::progress_dialog::spawn_reader(
+            std::io::BufReader::new(std::io::stdin()),
+            cc.egui_ctx.clone(),
+        );
+        let mut app =
+            eframe_test::TemplateApp::new_with_fonts(cc, &cli.font_dirs, messages.language())
+                .with_messages(messages)
+                .with_kind(DialogKind::Progress)
+                .with_title(title.clone())
+                .with_text(text)
+                .with_themes(themes)
+                .with_appearance(cli.appearance)
+                .with_progress_updates(updates)
+                .with_percentage(cli.percentage)
+                .with_pulsate(cli.pulsate)
+                .with_auto_close(cli.auto_close)
+                .with_cancellable(!cli.no_cancel);
+        if let Some(timeout) = cli.timeout {
+            app = app.with_timeout(timeout);
+        }
+        app
+    });
+    if outcome == DialogOutcome::Cancelled && cli.auto_kill {
+        zenity::terminate_parent();
+    }
+    report(outcome, output_format, ValueKind::Text, timing);
+}
+
+/// List mode: a filterable table, answered with the chosen rows
+#[cfg(not(target_arch = "wasm32"))]
+fn list_main(cli: Cli, timing: &Timing) -> ! {
+    let output_format = cli.output;
+    let table = match cli.list_table() {
+        Ok(table) => table,
+        Err(message) => report_rows(DialogOutcome::Error(message), output_format, &[], timing),
+    };
+    let themes = match ThemeSet::load(&cli.theme) {
+        Ok(themes) => themes,
+        Err(error) => report_rows(
+            DialogOutcome::Error(format!("Failed to load theme {}: {error}", cli.theme)),
+            output_format,
+            &table.columns,
+            timing,
+        ),
+    };
+
+    let messages = Messages::new(&cli.locale());
+    let title = cli
+        .title
+        .clone()
+        .unwrap_or_else(|| messages.get("title_list").to_owned());
+    let text = cli
+        .text
+        .clone()
+        .unwrap_or_else(|| messages.get("text_list").to_owned());
+
+    let outcome: DialogOutcome<Vec<usize>> = run_dialog(&title, [480.0, 360.0], |cc| {
+        let mut app =
+            eframe_test::TemplateApp::new_with_fonts(cc, &cli.font_dirs, messages.language())
+                .with_messages(messages)
+                .with_kind(DialogKind::List)
+                .with_title(title.clone())
+                .with_text(text)
+                .with_table(table.clone())
+                .with_multiple(cli.multiple)
+                .with_themes(themes)
+                .with_appearance(cli.appearance);
+        if let Some(timeout) = cli.timeout {
+            app = app.with_timeout(timeout);
+        }
+        app
+    });
+    // The dialog answers with the indices of the chosen rows
+    let outcome = outcome.map(|indices| {
+        indices
+            .into_iter()
+            .filter_map(|index| table.rows.get(index).cloned())
+            .collect()
+    });
+    report_rows(outcome, output_format, &table.columns, timing);
+}
+
+/// File chooser mode: existing files to open, or where to save one, answered with the paths
+#[cfg(not(target_arch = "wasm32"))]
+fn file_main(cli: Cli, timing: &Timing) -> ! {
+    let output_format = cli.output;
+    // Several files are answered one per line, a single one is a path like the created folder
+    let value_kind = if cli.multiple {
+        ValueKind::Text
+    } else {
+        ValueKind::Path
+    };
+    let start_dir = match cli.parent_dir() {
+        Ok(path) => path,
+        Err(error) => report(
+            DialogOutcome::Error(format!("Failed to read the parent folder: {error}")),
+            output_format,
+            value_kind,
+            timing,
+        ),
+    };
+    if !start_dir.is_dir() {
+        report(
+            DialogOutcome::Error(format!("{} is not a directory", start_dir.display())),
+            output_format,
+            value_kind,
+            timing,
+        );
+    }
+    let themes = match ThemeSet::load(&cli.theme) {
+        Ok(themes) => themes,
+        Err(error) => report(
+            DialogOutcome::Error(format!("Failed to load theme {}: {error}", cli.theme)),
+            output_format,
+            value_kind,
+            timing,
+        ),
+    };
+
+    let messages = Messages::new(&cli.locale());
+    let (kind, title_key) = if cli.save {
+        (DialogKind::SaveFile, "title_save")
+    } else {
+        (DialogKind::OpenFile, "title_open")
+    };
+    let title = cli
+        .title
+        .clone()
+        .unwrap_or_else(|| messages.get(title_key).to_owned());
+    // The Save dialog starts with its default name chosen in the parent directory
+    let start_path = match (cli.save, &cli.default_name) {
+        (true, Some(name)) => start_dir.join(name),
+        (true, None) => start_dir.join(messages.get("default_file_name")),
+        (false, _) => start_dir,
+    };
+
+    let outcome: DialogOutcome<Vec<PathBuf>> = run_dialog(&title, [640.0, 420.0], |cc| {
+        let mut app =
+            eframe_test::TemplateApp::new_with_fonts(cc, &cli.font_dirs, messages.language())
+                .with_messages(messages)
+                .with_kind(kind)
+                .with_title(title.clone())
+                .with_start_path(start_path)
+                .with_file_filters(cli.file_filters())
+                .with_multiple(cli.multiple)
+                .with_themes(themes)
+                .with_appearance(cli.appearance)
+                .with_normalization(cli.normalization);
+        if let Some(timeout) = cli.timeout {
+            app = app.with_timeout(timeout);
+        }
+        app
+    });
+    let outcome = outcome.map(|paths| join_paths(&paths, "\n"));
+    report(outcome, output_format, value_kind, timing);
+}
+
+/// Askpass helper mode for ssh and `sudo -A`: the prompt is the only argument, only the answer
+/// is printed and any other outcome exits with 1
+#[cfg(not(target_arch = "wasm32"))]
+fn askpass_main(args: Vec<std::ffi::OsString>) -> ! {
+    let messages = Messages::new(&detect_locale());
+    let prompt = match args.get(1) {
+        Some(prompt) => prompt.to_string_lossy().into_owned(),
+        None => messages.get("text_password").to_owned(),
+    };
+    let prompt_kind = Prompt::classify(
+        &prompt,
+        std::env::var(askpass::PROMPT_VARIABLE).ok().as_deref(),
+    );
+    let (kind, title_key, inner_size) = match prompt_kind {
+        Prompt::Secret => (DialogKind::Password, "title_password", [321.0, 179.0]),
+        // Host key questions are a few lines long, with the fingerprint
+        Prompt::Confirm => (
+            DialogKind::Message(MessageKind::Question),
+            "title_question",
+            [420.0, 220.0],
+        ),
+        Prompt::Notice => (
+            DialogKind::Message(MessageKind::Info),
+            "title_info",
+            [372.0, 165.0],
+        ),
+    };
+    let title = messages.get(title_key).to_owned();
+
+    let mut outcome: DialogOutcome = run_dialog(&title, inner_size, |cc| {
+        eframe_test::TemplateApp::new_with_fonts(cc, &[], messages.language())
+            .with_messages(messages)
+            .with_kind(kind)
+            .with_title(title.clone())
+            .with_text(prompt)
+    });
+
+    match &outcome {
+        DialogOutcome::Confirmed(secret) if prompt_kind == Prompt::Secret => println!("{secret}"),
+        // ssh takes the literal answer, whatever the button says in the user's language
+        DialogOutcome::Confirmed(_) if prompt_kind == Prompt::Confirm => println!("yes"),
+        DialogOutcome::Error(message) => log::error!("{message}"),
+        _ => {}
+    }
+    if let DialogOutcome::Confirmed(secret) = &mut outcome {
+        secret.zeroize();
+    }
+    std::process::exit(askpass::exit_code(&outcome));
+}
+
+/// pinentry mode for gpg-agent: the Assuan protocol on stdin and stdout, a dialog per GETPIN,
+/// CONFIRM or MESSAGE
+#[cfg(not(target_arch = "wasm32"))]
+fn pinentry_main(args: Vec<std::ffi::OsString>) -> ! {
+    let settings = pinentry::Settings {
+        locale: pinentry::locale_argument(&args),
+        ..Default::default()
+    };
+    let served = pinentry::serve(
+        std::io::stdin().lock(),
+        &mut std::io::stdout().lock(),
+        settings,
+        |request, settings| {
+            let messages = Messages::new(&settings.locale.clone().unwrap_or_else(detect_locale));
+            let (kind, title_key) = match request {
+                Request::GetPin => (DialogKind::Password, "title_password"),
+                Request::Confirm => (DialogKind::Message(MessageKind::Question), "title_question"),
+                Request::Message => (DialogKind::Message(MessageKind::Info), "title_info"),
+            };
+            let title = settings
+                .title
+                .clone()
+                .unwrap_or_else(|| messages.get(title_key).to_owned());
+            let text = settings
+                .description
+                .clone()
+                .or_else(|| settings.prompt.clone())
+                .unwrap_or_default();
+            let ok = settings
+                .ok
+                .clone()
+                .unwrap_or_else(|| messages.get("ok").to_owned());
+            let cancel = settings
+                .cancel
+                .clone()
+                .unwrap_or_else(|| messages.get("cancel").to_owned());
+            let buttons = match request {
+                Request::GetPin => Vec::new(),
+                Request::Confirm => {
+                    let mut buttons = vec![MessageButton::new(ok.clone(), ButtonRole::Default)];
+                    buttons.extend(
+                        settings
+                            .not_ok
+                            .clone()
+                            .map(|label| MessageButton::new(label, ButtonRole::Destructive)),
+                    );
+                    buttons.push(MessageButton::new(cancel.clone(), ButtonRole::Cancel));
+                    buttons
+                }
+                Request::Message => vec![MessageButton::new(ok.clone(), ButtonRole::Default)],
+            };
+            let inner_size = if kind == DialogKind::Password {
+                [321.0, 179.0]
+            } else {
+                [372.0, 165.0]
+            };
+
+            run_dialog(&title, inner_size, |cc| {
+                let mut app =
+                    eframe_test::TemplateApp::new_with_fonts(cc, &[], messages.language())
+                        .with_messages(messages)
+                        .with_kind(kind)
+                        .with_title(title.clone())
+                        .with_text(text)
+                        .with_ok_label(ok)
+                        .with_cancel_label(cancel)
+                        .with_buttons(buttons);
+                if let Some(error) = settings.error.clone() {
+                    app = app.with_error(error);
+                }
+                if let Some(timeout) = settings.timeout {
+                    app = app.with_timeout(timeout);
+                }
+                app
+            })
+        },
+    );
+    if let Err(error) = served {
+        log::error!("pinentry connection failed: {error}");
+        std::process::exit(1);
+    }
+    std::process::exit(0);
+}
+
+/// git credential helper mode: the Sign In dialog answers `get`, nothing is stored. The
+/// dialog is skipped when a canned answer is set, so the protocol can be scripted headlessly.
+#[cfg(not(target_arch = "wasm32"))]
+fn git_credential_main(action: git_credential::Action) -> ! {
+    let served = git_credential::serve(
+        action,
+        std::io::stdin().lock(),
+        &mut std::io::stdout().lock(),
+        |request| {
+            if let Some(answer) = git_credential::canned_answer() {
+                return answer;
+            }
+            let messages = Messages::new(&detect_locale());
+            let title = messages.get("title_login").to_owned();
+            let text = messages.format(
+                "text_login",
+                &[("target", &messages.quote(&request.target()))],
+            );
+            let username = request.get("username").unwrap_or_default().to_owned();
+            run_dialog(&title, [372.0, 200.0], |cc| {
+                eframe_test::TemplateApp::new_with_fonts(cc, &[], messages.language())
+                    .with_messages(messages)
+                    .with_kind(DialogKind::Login)
+                    .with_title(title.clone())
+                    .with_text(text)
+                    .with_entry_text(username)
+            })
+        },
+    );
+    if let Err(error) = served {
+        log::error!("git credential request failed: {error}");
+        std::process::exit(1);
+    }
+    std::process::exit(0);
+}
+
+/// zenity compatible mode: same dialog look, zenity's options, output and exit codes
+#[cfg(not(target_arch = "wasm32"))]
+fn zenity_main(args: Vec<std::ffi::OsString>) -> ! {
+    // zenity exits with -1 on bad options, where clap would use 2
+    let cli = ZenityCli::try_parse_from(args).unwrap_or_else(|error| {
+        error.print().ok();
+        std::process::exit(if error.use_stderr() {
+            zenity::EXIT_ERROR
+        } else {
+            0
+        });
+    });
+    let kind = match cli.kind() {
+        Ok(kind) => kind,
+        Err(message) => {
+            log::error!("{message}");
+            std::process::exit(zenity::EXIT_ERROR);
+        }
+    };
+    let messages = cli.messages();
+    let title = cli.title(kind, &messages);
+    let text = cli.text(kind, &messages);
+    let parent_dir = std::env::current_dir().unwrap_or_default();
+    let file_chooser = matches!(
+        kind,
+        DialogKind::OpenFile | DialogKind::OpenFolder | DialogKind::SaveFile
+    );
+    let default_size = if file_chooser {
+        [640.0, 420.0]
+    } else {
+        [321.0, 165.0]
+    };
+    let inner_size = [
+        cli.width.unwrap_or(default_size[0]),
+        cli.height.unwrap_or(default_size[1]),
+    ];
+
+    let make_app = |cc: &eframe::CreationContext<'_>| {
+        let mut app = eframe_test::TemplateApp::new_with_fonts(cc, &[], messages.language())
+            .with_messages(messages)
+            .with_kind(kind)
+            .with_parent_dir(parent_dir.clone())
+            .with_entry_text(cli.entry_text())
+            .with_title(title.clone())
+            .with_text(text)
+            .with_appearance(cli.appearance);
+        if kind == DialogKind::Progress {
+            let updates = eframe_test::progress_dialog::spawn_reader(
+                std::io::BufReader::new(std::io::stdin()),
+                cc.egui_ctx.clone(),
+            );
+            app = app
+                .with_progress_updates(updates)
+                .with_percentage(cli.percentage)
+                .with_pulsate(cli.pulsate)
+                .with_auto_close(cli.auto_close)
+                .with_cancellable(!cli.no_cancel);
+        }
+        if file_chooser {
+            app = app
+                .with_start_path(cli.filename.clone().unwrap_or_else(|| parent_dir.clone()))
+                .with_file_filters(cli.file_filters())
+                .with_multiple(cli.multiple);
+        }
+        if let Some(label) = cli.ok_label.clone() {
+            app = app.with_ok_label(label);
+        }
+        if let Some(label) = cli.cancel_label.clone() {
+            app = app.with_cancel_label(label);
+        }
+        if let Some(seconds) = cli.timeout {
+            app = app.with_timeout(std::time::Duration::from_secs(seconds));
+        }
+        app
+    };
+    // File selections answer with paths, printed with the separator between them
+    let mut outcome: DialogOutcome = if file_chooser {
+        run_dialog::<Vec<PathBuf>>(&title, inner_size, make_app)
+            .map(|paths| join_paths(&paths, &cli.separator))
+    } else {
+        run_dialog(&title, inner_size, make_app)
+    };
+
+    // Like zenity, only the answer of entries and file selections is printed
+    match &outcome {
+        DialogOutcome::Confirmed(value) if kind.has_field() => println!("{value}"),
+        DialogOutcome::Error(message) => log::error!("{message}"),
+        _ => {}
+    }
+    if let (DialogOutcome::Confirmed(secret), DialogKind::Password) = (&mut outcome, kind) {
+        secret.zeroize();
+    }
+    if outcome == DialogOutcome::Cancelled && cli.auto_kill {
+        zenity::terminate_parent();
+    }
+    std::process::exit(zenity::exit_code(&outcome));
+}
 
-    // Ask for current folder name via CLI
-    print!("Enter current folder name: ");
-    io::stdout().flush().unwrap();
-    
-    let mut folder_name = String::new();
-    io::stdin().read_line(&mut folder_name).expect("Failed to read line");
-    folder_name = folder_name.trim().to_string();
-    
-    println!("Opening dialog for folder: {}", folder_name);
-    
-    // Create a shared result container
-    let result = Arc::new(Mutex::new(String::new()));
-    let result_clone = result.clone();
+/// `paths` on one line each, or with another separator between them
+#[cfg(not(target_arch = "wasm32"))]
+fn join_paths(paths: &[PathBuf], separator: &str) -> String {
+    paths
+        .iter()
+        .map(|path| path.display().to_string())
+        .collect::<Vec<_>>()
+        .join(separator)
+}
 
+/// Show `make_app`'s dialog in a window and wait for its outcome, answered as a `T`
+#[cfg(not(target_arch = "wasm32"))]
+fn run_dialog<T: TryFrom<eframe_test::DialogValue> + 'static>(
+    title: &str,
+    inner_size: [f32; 2],
+    make_app: impl FnOnce(&eframe::CreationContext<'_>) -> eframe_test::TemplateApp,
+) -> DialogOutcome<T> {
+    // The dialog sends its outcome exactly once over this channel
+    let (outcome_sender, outcome_receiver) = mpsc::channel();
 
     let native_options = eframe::NativeOptions {
         viewport: egui::ViewportBuilder::default()
-            .with_inner_size([321.0, 165.0]) //[321.0, 165.0]
+            .with_inner_size(inner_size) //[321.0, 165.0]
             // .with_min_inner_size([300.0, 220.0])
             // .with_icon(
             //     // NOTE: Adding an icon is optional
             //     eframe::icon_data::from_png_bytes(&include_bytes!("../assets/icon-256.png")[..])
             //         .expect("Failed to load icon"),
             // )
-            .with_title("")
+            .with_title(title)
             .with_resizable(true)
             // .with_mouse_passthrough(true)
             .with_active(true)
@@ -49,39 +750,99 @@ fn main() -> eframe::Result {
             .with_titlebar_shown(false)
             .with_fullsize_content_view(true)
             // Window modifiers exclusive to Windows
-            .with_taskbar(false)
-            ,
+            .with_taskbar(false),
         ..Default::default()
     };
-    eframe::run_native(
+    // Earlier the app was wrapped to capture the result on exit:
+    // {
+    //   // Pass current_folder value to the app
+    //   let app = eframe_test::TemplateApp::new(cc).with_parent_dir(parent_dir);
+    //   // Wrap the app to capture the result on exit
+    //   Ok(Box::new(AppWrapper {
+    //       app,
+    //       result: result_clone,
+    //   }))
+    // }
+    // cc.raw_window_handle = windowHandler.raw_window_handle().unwrap();
+    let run_result = eframe::run_native(
         "eframe template",
         native_options,
-        Box::new(
-          |cc| 
-          // {
-          //   // Pass current_folder value to the app
-          //   let app = eframe_test::TemplateApp::new(cc).with_current_folder(folder_name);
-          //   // Wrap the app to capture the result on exit
-          //   Ok(Box::new(AppWrapper {
-          //       app,
-          //       result: result_clone,
-          //   }))
-          // }
-          // cc.raw_window_handle = windowHandler.raw_window_handle().unwrap();
-          Ok(Box::new(eframe_test::TemplateApp::new(cc).with_current_folder(folder_name).with_result(result_clone)))
-          )
-    )?;
-
-    // Get the result after the app closes
-    let app_result = result.lock().unwrap();
-    // Verify if app_result is "" or not
-    if app_result.is_empty() {
-        println!("No folder name provided.");
-    } else {
-        println!("New folder name: {}", *app_result);
+        Box::new(|cc| Ok(Box::new(make_app(cc).with_outcome_sender(outcome_sender)))),
+    );
+
+    // Get the outcome after the app closes
+    match run_result {
+        Err(error) => DialogOutcome::Error(error.to_string()),
+        // The sender is dropped with the app, so a missing outcome means it never got to send one
+        Ok(()) => outcome_receiver
+            .try_recv()
+            .unwrap_or(DialogOutcome::Dismissed),
+    }
+}
+
+/// Print the dialog outcome in the requested format and exit with its exit code.
+///
+/// Only the outcome goes to stdout, what happened is also logged on stderr.
+#[cfg(not(target_arch = "wasm32"))]
+fn report(
+    mut outcome: DialogOutcome,
+    output_format: OutputFormat,
+    value_kind: ValueKind,
+    timing: &Timing,
+) -> ! {
+    match &outcome {
+        DialogOutcome::Confirmed(path) if value_kind == ValueKind::Path => {
+            log::info!("Answered with path: {}", path)
+        }
+        DialogOutcome::Confirmed(_) if value_kind == ValueKind::Secret => {
+            log::info!("Secret entered.")
+        }
+        DialogOutcome::Confirmed(label) => log::info!("Answered: {}", label),
+        DialogOutcome::Cancelled => log::info!("Cancelled."),
+        DialogOutcome::Dismissed => log::info!("Dismissed without an answer."),
+        DialogOutcome::Error(message) => log::error!("{}", message),
+        DialogOutcome::TimedOut => log::info!("Timed out without an answer."),
+    }
+    if let Err(error) = write_outcome(
+        &mut std::io::stdout().lock(),
+        output_format,
+        &outcome,
+        value_kind,
+        timing,
+    ) {
+        log::error!("Failed to write the outcome: {error}");
     }
+    if let (DialogOutcome::Confirmed(secret), ValueKind::Secret) = (&mut outcome, value_kind) {
+        secret.zeroize();
+    }
+    std::process::exit(outcome.exit_code());
+}
 
-    Ok(())
+/// Print the outcome of a List dialog in the requested format and exit with its exit code
+#[cfg(not(target_arch = "wasm32"))]
+fn report_rows(
+    outcome: DialogOutcome<Vec<Vec<String>>>,
+    output_format: OutputFormat,
+    columns: &[String],
+    timing: &Timing,
+) -> ! {
+    match &outcome {
+        DialogOutcome::Confirmed(rows) => log::info!("Picked {} row(s).", rows.len()),
+        DialogOutcome::Cancelled => log::info!("Cancelled."),
+        DialogOutcome::Dismissed => log::info!("Dismissed without picking a row."),
+        DialogOutcome::Error(message) => log::error!("{}", message),
+        DialogOutcome::TimedOut => log::info!("Timed out without an answer."),
+    }
+    if let Err(error) = write_rows(
+        &mut std::io::stdout().lock(),
+        output_format,
+        &outcome,
+        columns,
+        timing,
+    ) {
+        log::error!("Failed to write the outcome: {error}");
+    }
+    std::process::exit(outcome.exit_code());
 }
 
 // When compiling to web using trunk:
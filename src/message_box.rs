@@ -0,0 +1,249 @@
+use egui::Color32;
+use egui::Stroke;
+use egui::{FontFamily, FontId};
+
+use egui::TextStyle::*;
+
+use crate::i18n::Messages;
+use crate::input_dialog::{
+    button_style, cancel_button, dialog_button, dialog_frame, dialog_keys, draw_focus_ring,
+    focus_colors, leading_align, leading_layout, ok_button, visual_lines,
+};
+use crate::theme::Theme;
+use crate::MessageKind;
+
+/// What pressing a message box button means, which decides its look and its keyboard shortcut
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(clap::ValueEnum))]
+pub enum ButtonRole {
+    /// The expected answer, drawn in the accent color and pressed with Return
+    Default,
+    /// Backing out, pressed with Escape or Cmd/Ctrl+W
+    Cancel,
+    /// An answer that loses data, e.g. "Don't Save" or "Delete", drawn red
+    Destructive,
+}
+
+/// A button of a [`MessageBox`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MessageButton {
+    pub label: String,
+    pub role: ButtonRole,
+}
+
+impl MessageButton {
+    /// Button labelled `label`
+    pub fn new(label: impl Into<String>, role: ButtonRole) -> Self {
+        Self {
+            label: label.into(),
+            role,
+        }
+    }
+}
+
+/// What happened in the message box during a frame
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct MessageResponse {
+    /// Index of the button that was clicked or reached with its keyboard shortcut
+    pub pressed: Option<usize>,
+}
+
+/// A question or alert: an icon beside a heading and a wrapped body, and one to three buttons
+/// with roles, laid out from the trailing edge in the order given, macOS-style.
+///
+/// Like [`crate::InputDialog`] it is built every frame and fills the central panel with the
+/// look of a [`Theme`], mirrored for right-to-left languages.
+pub struct MessageBox {
+    kind: MessageKind,
+    heading: String,
+    body: String,
+    buttons: Vec<MessageButton>,
+}
+
+impl MessageBox {
+    /// Message box titled `heading` with the icon of `kind` and a single OK button
+    pub fn new(kind: MessageKind, heading: impl Into<String>) -> Self {
+        Self {
+            kind,
+            heading: heading.into(),
+            body: String::new(),
+            buttons: vec![MessageButton::new("OK", ButtonRole::Default)],
+        }
+    }
+
+    /// Setter for the text under the heading
+    pub fn with_body(mut self, body: impl Into<String>) -> Self {
+        self.body = body.into();
+        self
+    }
+
+    /// Setter for the buttons, the first one at the trailing edge.
+    ///
+    /// # Panics
+    /// Without buttons or with more than three.
+    pub fn with_buttons(mut self, buttons: Vec<MessageButton>) -> Self {
+        assert!(
+            (1..=3).contains(&buttons.len()),
+            "a message box has one to three buttons"
+        );
+        self.buttons = buttons;
+        self
+    }
+
+    /// Lay the message box out in the central panel and report which button was pressed
+    pub fn show(self, ctx: &egui::Context, theme: &Theme, messages: &Messages) -> MessageResponse {
+        let mut response = MessageResponse::default();
+        let rtl = messages.is_rtl();
+
+        // Return presses the default button; Escape the cancel one, or the only button there is
+        let (return_pressed, cancel_pressed) = dialog_keys(ctx);
+        let default_index = self
+            .buttons
+            .iter()
+            .position(|button| button.role == ButtonRole::Default);
+        let cancel_index = self
+            .buttons
+            .iter()
+            .position(|button| button.role == ButtonRole::Cancel)
+            .or((self.buttons.len() == 1).then_some(0));
+
+        let (stroke_color, _, default_button_color) = focus_colors(ctx, theme);
+
+        egui::CentralPanel::default()
+            .frame(dialog_frame(theme, rtl))
+            .show(ctx, |ui| {
+                ui.with_layout(egui::Layout::top_down(leading_align(rtl)), |ui| {
+                    ui.horizontal_top(|ui| {
+                        ui.add_space(theme.margins.content_indent);
+                        draw_icon(ui, self.kind, theme);
+                        ui.add_space(10.);
+                        ui.with_layout(egui::Layout::top_down(leading_align(rtl)), |ui| {
+                            ui.add(
+                                egui::Label::new(
+                                    egui::RichText::new(visual_lines(
+                                        ui,
+                                        messages,
+                                        &self.heading,
+                                        &Name("DialogHeading".into()),
+                                    ))
+                                    .color(theme.colors.text)
+                                    .text_style(Name("DialogHeading".into())),
+                                )
+                                .wrap()
+                                .selectable(false),
+                            );
+                            if !self.body.is_empty() {
+                                ui.add_space(4.);
+                                ui.add(
+                                    egui::Label::new(
+                                        egui::RichText::new(visual_lines(
+                                            ui,
+                                            messages,
+                                            &self.body,
+                                            &Name("DialogBody".into()),
+                                        ))
+                                        .color(theme.colors.text)
+                                        .text_style(Name("DialogBody".into())),
+                                    )
+                                    .wrap()
+                                    .selectable(false),
+                                );
+                            }
+                        });
+                    });
+                    ui.add_space(12.);
+
+                    ui.with_layout(leading_layout(!rtl, egui::Align::BOTTOM), |ui| {
+                        ui.add_space(3.);
+
+                        button_style(ui, theme);
+
+                        for (index, button) in self.buttons.iter().enumerate() {
+                            let widget = match button.role {
+                                ButtonRole::Default => ok_button(
+                                    &button.label,
+                                    default_button_color,
+                                    true,
+                                    theme,
+                                    messages,
+                                ),
+                                ButtonRole::Destructive => dialog_button(
+                                    &button.label,
+                                    theme.colors.default_button_text,
+                                    theme.colors.error,
+                                    theme,
+                                    messages,
+                                ),
+                                ButtonRole::Cancel => cancel_button(&button.label, theme, messages),
+                            };
+
+                            let button_response = ui.add(widget);
+                            draw_focus_ring(ui, &button_response, stroke_color, theme);
+                            if button_response.clicked() {
+                                log::debug!("{} clicked", button.label);
+                                response.pressed = Some(index);
+                            }
+                            ui.add_space(0.1); // Space between buttons
+                        }
+                    });
+                });
+            });
+
+        if response.pressed.is_none() {
+            if cancel_pressed {
+                response.pressed = cancel_index;
+            } else if return_pressed {
+                response.pressed = default_index;
+            }
+        }
+        response
+    }
+}
+
+/// Draw the icon of a message kind: a blue "i" or "?", an orange triangle or a red "!"
+fn draw_icon(ui: &mut egui::Ui, kind: MessageKind, theme: &Theme) {
+    let size = 2.0 * theme.font_sizes.heading + 6.0;
+    let (rect, _) = ui.allocate_exact_size(egui::Vec2::splat(size), egui::Sense::hover());
+    let painter = ui.painter();
+    let glyph_font = FontId::new(size * 0.6, FontFamily::Name("System-Text-Heavy".into()));
+    let (glyph, glyph_center, glyph_color) = match kind {
+        MessageKind::Warning => {
+            let points = vec![
+                rect.center_top(),
+                rect.right_bottom() - egui::vec2(0.0, size * 0.07),
+                rect.left_bottom() - egui::vec2(0.0, size * 0.07),
+            ];
+            painter.add(egui::Shape::convex_polygon(
+                points,
+                theme.colors.warning,
+                Stroke::NONE,
+            ));
+            // Dark on yellow, white would disappear
+            (
+                "!",
+                rect.center() + egui::vec2(0.0, size * 0.12),
+                Color32::from_gray(30),
+            )
+        }
+        _ => {
+            let color = match kind {
+                MessageKind::Error => theme.colors.error,
+                _ => theme.colors.link,
+            };
+            painter.circle_filled(rect.center(), size / 2.0, color);
+            let glyph = match kind {
+                MessageKind::Question => "?",
+                MessageKind::Error => "!",
+                _ => "i",
+            };
+            (glyph, rect.center(), theme.colors.default_button_text)
+        }
+    };
+    painter.text(
+        glyph_center,
+        egui::Align2::CENTER_CENTER,
+        glyph,
+        glyph_font,
+        glyph_color,
+    );
+}
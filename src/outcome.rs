@@ -0,0 +1,141 @@
+use std::path::PathBuf;
+
+use crate::login_dialog::Credential;
+
+/// How a dialog ended, delivered once over a channel to whoever opened it.
+///
+/// Each variant maps to a distinct process exit code, see [`DialogOutcome::exit_code`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DialogOutcome<T = String> {
+    /// The default button was pressed; carries the dialog's value
+    Confirmed(T),
+    /// The Cancel button was pressed
+    Cancelled,
+    /// The window was closed without pressing a button (title-bar close button, Alt+F4, …)
+    Dismissed,
+    /// The dialog could not be shown or could not complete
+    Error(String),
+    /// Nobody answered before the timeout
+    TimedOut,
+}
+
+impl<T> DialogOutcome<T> {
+    /// Exit code reported by the binary for this outcome
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            Self::Confirmed(_) => 0,
+            Self::Cancelled => 1,
+            Self::Dismissed => 2,
+            Self::Error(_) => 3,
+            Self::TimedOut => 4,
+        }
+    }
+
+    /// The outcome with the confirmed value converted by `f`
+    pub fn map<U>(self, f: impl FnOnce(T) -> U) -> DialogOutcome<U> {
+        self.and_then(|value| DialogOutcome::Confirmed(f(value)))
+    }
+
+    /// The outcome `f` gives for the confirmed value, e.g. an error for a value that can't be used
+    pub fn and_then<U>(self, f: impl FnOnce(T) -> DialogOutcome<U>) -> DialogOutcome<U> {
+        match self {
+            Self::Confirmed(value) => f(value),
+            Self::Cancelled => DialogOutcome::Cancelled,
+            Self::Dismissed => DialogOutcome::Dismissed,
+            Self::Error(message) => DialogOutcome::Error(message),
+            Self::TimedOut => DialogOutcome::TimedOut,
+        }
+    }
+
+    /// Lowercase name of the variant, as used in machine readable output
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::Confirmed(_) => "confirmed",
+            Self::Cancelled => "cancelled",
+            Self::Dismissed => "dismissed",
+            Self::Error(_) => "error",
+            Self::TimedOut => "timed-out",
+        }
+    }
+}
+
+/// The value a dialog is confirmed with, handed over as the type its outcome channel carries
+#[derive(Clone, Debug)]
+pub enum DialogValue {
+    /// Typed text, a chosen path or the label of a message box button
+    Text(String),
+    /// User name and password of the Login dialog
+    Credential(Credential),
+    /// Indices of the rows picked in the List dialog
+    Rows(Vec<usize>),
+    /// Absolute paths chosen in the Open and Save dialogs
+    Paths(Vec<PathBuf>),
+}
+
+impl From<String> for DialogValue {
+    fn from(text: String) -> Self {
+        Self::Text(text)
+    }
+}
+
+impl From<Credential> for DialogValue {
+    fn from(credential: Credential) -> Self {
+        Self::Credential(credential)
+    }
+}
+
+impl From<Vec<usize>> for DialogValue {
+    fn from(rows: Vec<usize>) -> Self {
+        Self::Rows(rows)
+    }
+}
+
+impl From<Vec<PathBuf>> for DialogValue {
+    fn from(paths: Vec<PathBuf>) -> Self {
+        Self::Paths(paths)
+    }
+}
+
+impl TryFrom<DialogValue> for String {
+    type Error = DialogValue;
+
+    fn try_from(value: DialogValue) -> Result<Self, Self::Error> {
+        match value {
+            DialogValue::Text(text) => Ok(text),
+            value => Err(value),
+        }
+    }
+}
+
+impl TryFrom<DialogValue> for Credential {
+    type Error = DialogValue;
+
+    fn try_from(value: DialogValue) -> Result<Self, Self::Error> {
+        match value {
+            DialogValue::Credential(credential) => Ok(credential),
+            value => Err(value),
+        }
+    }
+}
+
+impl TryFrom<DialogValue> for Vec<usize> {
+    type Error = DialogValue;
+
+    fn try_from(value: DialogValue) -> Result<Self, Self::Error> {
+        match value {
+            DialogValue::Rows(rows) => Ok(rows),
+            value => Err(value),
+        }
+    }
+}
+
+impl TryFrom<DialogValue> for Vec<PathBuf> {
+    type Error = DialogValue;
+
+    fn try_from(value: DialogValue) -> Result<Self, Self::Error> {
+        match value {
+            DialogValue::Paths(paths) => Ok(paths),
+            value => Err(value),
+        }
+    }
+}
@@ -0,0 +1,313 @@
+use std::io::{self, Write};
+use std::path::Path;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use crate::cli::OutputFormat;
+use crate::DialogOutcome;
+
+/// When the dialog was launched, to report how long the answer took
+#[derive(Clone, Copy, Debug)]
+pub struct Timing {
+    started: SystemTime,
+    clock: Instant,
+}
+
+impl Timing {
+    /// Start timing now
+    pub fn start() -> Self {
+        Self {
+            started: SystemTime::now(),
+            clock: Instant::now(),
+        }
+    }
+
+    /// Seconds since the Unix epoch at launch
+    pub fn started_unix(&self) -> f64 {
+        self.started
+            .duration_since(UNIX_EPOCH)
+            .map_or(0.0, |since_epoch| since_epoch.as_secs_f64())
+    }
+
+    /// Time since launch
+    pub fn elapsed(&self) -> Duration {
+        self.clock.elapsed()
+    }
+}
+
+/// What the confirmed value of a dialog is
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ValueKind {
+    /// Absolute path of the created or renamed entry
+    Path,
+    /// The answer itself, e.g. the label of the pressed message box button
+    Text,
+    /// A password or another secret, written like [`ValueKind::Text`] but never logged
+    Secret,
+}
+
+/// Write the outcome of a dialog: plain and NUL formats print the confirmed path (or text) alone
+pub fn write_outcome(
+    out: &mut impl Write,
+    format: OutputFormat,
+    outcome: &DialogOutcome,
+    value_kind: ValueKind,
+    timing: &Timing,
+) -> io::Result<()> {
+    let path = match (outcome, value_kind) {
+        (DialogOutcome::Confirmed(path), ValueKind::Path) => Some(path.as_str()),
+        _ => None,
+    };
+    // The value is what the user answered: the folder name or button label, or the error message
+    let value = match (outcome, value_kind) {
+        (DialogOutcome::Confirmed(path), ValueKind::Path) => Some(
+            Path::new(path)
+                .file_name()
+                .map_or_else(|| path.clone(), |name| name.to_string_lossy().into_owned()),
+        ),
+        (DialogOutcome::Confirmed(text), ValueKind::Text | ValueKind::Secret) => Some(text.clone()),
+        (DialogOutcome::Error(message), _) => Some(message.clone()),
+        _ => None,
+    };
+    let printed = match value_kind {
+        ValueKind::Path => path,
+        ValueKind::Text | ValueKind::Secret => match outcome {
+            DialogOutcome::Confirmed(text) => Some(text.as_str()),
+            _ => None,
+        },
+    };
+    let elapsed_ms = timing.elapsed().as_millis();
+
+    match format {
+        OutputFormat::Plain => {
+            if let Some(printed) = printed {
+                writeln!(out, "{printed}")?;
+            }
+        }
+        OutputFormat::Nul => {
+            if let Some(printed) = printed {
+                write!(out, "{printed}\0")?;
+            }
+        }
+        OutputFormat::Json => {
+            let json = serde_json::json!({
+                "outcome": outcome.kind(),
+                "value": value,
+                "path": path,
+                "exit_code": outcome.exit_code(),
+                "timing": {
+                    "started": timing.started_unix(),
+                    "elapsed_ms": elapsed_ms,
+                },
+            });
+            writeln!(out, "{json}")?;
+        }
+        OutputFormat::Shell => {
+            writeln!(out, "DIALOG_OUTCOME={}", shell_quote(outcome.kind()))?;
+            writeln!(
+                out,
+                "DIALOG_VALUE={}",
+                shell_quote(value.as_deref().unwrap_or_default())
+            )?;
+            writeln!(out, "DIALOG_PATH={}", shell_quote(path.unwrap_or_default()))?;
+            writeln!(out, "DIALOG_EXIT_CODE={}", outcome.exit_code())?;
+            writeln!(out, "DIALOG_ELAPSED_MS={elapsed_ms}")?;
+        }
+    }
+    out.flush()
+}
+
+/// Write the outcome of a List dialog: plain and NUL formats print the chosen rows with their
+/// cells separated by tabs, one per line or each terminated by a NUL byte; JSON gives each row as
+/// an object of cells by column name
+pub fn write_rows(
+    out: &mut impl Write,
+    format: OutputFormat,
+    outcome: &DialogOutcome<Vec<Vec<String>>>,
+    columns: &[String],
+    timing: &Timing,
+) -> io::Result<()> {
+    let rows = match outcome {
+        DialogOutcome::Confirmed(rows) => rows.as_slice(),
+        _ => &[],
+    };
+    // Tabs and line breaks inside cells would split them, they become spaces
+    let tsv_rows: Vec<String> = rows
+        .iter()
+        .map(|row| {
+            row.iter()
+                .map(|cell| cell.replace(['\t', '\n', '\r'], " "))
+                .collect::<Vec<_>>()
+                .join("\t")
+        })
+        .collect();
+    let elapsed_ms = timing.elapsed().as_millis();
+
+    match format {
+        OutputFormat::Plain => {
+            for row in &tsv_rows {
+                writeln!(out, "{row}")?;
+            }
+        }
+        OutputFormat::Nul => {
+            for row in &tsv_rows {
+                write!(out, "{row}\0")?;
+            }
+        }
+        OutputFormat::Json => {
+            let value = match outcome {
+                DialogOutcome::Confirmed(rows) => serde_json::Value::Array(
+                    rows.iter()
+                        .map(|row| {
+                            columns
+                                .iter()
+                                .cloned()
+                                .zip(row.iter().cloned().map(serde_json::Value::String))
+                                .collect()
+                        })
+                        .collect(),
+                ),
+                DialogOutcome::Error(message) => message.clone().into(),
+                _ => serde_json::Value::Null,
+            };
+            let json = serde_json::json!({
+                "outcome": outcome.kind(),
+                "value": value,
+                "path": null,
+                "exit_code": outcome.exit_code(),
+                "timing": {
+                    "started": timing.started_unix(),
+                    "elapsed_ms": elapsed_ms,
+                },
+            });
+            writeln!(out, "{json}")?;
+        }
+        OutputFormat::Shell => {
+            let value = match outcome {
+                DialogOutcome::Error(message) => message.clone(),
+                _ => tsv_rows.join("\n"),
+            };
+            writeln!(out, "DIALOG_OUTCOME={}", shell_quote(outcome.kind()))?;
+            writeln!(out, "DIALOG_VALUE={}", shell_quote(&value))?;
+            writeln!(out, "DIALOG_PATH=''")?;
+            writeln!(out, "DIALOG_EXIT_CODE={}", outcome.exit_code())?;
+            writeln!(out, "DIALOG_ELAPSED_MS={elapsed_ms}")?;
+        }
+    }
+    out.flush()
+}
+
+/// Quote `value` for a POSIX shell: single quotes, with embedded ones closed, escaped and reopened
+pub fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', r"'\''"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn outcome_output(format: OutputFormat, outcome: &DialogOutcome, kind: ValueKind) -> String {
+        let mut out = Vec::new();
+        write_outcome(&mut out, format, outcome, kind, &Timing::start()).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    fn rows_output(format: OutputFormat, outcome: &DialogOutcome<Vec<Vec<String>>>) -> String {
+        let columns = ["Name".to_owned(), "Size".to_owned()];
+        let mut out = Vec::new();
+        write_rows(&mut out, format, outcome, &columns, &Timing::start()).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn shell_quoting() {
+        assert_eq!(shell_quote(""), "''");
+        assert_eq!(shell_quote("plain"), "'plain'");
+        assert_eq!(shell_quote("it's"), r"'it'\''s'");
+        assert_eq!(shell_quote("''"), r"''\'''\'''");
+        assert_eq!(shell_quote("two\nlines"), "'two\nlines'");
+        assert_eq!(
+            shell_quote("$(rm -rf ~) `id` $HOME"),
+            "'$(rm -rf ~) `id` $HOME'"
+        );
+    }
+
+    #[test]
+    fn json_carries_outcome_value_path_and_timing() {
+        let outcome = DialogOutcome::Confirmed("/home/ana/New Folder".to_owned());
+        let json: serde_json::Value = serde_json::from_str(&outcome_output(
+            OutputFormat::Json,
+            &outcome,
+            ValueKind::Path,
+        ))
+        .unwrap();
+        assert_eq!(json["outcome"], "confirmed");
+        assert_eq!(json["value"], "New Folder");
+        assert_eq!(json["path"], "/home/ana/New Folder");
+        assert_eq!(json["exit_code"], 0);
+        assert!(json["timing"]["started"].as_f64().unwrap() > 0.0);
+        assert!(json["timing"]["elapsed_ms"].is_u64());
+
+        let json: serde_json::Value = serde_json::from_str(&outcome_output(
+            OutputFormat::Json,
+            &DialogOutcome::Cancelled,
+            ValueKind::Path,
+        ))
+        .unwrap();
+        assert_eq!(json["outcome"], "cancelled");
+        assert!(json["value"].is_null());
+        assert!(json["path"].is_null());
+        assert_eq!(json["exit_code"], 1);
+    }
+
+    #[test]
+    fn shell_output_is_quoted() {
+        let outcome = DialogOutcome::Confirmed("it's $(here)".to_owned());
+        let output = outcome_output(OutputFormat::Shell, &outcome, ValueKind::Text);
+        assert!(output.starts_with("DIALOG_OUTCOME='confirmed'\n"));
+        assert!(output.contains("DIALOG_VALUE='it'\\''s $(here)'\n"));
+        assert!(output.contains("DIALOG_PATH=''\n"));
+        assert!(output.contains("DIALOG_EXIT_CODE=0\n"));
+    }
+
+    #[test]
+    fn nul_terminates_records() {
+        let outcome = DialogOutcome::Confirmed("/tmp/line\nbreak".to_owned());
+        assert_eq!(
+            outcome_output(OutputFormat::Nul, &outcome, ValueKind::Path),
+            "/tmp/line\nbreak\0"
+        );
+        assert_eq!(
+            outcome_output(
+                OutputFormat::Nul,
+                &DialogOutcome::Dismissed,
+                ValueKind::Path
+            ),
+            ""
+        );
+
+        let rows = DialogOutcome::Confirmed(vec![
+            vec!["a\tb".to_owned(), "1".to_owned()],
+            vec!["c\nd".to_owned(), "2".to_owned()],
+        ]);
+        assert_eq!(rows_output(OutputFormat::Nul, &rows), "a b\t1\0c d\t2\0");
+        assert_eq!(rows_output(OutputFormat::Plain, &rows), "a b\t1\nc d\t2\n");
+        assert_eq!(
+            rows_output(OutputFormat::Nul, &DialogOutcome::Cancelled),
+            ""
+        );
+    }
+
+    #[test]
+    fn json_rows_are_objects_by_column() {
+        let rows = DialogOutcome::Confirmed(vec![vec!["notes.txt".to_owned(), "12".to_owned()]]);
+        let json: serde_json::Value =
+            serde_json::from_str(&rows_output(OutputFormat::Json, &rows)).unwrap();
+        assert_eq!(json["outcome"], "confirmed");
+        assert_eq!(
+            json["value"],
+            serde_json::json!([{"Name": "notes.txt", "Size": "12"}])
+        );
+        assert!(json["path"].is_null());
+        assert!(json["timing"]["elapsed_ms"].is_u64());
+    }
+}
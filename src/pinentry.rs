@@ -0,0 +1,343 @@
+use std::ffi::OsString;
+use std::io::{self, BufRead, Write};
+use std::path::Path;
+use std::time::Duration;
+
+use zeroize::Zeroizing;
+
+use crate::places::percent_decode;
+use crate::DialogOutcome;
+
+/// `ERR` codes of the replies, libgpg-error values: the error source in the top byte
+const ERR_CANCELED: u32 = 83886179; // Pinentry, GPG_ERR_CANCELED
+const ERR_NOT_CONFIRMED: u32 = 83886194; // Pinentry, GPG_ERR_NOT_CONFIRMED
+const ERR_TIMEOUT: u32 = 83886142; // Pinentry, GPG_ERR_TIMEOUT
+const ERR_GENERAL: u32 = 83886081; // Pinentry, GPG_ERR_GENERAL
+const ERR_UNKNOWN_COMMAND: u32 = 536871187; // User defined source 1, GPG_ERR_ASS_UNKNOWN_CMD
+const ERR_PARAMETER: u32 = 536871192; // User defined source 1, GPG_ERR_ASS_PARAMETER
+
+/// What the agent configured with the SET* commands and OPTION, until RESET
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Settings {
+    /// SETTITLE, the dialog heading
+    pub title: Option<String>,
+    /// SETDESC, e.g. which key the passphrase unlocks
+    pub description: Option<String>,
+    /// SETPROMPT, e.g. "Passphrase:"
+    pub prompt: Option<String>,
+    /// SETOK, without the underscores marking access keys
+    pub ok: Option<String>,
+    /// SETCANCEL, without the underscores marking access keys
+    pub cancel: Option<String>,
+    /// SETNOTOK, the third button of CONFIRM, without the underscores marking access keys
+    pub not_ok: Option<String>,
+    /// SETERROR, why the last passphrase was rejected; shown once
+    pub error: Option<String>,
+    /// SETTIMEOUT, seconds before the dialog gives up
+    pub timeout: Option<Duration>,
+    /// OPTION lc-messages, the language of the default strings
+    pub locale: Option<String>,
+}
+
+/// A dialog the agent asked for
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Request {
+    /// GETPIN: ask for the passphrase, answered with it
+    GetPin,
+    /// CONFIRM: OK, Cancel and, with SETNOTOK, a not-OK button answered with its label
+    Confirm,
+    /// MESSAGE or CONFIRM --one-button: acknowledge the description
+    Message,
+}
+
+/// Whether the binary was started as a pinentry, e.g. through a `pinentry-eframe` symlink set
+/// as the `pinentry-program` of gpg-agent
+pub fn is_invocation(args: &[OsString]) -> bool {
+    args.first()
+        .and_then(|program| Path::new(program).file_stem())
+        .is_some_and(|stem| stem.to_string_lossy().starts_with("pinentry"))
+}
+
+/// Locale given as `--lc-messages LOCALE` on the command line, as gpg-agent does
+pub fn locale_argument(args: &[OsString]) -> Option<String> {
+    args.iter()
+        .position(|arg| arg == "--lc-messages")
+        .and_then(|index| args.get(index + 1))
+        .map(|locale| locale.to_string_lossy().into_owned())
+}
+
+/// Speak the Assuan protocol: read commands from `input` until BYE or end of input, reply on
+/// `output`, and call `ask` to show a dialog whenever the agent wants an answer
+pub fn serve(
+    input: impl BufRead,
+    output: &mut impl Write,
+    mut settings: Settings,
+    mut ask: impl FnMut(Request, &Settings) -> DialogOutcome,
+) -> io::Result<()> {
+    writeln!(
+        output,
+        "OK Pleased to meet you, process {}",
+        std::process::id()
+    )?;
+    output.flush()?;
+    for line in input.lines() {
+        let line = line?;
+        let line = line.trim_end_matches('\r');
+        // Comments and empty lines are allowed and not answered
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let (command, argument) = line.split_once(' ').unwrap_or((line, ""));
+        let text = || Some(percent_decode(argument));
+        let label = || Some(without_mnemonics(&percent_decode(argument)));
+        match command.to_ascii_uppercase().as_str() {
+            "SETTITLE" => settings.title = text(),
+            "SETDESC" => settings.description = text(),
+            "SETPROMPT" => settings.prompt = text(),
+            "SETOK" => settings.ok = label(),
+            "SETCANCEL" => settings.cancel = label(),
+            "SETNOTOK" => settings.not_ok = label(),
+            "SETERROR" => settings.error = text(),
+            "SETTIMEOUT" => match argument.trim().parse::<u64>() {
+                Ok(0) => settings.timeout = None,
+                Ok(seconds) => settings.timeout = Some(Duration::from_secs(seconds)),
+                Err(_) => {
+                    error(output, ERR_PARAMETER, "Invalid timeout")?;
+                    continue;
+                }
+            },
+            "OPTION" => {
+                let (name, value) = argument.split_once(['=', ' ']).unwrap_or((argument, ""));
+                if name.trim_start_matches("--") == "lc-messages" {
+                    settings.locale = Some(value.trim().to_owned());
+                }
+                // ttyname, display, allow-external-password-cache, … don't apply to a window
+            }
+            // Accepted and ignored: this dialog has no quality bar, repeat field or key cache
+            "SETKEYINFO" | "SETQUALITYBAR" | "SETQUALITYBAR_TT" | "SETREPEAT"
+            | "SETREPEATERROR" | "SETREPEATOK" | "SETGENPIN" | "SETGENPIN_TT"
+            | "CLEARPASSPHRASE" | "NOP" => {}
+            "RESET" => {
+                settings = Settings {
+                    locale: settings.locale.take(),
+                    ..Settings::default()
+                }
+            }
+            "GETINFO" => {
+                let info = match argument.trim() {
+                    "version" => env!("CARGO_PKG_VERSION").to_owned(),
+                    "pid" => std::process::id().to_string(),
+                    "flavor" => "eframe".to_owned(),
+                    "ttyinfo" => "- - -".to_owned(),
+                    _ => {
+                        error(output, ERR_PARAMETER, "Unknown GETINFO item")?;
+                        continue;
+                    }
+                };
+                write_data(output, &info)?;
+            }
+            "GETPIN" => {
+                let outcome = ask(Request::GetPin, &settings);
+                settings.error = None;
+                match outcome {
+                    DialogOutcome::Confirmed(pin) => {
+                        let pin = Zeroizing::new(pin);
+                        if !pin.is_empty() {
+                            write_data(output, &pin)?;
+                        }
+                    }
+                    outcome => {
+                        dialog_error(output, &outcome)?;
+                        continue;
+                    }
+                }
+            }
+            "CONFIRM" | "MESSAGE" => {
+                let request = if command.eq_ignore_ascii_case("MESSAGE")
+                    || argument.trim() == "--one-button"
+                {
+                    Request::Message
+                } else {
+                    Request::Confirm
+                };
+                let outcome = ask(request, &settings);
+                settings.error = None;
+                match outcome {
+                    DialogOutcome::Confirmed(label)
+                        if request == Request::Confirm
+                            && settings.not_ok.as_ref() == Some(&label) =>
+                    {
+                        error(output, ERR_NOT_CONFIRMED, "Not confirmed")?;
+                        continue;
+                    }
+                    DialogOutcome::Confirmed(_) => {}
+                    outcome => {
+                        dialog_error(output, &outcome)?;
+                        continue;
+                    }
+                }
+            }
+            "BYE" => {
+                writeln!(output, "OK closing connection")?;
+                return output.flush();
+            }
+            _ => {
+                error(output, ERR_UNKNOWN_COMMAND, "Unknown IPC command")?;
+                continue;
+            }
+        }
+        writeln!(output, "OK")?;
+        output.flush()?;
+    }
+    Ok(())
+}
+
+/// Reply to a dialog that wasn't answered
+fn dialog_error(output: &mut impl Write, outcome: &DialogOutcome) -> io::Result<()> {
+    match outcome {
+        DialogOutcome::TimedOut => error(output, ERR_TIMEOUT, "Timeout"),
+        DialogOutcome::Error(message) => {
+            log::error!("{message}");
+            error(output, ERR_GENERAL, "General error")
+        }
+        _ => error(output, ERR_CANCELED, "Operation cancelled"),
+    }
+}
+
+fn error(output: &mut impl Write, code: u32, description: &str) -> io::Result<()> {
+    let source = if code >> 24 == 5 {
+        "Pinentry"
+    } else {
+        "User defined source 1"
+    };
+    writeln!(output, "ERR {code} {description} <{source}>")?;
+    output.flush()
+}
+
+/// Send `text` in a data line, escaping what it can't contain: %, CR and LF. Written piecewise,
+/// so no copy of a passphrase is left behind.
+fn write_data(output: &mut impl Write, text: &str) -> io::Result<()> {
+    output.write_all(b"D ")?;
+    for piece in text.split_inclusive(['%', '\r', '\n']) {
+        let (plain, escape) = match piece.chars().last() {
+            Some('%') => (&piece[..piece.len() - 1], "%25"),
+            Some('\r') => (&piece[..piece.len() - 1], "%0D"),
+            Some('\n') => (&piece[..piece.len() - 1], "%0A"),
+            _ => (piece, ""),
+        };
+        output.write_all(plain.as_bytes())?;
+        output.write_all(escape.as_bytes())?;
+    }
+    output.write_all(b"\n")
+}
+
+/// "_OK" -> "OK": pinentry labels mark access keys with an underscore, "__" is a literal one
+fn without_mnemonics(label: &str) -> String {
+    let mut plain = String::with_capacity(label.len());
+    let mut chars = label.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '_' => plain.extend(chars.next()),
+            _ => plain.push(c),
+        }
+    }
+    plain
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+
+    /// Reply lines to `script`, each request answered by `ask` and recorded with its settings
+    fn run(
+        script: &str,
+        mut ask: impl FnMut(Request, &Settings) -> DialogOutcome,
+    ) -> (Vec<String>, Vec<(Request, Settings)>) {
+        let mut output = Vec::new();
+        let mut requests = Vec::new();
+        serve(
+            Cursor::new(script),
+            &mut output,
+            Settings::default(),
+            |request, settings| {
+                requests.push((request, settings.clone()));
+                ask(request, settings)
+            },
+        )
+        .unwrap();
+        let replies = String::from_utf8(output)
+            .unwrap()
+            .lines()
+            .map(str::to_owned)
+            .collect();
+        (replies, requests)
+    }
+
+    fn unused(_: Request, _: &Settings) -> DialogOutcome {
+        panic!("no dialog expected")
+    }
+
+    #[test]
+    fn greets() {
+        let (replies, _) = run("", unused);
+        assert_eq!(replies.len(), 1);
+        assert!(replies[0].starts_with("OK Pleased to meet you"));
+    }
+
+    #[test]
+    fn getpin_sends_the_escaped_pin() {
+        let script = "SETDESC Unlock key%0A\"Alice\"\nSETPROMPT Passphrase:\nGETPIN\n";
+        let (replies, requests) = run(script, |_, _| {
+            DialogOutcome::Confirmed("50%\nof it".to_owned())
+        });
+        assert_eq!(replies[1..], ["OK", "OK", "D 50%25%0Aof it", "OK"]);
+        let (request, settings) = &requests[0];
+        assert_eq!(*request, Request::GetPin);
+        assert_eq!(
+            settings.description.as_deref(),
+            Some("Unlock key\n\"Alice\"")
+        );
+        assert_eq!(settings.prompt.as_deref(), Some("Passphrase:"));
+    }
+
+    #[test]
+    fn getpin_cancelled() {
+        let (replies, _) = run("GETPIN\n", |_, _| DialogOutcome::Cancelled);
+        assert_eq!(replies[1], "ERR 83886179 Operation cancelled <Pinentry>");
+    }
+
+    #[test]
+    fn confirm_not_ok() {
+        let script = "SETOK _Yes\nSETNOTOK _No\nCONFIRM\n";
+        let (replies, requests) = run(script, |_, settings| {
+            DialogOutcome::Confirmed(settings.not_ok.clone().unwrap())
+        });
+        assert_eq!(replies[3], "ERR 83886194 Not confirmed <Pinentry>");
+        assert_eq!(requests[0].0, Request::Confirm);
+        assert_eq!(requests[0].1.ok.as_deref(), Some("Yes"));
+    }
+
+    #[test]
+    fn timeout() {
+        let (replies, requests) = run("SETTIMEOUT 30\nGETPIN\n", |_, _| DialogOutcome::TimedOut);
+        assert_eq!(requests[0].1.timeout, Some(Duration::from_secs(30)));
+        assert_eq!(replies[1..], ["OK", "ERR 83886142 Timeout <Pinentry>"]);
+    }
+
+    #[test]
+    fn unknown_command() {
+        let (replies, _) = run("FROBNICATE\n", unused);
+        assert_eq!(
+            replies[1],
+            "ERR 536871187 Unknown IPC command <User defined source 1>"
+        );
+    }
+
+    #[test]
+    fn bye_ends_the_session() {
+        let (replies, _) = run("BYE\nGETPIN\n", unused);
+        assert_eq!(replies[1..], ["OK closing connection"]);
+    }
+}
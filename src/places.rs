@@ -0,0 +1,304 @@
+use std::path::{Path, PathBuf};
+
+/// A folder offered as a shortcut, with the name it is shown with
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Place {
+    pub name: String,
+    pub path: PathBuf,
+}
+
+/// The XDG user directories offered as shortcuts, in this order
+const USER_DIRS: [(&str, &str); 6] = [
+    ("XDG_DESKTOP_DIR", "Desktop"),
+    ("XDG_DOCUMENTS_DIR", "Documents"),
+    ("XDG_DOWNLOAD_DIR", "Downloads"),
+    ("XDG_MUSIC_DIR", "Music"),
+    ("XDG_PICTURES_DIR", "Pictures"),
+    ("XDG_VIDEOS_DIR", "Videos"),
+];
+
+/// The user's home directory, from HOME or USERPROFILE on Windows
+pub fn home_dir() -> Option<PathBuf> {
+    std::env::var_os("HOME")
+        .or_else(|| std::env::var_os("USERPROFILE"))
+        .filter(|home| !home.is_empty())
+        .map(PathBuf::from)
+}
+
+/// The existing XDG user directories (Desktop, Documents, …), read from
+/// `$XDG_CONFIG_HOME/user-dirs.dirs` where they may be translated or moved, named after their
+/// folder
+pub fn user_dirs() -> Vec<Place> {
+    let Some(home) = home_dir() else {
+        return Vec::new();
+    };
+    let configured = config_home()
+        .and_then(|config_home| std::fs::read_to_string(config_home.join("user-dirs.dirs")).ok())
+        .unwrap_or_default();
+
+    USER_DIRS
+        .iter()
+        .filter_map(|(key, default)| {
+            let path =
+                configured_user_dir(&configured, key, &home).unwrap_or_else(|| home.join(default));
+            // A user directory set to $HOME means it is disabled
+            (path != home && path.is_dir()).then(|| Place {
+                name: path.file_name().map_or_else(
+                    || default.to_string(),
+                    |name| name.to_string_lossy().into_owned(),
+                ),
+                path,
+            })
+        })
+        .collect()
+}
+
+/// Where `key` is set in the contents of a user-dirs.dirs file, e.g.
+/// `XDG_DOCUMENTS_DIR="$HOME/Documents"`
+fn configured_user_dir(configured: &str, key: &str, home: &Path) -> Option<PathBuf> {
+    configured
+        .lines()
+        .filter_map(|line| line.trim().split_once('='))
+        .find(|(name, _)| name.trim() == key)
+        .map(|(_, value)| expand_home(value, home))
+}
+
+/// Removable drives and other mounted volumes, read from `/proc/mounts`
+pub fn mounts() -> Vec<Place> {
+    volumes(&std::fs::read_to_string("/proc/mounts").unwrap_or_default())
+}
+
+/// The volumes of a mount table in the format of `/proc/mounts`: what is mounted under /media,
+/// /run/media or /mnt, or from a /dev device outside the system folders
+fn volumes(table: &str) -> Vec<Place> {
+    let mut volumes = Vec::new();
+    for line in table.lines() {
+        let mut fields = line.split_whitespace();
+        let (Some(device), Some(mount_point)) = (fields.next(), fields.next()) else {
+            continue;
+        };
+        let path = PathBuf::from(unescape_mount(mount_point));
+        let removable = ["/media", "/run/media", "/mnt"]
+            .iter()
+            .any(|prefix| path.starts_with(prefix));
+        let system = path == Path::new("/")
+            || [
+                "/boot", "/efi", "/home", "/var", "/usr", "/opt", "/srv", "/nix", "/snap",
+            ]
+            .iter()
+            .any(|prefix| path.starts_with(prefix));
+        if !(removable || (device.starts_with("/dev/") && !system))
+            || volumes.iter().any(|place: &Place| place.path == path)
+        {
+            continue;
+        }
+        volumes.push(Place {
+            name: path.file_name().map_or_else(
+                || path.display().to_string(),
+                |name| name.to_string_lossy().into_owned(),
+            ),
+            path,
+        });
+    }
+    volumes
+}
+
+/// `/media/USB\040Stick` -> `/media/USB Stick`: /proc/mounts escapes spaces, tabs, newlines and
+/// backslashes as octal
+fn unescape_mount(field: &str) -> String {
+    let mut unescaped = Vec::with_capacity(field.len());
+    let mut rest = field.as_bytes();
+    while let Some((&byte, tail)) = rest.split_first() {
+        let octal = tail
+            .get(..3)
+            .and_then(|digits| std::str::from_utf8(digits).ok());
+        match octal.and_then(|digits| u8::from_str_radix(digits, 8).ok()) {
+            Some(decoded) if byte == b'\\' => {
+                unescaped.push(decoded);
+                rest = &tail[3..];
+            }
+            _ => {
+                unescaped.push(byte);
+                rest = tail;
+            }
+        }
+    }
+    String::from_utf8_lossy(&unescaped).into_owned()
+}
+
+/// The folders bookmarked in GTK file choosers and Files, read from
+/// `$XDG_CONFIG_HOME/gtk-3.0/bookmarks`: `file:///home/user/Projects%20Old Old projects` lines,
+/// named after their label or folder. Bookmarks of remote or missing folders are left out.
+pub fn bookmarks() -> Vec<Place> {
+    let Some(config_home) = config_home() else {
+        return Vec::new();
+    };
+    let bookmarks =
+        std::fs::read_to_string(config_home.join("gtk-3.0").join("bookmarks")).unwrap_or_default();
+    parse_bookmarks(&bookmarks)
+        .into_iter()
+        .filter(|place| place.path.is_dir())
+        .collect()
+}
+
+/// The local folders of a GTK bookmarks file, whether they exist or not
+fn parse_bookmarks(bookmarks: &str) -> Vec<Place> {
+    bookmarks
+        .lines()
+        .filter_map(|line| {
+            let (uri, label) = line.trim().split_once(' ').unwrap_or((line.trim(), ""));
+            let path = PathBuf::from(percent_decode(uri.strip_prefix("file://")?));
+            let name = match label.trim() {
+                "" => path.file_name().map_or_else(
+                    || path.display().to_string(),
+                    |name| name.to_string_lossy().into_owned(),
+                ),
+                label => label.to_owned(),
+            };
+            Some(Place { name, path })
+        })
+        .collect()
+}
+
+/// Decode %XX escapes, as found in file URIs and the arguments of pinentry commands
+pub(crate) fn percent_decode(text: &str) -> String {
+    let mut bytes = Vec::with_capacity(text.len());
+    let mut rest = text.as_bytes();
+    while let Some((&byte, tail)) = rest.split_first() {
+        let hex = tail.get(..2).and_then(|hex| std::str::from_utf8(hex).ok());
+        match hex.and_then(|hex| u8::from_str_radix(hex, 16).ok()) {
+            Some(decoded) if byte == b'%' => {
+                bytes.push(decoded);
+                rest = &tail[2..];
+            }
+            _ => {
+                bytes.push(byte);
+                rest = tail;
+            }
+        }
+    }
+    String::from_utf8_lossy(&bytes).into_owned()
+}
+
+/// `$XDG_CONFIG_HOME`, or `~/.config`
+fn config_home() -> Option<PathBuf> {
+    std::env::var_os("XDG_CONFIG_HOME")
+        .filter(|config| !config.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| home_dir().map(|home| home.join(".config")))
+}
+
+/// `"$HOME/Documents"` -> `/home/user/Documents`: user-dirs.dirs values are quoted and may only
+/// start with $HOME or be absolute
+fn expand_home(value: &str, home: &Path) -> PathBuf {
+    let value = value.trim().trim_matches('"');
+    match value.strip_prefix("$HOME") {
+        Some(rest) => home.join(rest.trim_start_matches('/')),
+        None => PathBuf::from(value),
+    }
+}
+
+/// Whether the entry is hidden, Unix-style: its name starts with a dot
+pub fn is_hidden(path: &Path) -> bool {
+    path.file_name()
+        .is_some_and(|name| name.to_string_lossy().starts_with('.'))
+}
+
+/// The folders directly inside `path`, sorted by name ignoring case; hidden ones only if asked for
+pub fn subdirectories(path: &Path, show_hidden: bool) -> std::io::Result<Vec<PathBuf>> {
+    let mut folders: Vec<PathBuf> = std::fs::read_dir(path)?
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        // Links to folders count, like in Finder and Files
+        .filter(|path| path.is_dir() && (show_hidden || !is_hidden(path)))
+        .collect();
+    folders.sort_by_key(|path| {
+        path.file_name()
+            .map(|name| name.to_string_lossy().to_lowercase())
+    });
+    Ok(folders)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn user_dirs_expand_home() {
+        let home = Path::new("/home/ana");
+        let configured = "# written by xdg-user-dirs-update\n\
+            XDG_DESKTOP_DIR=\"$HOME/Escritorio\"\n\
+            XDG_DOCUMENTS_DIR=\"$HOME/\"\n\
+            XDG_MUSIC_DIR=\"/srv/music\"\n\
+            XDG_VIDEOS_DIR=\"$HOME\"\n";
+        let dir = |key| configured_user_dir(configured, key, home);
+        assert_eq!(
+            dir("XDG_DESKTOP_DIR"),
+            Some(PathBuf::from("/home/ana/Escritorio"))
+        );
+        assert_eq!(dir("XDG_MUSIC_DIR"), Some(PathBuf::from("/srv/music")));
+        // Both mean the directory is disabled, which user_dirs() then leaves out
+        assert_eq!(dir("XDG_DOCUMENTS_DIR"), Some(home.to_path_buf()));
+        assert_eq!(dir("XDG_VIDEOS_DIR"), Some(home.to_path_buf()));
+        assert_eq!(dir("XDG_DOWNLOAD_DIR"), None);
+    }
+
+    #[test]
+    fn mount_points_are_unescaped() {
+        let table = "/dev/sda2 / ext4 rw 0 0\n\
+            /dev/sda1 /boot/efi vfat rw 0 0\n\
+            proc /proc proc rw 0 0\n\
+            /dev/sdb1 /media/ana/USB\\040Stick vfat rw 0 0\n\
+            //server/share /mnt/Back\\134slash cifs rw 0 0\n\
+            /dev/sdc1 /data ext4 rw 0 0\n\
+            /dev/sdc1 /data ext4 rw 0 0\n";
+        assert_eq!(
+            volumes(table),
+            [
+                Place {
+                    name: "USB Stick".into(),
+                    path: "/media/ana/USB Stick".into(),
+                },
+                Place {
+                    name: "Back\\slash".into(),
+                    path: "/mnt/Back\\slash".into(),
+                },
+                Place {
+                    name: "data".into(),
+                    path: "/data".into(),
+                },
+            ]
+        );
+        assert_eq!(
+            unescape_mount("tab\\011and\\012newline"),
+            "tab\tand\nnewline"
+        );
+        assert_eq!(unescape_mount("trailing\\04"), "trailing\\04");
+    }
+
+    #[test]
+    fn bookmarks_are_decoded_and_named() {
+        let bookmarks = "file:///home/ana/Projects%20Old Old projects\n\
+            file:///home/ana/M%C3%BAsica\n\
+            sftp://server/home/ana Server\n\
+            \n\
+            file:///home/ana/100%25%zz\n";
+        assert_eq!(
+            parse_bookmarks(bookmarks),
+            [
+                Place {
+                    name: "Old projects".into(),
+                    path: "/home/ana/Projects Old".into(),
+                },
+                Place {
+                    name: "Música".into(),
+                    path: "/home/ana/Música".into(),
+                },
+                Place {
+                    name: "100%%zz".into(),
+                    path: "/home/ana/100%%zz".into(),
+                },
+            ]
+        );
+    }
+}
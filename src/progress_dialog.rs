@@ -0,0 +1,277 @@
+use egui::TextStyle::*;
+
+use crate::i18n::Messages;
+use crate::input_dialog::{
+    button_style, cancel_button, dialog_frame, dialog_keys, draw_focus_ring, focus_colors,
+    leading_align, leading_layout, ok_button, visual_lines,
+};
+use crate::theme::Theme;
+use crate::InputResponse;
+
+/// A line read from the job driving a progress dialog, zenity-style
+#[derive(Clone, Debug, PartialEq)]
+pub enum ProgressUpdate {
+    /// "42" or "42.5": how far the job is, in percent
+    Percentage(f32),
+    /// "# Copying files…": the new status line
+    Text(String),
+    /// "pulsate:true" or "pulsate:false": whether the total is unknown
+    Pulsate(bool),
+    /// End of input: the job is done
+    Finished,
+}
+
+impl ProgressUpdate {
+    /// The update a line stands for, None for lines that mean nothing (they are ignored)
+    pub fn parse(line: &str) -> Option<Self> {
+        let line = line.trim_end();
+        if let Some(text) = line.strip_prefix('#') {
+            return Some(Self::Text(text.trim().to_owned()));
+        }
+        if let Some(pulsate) = line.trim().strip_prefix("pulsate:") {
+            return Some(Self::Pulsate(pulsate.trim().eq_ignore_ascii_case("true")));
+        }
+        // zenity takes the leading number of the line, e.g. "50 %" or "12.5"
+        let number: String = line
+            .trim()
+            .chars()
+            .take_while(|c| c.is_ascii_digit() || *c == '.')
+            .collect();
+        number
+            .parse::<f32>()
+            .ok()
+            .map(|percentage| Self::Percentage(percentage.clamp(0.0, 100.0)))
+    }
+}
+
+/// Read updates from `input` on a thread, waking the dialog up for each one
+#[cfg(not(target_arch = "wasm32"))]
+pub fn spawn_reader(
+    input: impl std::io::BufRead + Send + 'static,
+    ctx: egui::Context,
+) -> std::sync::mpsc::Receiver<ProgressUpdate> {
+    let (sender, receiver) = std::sync::mpsc::channel();
+    std::thread::spawn(move || {
+        for line in input.lines() {
+            let Ok(line) = line else { break };
+            if let Some(update) = ProgressUpdate::parse(&line) {
+                // The dialog is gone once the receiver is, nothing left to update
+                if sender.send(update).is_err() {
+                    return;
+                }
+                ctx.request_repaint();
+            }
+        }
+        sender.send(ProgressUpdate::Finished).ok();
+        ctx.request_repaint();
+    });
+    receiver
+}
+
+/// A heading, a status line and a progress bar with Cancel and OK buttons, in the look of
+/// [`crate::InputDialog`]. OK is enabled once the job is done.
+pub struct ProgressDialog {
+    heading: String,
+    status: String,
+    /// None while pulsating
+    fraction: Option<f32>,
+    done: bool,
+    ok_label: String,
+    cancel_label: Option<String>,
+}
+
+impl ProgressDialog {
+    /// Dialog titled `heading`, at `fraction` (0 to 1) of the job or pulsating for None
+    pub fn new(heading: impl Into<String>, fraction: Option<f32>) -> Self {
+        Self {
+            heading: heading.into(),
+            status: String::new(),
+            fraction,
+            done: false,
+            ok_label: "OK".to_owned(),
+            cancel_label: Some("Cancel".to_owned()),
+        }
+    }
+
+    /// Setter for the status line under the heading
+    pub fn with_status(mut self, status: impl Into<String>) -> Self {
+        self.status = status.into();
+        self
+    }
+
+    /// Setter for whether the job is done, which enables OK
+    pub fn with_done(mut self, done: bool) -> Self {
+        self.done = done;
+        self
+    }
+
+    /// Setter for the OK button label
+    pub fn with_ok_label(mut self, label: impl Into<String>) -> Self {
+        self.ok_label = label.into();
+        self
+    }
+
+    /// Setter for the Cancel button label, None for a job that can't be cancelled
+    pub fn with_cancel_label(mut self, label: Option<String>) -> Self {
+        self.cancel_label = label;
+        self
+    }
+
+    /// Lay the dialog out in the central panel and report what the user did
+    pub fn show(self, ctx: &egui::Context, theme: &Theme, messages: &Messages) -> InputResponse {
+        let mut response = InputResponse::default();
+        let rtl = messages.is_rtl();
+
+        let (return_pressed, cancel_pressed) = dialog_keys(ctx);
+        let (stroke_color, _, ok_button_color) = focus_colors(ctx, theme);
+
+        egui::CentralPanel::default()
+            .frame(dialog_frame(theme, rtl))
+            .show(ctx, |ui| {
+                ui.with_layout(egui::Layout::top_down(leading_align(rtl)), |ui| {
+                    ui.horizontal(|ui| {
+                        ui.add_space(theme.margins.content_indent);
+                        ui.add(
+                            egui::Label::new(
+                                egui::RichText::new(messages.visual(&self.heading))
+                                    .color(theme.colors.text)
+                                    .text_style(Name("DialogHeading".into())),
+                            )
+                            .selectable(false),
+                        );
+                    });
+                    ui.horizontal(|ui| {
+                        ui.add_space(theme.margins.content_indent);
+                        ui.add(
+                            egui::Label::new(
+                                egui::RichText::new(visual_lines(
+                                    ui,
+                                    messages,
+                                    &self.status,
+                                    &Name("DialogBody".into()),
+                                ))
+                                .color(theme.colors.text)
+                                .text_style(Name("DialogBody".into())),
+                            )
+                            .wrap()
+                            .selectable(false),
+                        );
+                    });
+                    ui.add_space(8.);
+
+                    ui.horizontal(|ui| {
+                        ui.add_space(theme.margins.content_indent);
+                        let width = ui.available_width() - theme.margins.content_indent;
+                        let (rect, _) =
+                            ui.allocate_exact_size(egui::vec2(width, 6.), egui::Sense::hover());
+                        draw_bar(ui, rect, self.fraction, rtl, theme);
+                    });
+                    ui.add_space(12.);
+
+                    ui.with_layout(leading_layout(!rtl, egui::Align::BOTTOM), |ui| {
+                        ui.add_space(3.);
+
+                        button_style(ui, theme);
+
+                        let ok_button =
+                            ok_button(&self.ok_label, ok_button_color, self.done, theme, messages);
+                        let ok_response = ui.add_enabled(self.done, ok_button);
+                        draw_focus_ring(ui, &ok_response, stroke_color, theme);
+                        response.confirmed = ok_response.clicked();
+
+                        ui.add_space(0.1); // Space between buttons
+
+                        if let Some(cancel_label) = &self.cancel_label {
+                            let cancel_button = cancel_button(cancel_label, theme, messages);
+                            let cancel_response = ui.add(cancel_button);
+                            draw_focus_ring(ui, &cancel_response, stroke_color, theme);
+                            response.cancelled = cancel_response.clicked();
+                        }
+                    });
+                });
+            });
+
+        if cancel_pressed && self.cancel_label.is_some() {
+            response.cancelled = true;
+        } else if return_pressed && self.done {
+            response.confirmed = true;
+        }
+        response
+    }
+}
+
+/// Draw the track and the filled part of the bar from the leading edge, or a block sliding back
+/// and forth while pulsating
+fn draw_bar(ui: &egui::Ui, rect: egui::Rect, fraction: Option<f32>, rtl: bool, theme: &Theme) {
+    let rounding = egui::Rounding::same(rect.height() / 2.0);
+    let painter = ui.painter();
+    painter.rect_filled(rect, rounding, theme.colors.field_border);
+    let (start, end) = match fraction {
+        Some(fraction) => {
+            let fraction = fraction.clamp(0.0, 1.0);
+            if rtl {
+                (1.0 - fraction, 1.0)
+            } else {
+                (0.0, fraction)
+            }
+        }
+        None => {
+            // A quarter of the track, one sweep every 1.5 seconds
+            let phase = (ui.input(|i| i.time) / 1.5).fract() as f32;
+            let position = 1.0 - (2.0 * phase - 1.0).abs();
+            ui.ctx().request_repaint();
+            (position * 0.75, position * 0.75 + 0.25)
+        }
+    };
+    if end > start {
+        let filled = egui::Rect::from_x_y_ranges(
+            rect.left() + start * rect.width()..=rect.left() + end * rect.width(),
+            rect.y_range(),
+        );
+        painter.rect_filled(filled, rounding, theme.colors.default_button);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn percentages() {
+        assert_eq!(
+            ProgressUpdate::parse("50"),
+            Some(ProgressUpdate::Percentage(50.0))
+        );
+        assert_eq!(
+            ProgressUpdate::parse("12.5 %"),
+            Some(ProgressUpdate::Percentage(12.5))
+        );
+        assert_eq!(
+            ProgressUpdate::parse("150"),
+            Some(ProgressUpdate::Percentage(100.0))
+        );
+    }
+
+    #[test]
+    fn status_and_pulsate() {
+        assert_eq!(
+            ProgressUpdate::parse("# status"),
+            Some(ProgressUpdate::Text("status".to_owned()))
+        );
+        assert_eq!(
+            ProgressUpdate::parse("pulsate:true"),
+            Some(ProgressUpdate::Pulsate(true))
+        );
+        assert_eq!(
+            ProgressUpdate::parse("pulsate:false"),
+            Some(ProgressUpdate::Pulsate(false))
+        );
+    }
+
+    #[test]
+    fn junk_is_ignored() {
+        assert_eq!(ProgressUpdate::parse("done"), None);
+        assert_eq!(ProgressUpdate::parse(""), None);
+        assert_eq!(ProgressUpdate::parse("."), None);
+    }
+}
@@ -0,0 +1,672 @@
+use std::fmt;
+
+#[cfg(not(target_arch = "wasm32"))]
+use serde::Deserialize as _;
+
+use egui::epaint::Margin;
+use egui::{Color32, Vec2};
+
+/// Names accepted by [`Theme::preset`]
+pub const PRESETS: [&str; 6] = [
+    "macos-dark",
+    "macos-light",
+    "adwaita",
+    "adwaita-dark",
+    "breeze",
+    "breeze-dark",
+];
+
+/// Names accepted by [`ThemeSet::preset`] besides [`PRESETS`], each with a dark and a light variant
+pub const THEME_SETS: [&str; 3] = ["macos", "adwaita", "breeze"];
+
+/// Whether the dialog is drawn with the dark or the light variant of its theme
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(clap::ValueEnum))]
+pub enum Appearance {
+    /// Follow the system preference, switching live when it changes
+    #[default]
+    Auto,
+    Dark,
+    Light,
+}
+
+impl Appearance {
+    /// The matching egui theme preference
+    pub fn theme_preference(self) -> egui::ThemePreference {
+        match self {
+            Self::Auto => egui::ThemePreference::System,
+            Self::Dark => egui::ThemePreference::Dark,
+            Self::Light => egui::ThemePreference::Light,
+        }
+    }
+}
+
+/// A dark and a light theme, picked by the [`Appearance`]
+#[derive(Clone, Debug, PartialEq)]
+pub struct ThemeSet {
+    pub dark: Theme,
+    pub light: Theme,
+}
+
+impl Default for ThemeSet {
+    fn default() -> Self {
+        Self {
+            dark: Theme::macos_dark(),
+            light: Theme::macos_light(),
+        }
+    }
+}
+
+/// Every color, rounding, margin and font size used to draw the dialog.
+///
+/// Theme files may leave out any field, which then keeps its "macos-dark" value. Colors are
+/// written as "#rgb", "#rrggbb" or "#rrggbbaa".
+#[derive(Clone, Debug, PartialEq, serde::Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Theme {
+    pub name: String,
+    pub colors: ThemeColors,
+    pub rounding: ThemeRounding,
+    pub margins: ThemeMargins,
+    pub font_sizes: ThemeFontSizes,
+}
+
+#[derive(Clone, Debug, PartialEq, serde::Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ThemeColors {
+    /// Window background (the clear color)
+    #[serde(with = "hex_color")]
+    pub background: Color32,
+    /// Heading, body and folder name text
+    #[serde(with = "hex_color")]
+    pub text: Color32,
+    /// Inline error and collision messages, and the field ring while the name is unusable
+    #[serde(with = "hex_color")]
+    pub error: Color32,
+    /// Warning icon of message boxes
+    #[serde(with = "hex_color")]
+    pub warning: Color32,
+    /// Clickable text, e.g. "Use next available name"
+    #[serde(with = "hex_color")]
+    pub link: Color32,
+    #[serde(with = "hex_color")]
+    pub field_fill: Color32,
+    #[serde(with = "hex_color")]
+    pub field_border: Color32,
+    #[serde(with = "hex_color")]
+    pub field_text: Color32,
+    /// Ring around the focused field or button
+    #[serde(with = "hex_color")]
+    pub focus_ring: Color32,
+    /// Selected text while the window is focused
+    #[serde(with = "hex_color")]
+    pub selection: Color32,
+    /// Selected text while the window is in the background
+    #[serde(with = "hex_color")]
+    pub selection_unfocused: Color32,
+    /// Default (Create) button while the window is focused
+    #[serde(with = "hex_color")]
+    pub default_button: Color32,
+    #[serde(with = "hex_color")]
+    pub default_button_text: Color32,
+    /// Default button while the window is in the background or the button is disabled
+    #[serde(with = "hex_color")]
+    pub default_button_inactive: Color32,
+    /// Secondary (Cancel) button
+    #[serde(with = "hex_color")]
+    pub button: Color32,
+    #[serde(with = "hex_color")]
+    pub button_text: Color32,
+    /// Text of a disabled button
+    #[serde(with = "hex_color")]
+    pub disabled_text: Color32,
+}
+
+#[derive(Clone, Debug, PartialEq, serde::Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ThemeRounding {
+    pub dialog: f32,
+    pub field: f32,
+    /// Ring drawn around the field, outside of it
+    pub field_ring: f32,
+    pub button: f32,
+    pub focus_ring: f32,
+}
+
+#[derive(Clone, Debug, PartialEq, serde::Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ThemeMargins {
+    /// Space between the window edges and the content
+    pub dialog: Margin,
+    /// Thickness of the ring drawn around the field
+    pub field_ring: Margin,
+    /// Space between the field ring and the field
+    pub field: Margin,
+    /// Space between the field edges and its text
+    pub field_text: Vec2,
+    /// Indent of the heading, body and messages relative to the field ring
+    pub content_indent: f32,
+    pub button_padding: Vec2,
+    pub default_button_min_size: Vec2,
+    pub field_border_width: f32,
+    pub focus_ring_width: f32,
+}
+
+#[derive(Clone, Debug, PartialEq, serde::Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ThemeFontSizes {
+    pub heading: f32,
+    pub body: f32,
+    pub folder_name: f32,
+    pub text_input: f32,
+    pub button: f32,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::macos_dark()
+    }
+}
+
+impl Default for ThemeColors {
+    fn default() -> Self {
+        Theme::macos_dark().colors
+    }
+}
+
+impl Default for ThemeRounding {
+    fn default() -> Self {
+        Theme::macos_dark().rounding
+    }
+}
+
+impl Default for ThemeMargins {
+    fn default() -> Self {
+        Theme::macos_dark().margins
+    }
+}
+
+impl Default for ThemeFontSizes {
+    fn default() -> Self {
+        Theme::macos_dark().font_sizes
+    }
+}
+
+impl Theme {
+    /// Built-in theme called `name`, one of [`PRESETS`]
+    pub fn preset(name: &str) -> Option<Self> {
+        match name {
+            "macos-dark" => Some(Self::macos_dark()),
+            "macos-light" => Some(Self::macos_light()),
+            "adwaita" => Some(Self::adwaita()),
+            "adwaita-dark" => Some(Self::adwaita_dark()),
+            "breeze" => Some(Self::breeze()),
+            "breeze-dark" => Some(Self::breeze_dark()),
+            _ => None,
+        }
+    }
+
+    /// The look of a macOS dark mode sheet
+    pub fn macos_dark() -> Self {
+        Self {
+            name: "macos-dark".to_owned(),
+            colors: ThemeColors {
+                background: Color32::from_rgb(33, 32, 29),
+                text: Color32::from_rgb(221, 221, 221),
+                error: Color32::from_rgb(255, 105, 97),
+                warning: Color32::from_rgb(255, 214, 10),
+                link: Color32::from_rgb(65, 156, 255),
+                field_fill: Color32::from_rgb(44, 43, 40),
+                field_border: Color32::from_rgb(83, 82, 82),
+                field_text: Color32::from_rgb(221, 221, 221),
+                focus_ring: Color32::from_rgb(56, 100, 138),
+                selection: Color32::from_rgb(71, 98, 135),
+                selection_unfocused: Color32::from_rgb(70, 70, 70),
+                default_button: Color32::from_rgb(48, 98, 212),
+                default_button_text: Color32::from_rgb(221, 221, 221),
+                default_button_inactive: Color32::from_rgb(89, 88, 86),
+                button: Color32::from_rgb(89, 88, 86),
+                button_text: Color32::from_rgb(221, 221, 221),
+                disabled_text: Color32::from_rgb(140, 140, 140),
+            },
+            rounding: ThemeRounding {
+                dialog: 1.0,
+                field: 0.0,
+                field_ring: 3.0,
+                button: 5.0,
+                focus_ring: 6.5,
+            },
+            margins: ThemeMargins {
+                dialog: Margin {
+                    left: 20.5 - 3.5,
+                    right: 20.5 - 3.5,
+                    top: 19.5,
+                    bottom: 20.,
+                },
+                field_ring: Margin::same(3.5),
+                field: Margin::same(0.2),
+                field_text: Vec2::new(3.0, 1.0),
+                content_indent: 3.5,
+                button_padding: Vec2::new(10.0, 2.0),
+                default_button_min_size: Vec2::new(61., 20.),
+                field_border_width: 0.15,
+                focus_ring_width: 2.5,
+            },
+            font_sizes: ThemeFontSizes {
+                heading: 13.0,
+                body: 11.0,
+                folder_name: 11.5,
+                text_input: 13.0,
+                button: 13.5,
+            },
+        }
+    }
+
+    /// The look of a macOS light mode sheet
+    pub fn macos_light() -> Self {
+        let dark = Self::macos_dark();
+        Self {
+            name: "macos-light".to_owned(),
+            colors: ThemeColors {
+                background: Color32::from_rgb(236, 236, 236),
+                text: Color32::from_rgb(38, 38, 38),
+                error: Color32::from_rgb(215, 0, 21),
+                warning: Color32::from_rgb(255, 204, 0),
+                link: Color32::from_rgb(0, 104, 218),
+                field_fill: Color32::from_rgb(255, 255, 255),
+                field_border: Color32::from_rgb(196, 196, 196),
+                field_text: Color32::from_rgb(38, 38, 38),
+                focus_ring: Color32::from_rgb(128, 170, 238),
+                selection: Color32::from_rgb(179, 215, 255),
+                selection_unfocused: Color32::from_rgb(220, 220, 220),
+                default_button: Color32::from_rgb(0, 122, 255),
+                default_button_text: Color32::from_rgb(255, 255, 255),
+                default_button_inactive: Color32::from_rgb(255, 255, 255),
+                button: Color32::from_rgb(255, 255, 255),
+                button_text: Color32::from_rgb(38, 38, 38),
+                disabled_text: Color32::from_rgb(160, 160, 160),
+            },
+            ..dark
+        }
+    }
+
+    /// The look of a GNOME (libadwaita) dialog
+    pub fn adwaita() -> Self {
+        Self {
+            name: "adwaita".to_owned(),
+            colors: ThemeColors {
+                background: Color32::from_rgb(250, 250, 250),
+                text: Color32::from_rgb(46, 52, 54),
+                error: Color32::from_rgb(192, 28, 40),
+                warning: Color32::from_rgb(229, 165, 10),
+                link: Color32::from_rgb(27, 106, 203),
+                field_fill: Color32::from_rgb(235, 235, 235),
+                field_border: Color32::from_rgb(235, 235, 235),
+                field_text: Color32::from_rgb(46, 52, 54),
+                focus_ring: Color32::from_rgb(120, 174, 237),
+                selection: Color32::from_rgb(188, 213, 246),
+                selection_unfocused: Color32::from_rgb(222, 222, 222),
+                default_button: Color32::from_rgb(53, 132, 228),
+                default_button_text: Color32::from_rgb(255, 255, 255),
+                default_button_inactive: Color32::from_rgb(222, 222, 222),
+                button: Color32::from_rgb(222, 222, 222),
+                button_text: Color32::from_rgb(46, 52, 54),
+                disabled_text: Color32::from_rgb(154, 153, 150),
+            },
+            rounding: ThemeRounding {
+                dialog: 12.0,
+                field: 6.0,
+                field_ring: 8.0,
+                button: 6.0,
+                focus_ring: 8.0,
+            },
+            margins: ThemeMargins {
+                dialog: Margin::symmetric(20.0, 18.0),
+                field_ring: Margin::same(2.0),
+                field: Margin::same(0.0),
+                field_text: Vec2::new(8.0, 5.0),
+                content_indent: 2.0,
+                button_padding: Vec2::new(14.0, 5.0),
+                default_button_min_size: Vec2::new(80., 30.),
+                field_border_width: 0.0,
+                focus_ring_width: 2.0,
+            },
+            font_sizes: ThemeFontSizes {
+                heading: 15.0,
+                body: 12.0,
+                folder_name: 12.0,
+                text_input: 13.0,
+                button: 13.0,
+            },
+        }
+    }
+
+    /// The look of a GNOME (libadwaita) dialog in dark mode
+    pub fn adwaita_dark() -> Self {
+        let light = Self::adwaita();
+        Self {
+            name: "adwaita-dark".to_owned(),
+            colors: ThemeColors {
+                background: Color32::from_rgb(54, 54, 54),
+                text: Color32::from_rgb(238, 238, 238),
+                error: Color32::from_rgb(255, 123, 99),
+                warning: Color32::from_rgb(205, 147, 9),
+                link: Color32::from_rgb(120, 174, 237),
+                field_fill: Color32::from_rgb(76, 76, 76),
+                field_border: Color32::from_rgb(76, 76, 76),
+                field_text: Color32::from_rgb(238, 238, 238),
+                focus_ring: Color32::from_rgb(120, 174, 237),
+                selection: Color32::from_rgb(43, 84, 134),
+                selection_unfocused: Color32::from_rgb(80, 80, 80),
+                default_button: Color32::from_rgb(53, 132, 228),
+                default_button_text: Color32::from_rgb(255, 255, 255),
+                default_button_inactive: Color32::from_rgb(80, 80, 80),
+                button: Color32::from_rgb(80, 80, 80),
+                button_text: Color32::from_rgb(238, 238, 238),
+                disabled_text: Color32::from_rgb(130, 130, 130),
+            },
+            ..light
+        }
+    }
+
+    /// The look of a KDE Plasma (Breeze) dialog
+    pub fn breeze() -> Self {
+        Self {
+            name: "breeze".to_owned(),
+            colors: ThemeColors {
+                background: Color32::from_rgb(239, 240, 241),
+                text: Color32::from_rgb(35, 38, 41),
+                error: Color32::from_rgb(218, 68, 83),
+                warning: Color32::from_rgb(246, 116, 0),
+                link: Color32::from_rgb(41, 128, 185),
+                field_fill: Color32::from_rgb(252, 252, 252),
+                field_border: Color32::from_rgb(188, 190, 191),
+                field_text: Color32::from_rgb(35, 38, 41),
+                focus_ring: Color32::from_rgb(61, 174, 233),
+                selection: Color32::from_rgb(61, 174, 233),
+                selection_unfocused: Color32::from_rgb(200, 202, 204),
+                default_button: Color32::from_rgb(61, 174, 233),
+                default_button_text: Color32::from_rgb(252, 252, 252),
+                default_button_inactive: Color32::from_rgb(252, 252, 252),
+                button: Color32::from_rgb(252, 252, 252),
+                button_text: Color32::from_rgb(35, 38, 41),
+                disabled_text: Color32::from_rgb(160, 162, 162),
+            },
+            rounding: ThemeRounding {
+                dialog: 0.0,
+                field: 3.0,
+                field_ring: 4.0,
+                button: 3.0,
+                focus_ring: 4.0,
+            },
+            margins: ThemeMargins {
+                dialog: Margin::symmetric(16.0, 14.0),
+                field_ring: Margin::same(1.0),
+                field: Margin::same(0.0),
+                field_text: Vec2::new(6.0, 4.0),
+                content_indent: 1.0,
+                button_padding: Vec2::new(12.0, 4.0),
+                default_button_min_size: Vec2::new(80., 26.),
+                field_border_width: 1.0,
+                focus_ring_width: 1.0,
+            },
+            font_sizes: ThemeFontSizes {
+                heading: 13.0,
+                body: 10.0,
+                folder_name: 10.0,
+                text_input: 10.0,
+                button: 10.0,
+            },
+        }
+    }
+
+    /// The look of a KDE Plasma (Breeze Dark) dialog
+    pub fn breeze_dark() -> Self {
+        let light = Self::breeze();
+        Self {
+            name: "breeze-dark".to_owned(),
+            colors: ThemeColors {
+                background: Color32::from_rgb(49, 54, 59),
+                text: Color32::from_rgb(252, 252, 252),
+                error: Color32::from_rgb(218, 68, 83),
+                warning: Color32::from_rgb(246, 116, 0),
+                link: Color32::from_rgb(29, 153, 243),
+                field_fill: Color32::from_rgb(35, 38, 41),
+                field_border: Color32::from_rgb(97, 101, 105),
+                field_text: Color32::from_rgb(252, 252, 252),
+                focus_ring: Color32::from_rgb(61, 174, 233),
+                selection: Color32::from_rgb(61, 174, 233),
+                selection_unfocused: Color32::from_rgb(71, 80, 87),
+                default_button: Color32::from_rgb(61, 174, 233),
+                default_button_text: Color32::from_rgb(252, 252, 252),
+                default_button_inactive: Color32::from_rgb(59, 64, 69),
+                button: Color32::from_rgb(59, 64, 69),
+                button_text: Color32::from_rgb(252, 252, 252),
+                disabled_text: Color32::from_rgb(110, 113, 116),
+            },
+            ..light
+        }
+    }
+}
+
+impl ThemeSet {
+    /// The same theme whatever the appearance
+    pub fn single(theme: Theme) -> Self {
+        Self {
+            dark: theme.clone(),
+            light: theme,
+        }
+    }
+
+    /// Built-in set called `name`: one of [`THEME_SETS`], or a single theme of [`PRESETS`]
+    pub fn preset(name: &str) -> Option<Self> {
+        match name {
+            "macos" => Some(Self::default()),
+            "adwaita" => Some(Self {
+                dark: Theme::adwaita_dark(),
+                light: Theme::adwaita(),
+            }),
+            "breeze" => Some(Self {
+                dark: Theme::breeze_dark(),
+                light: Theme::breeze(),
+            }),
+            _ => Theme::preset(name).map(Self::single),
+        }
+    }
+
+    /// The variant drawn in dark or light mode
+    pub fn for_dark_mode(&self, dark_mode: bool) -> &Theme {
+        if dark_mode {
+            &self.dark
+        } else {
+            &self.light
+        }
+    }
+
+    /// Themes from a preset name, or else from a TOML or JSON file (chosen by extension).
+    ///
+    /// A file holds either a single theme, used in both modes, or `dark` and `light` tables
+    /// (objects in JSON); a missing one of the two falls back to the other.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn load(preset_or_path: &str) -> Result<Self, ThemeError> {
+        if let Some(themes) = Self::preset(preset_or_path) {
+            return Ok(themes);
+        }
+
+        let path = std::path::Path::new(preset_or_path);
+        let contents = std::fs::read_to_string(path).map_err(ThemeError::Io)?;
+        let is_json = path
+            .extension()
+            .is_some_and(|extension| extension.eq_ignore_ascii_case("json"));
+        // TOML is read into a JSON value too, so both formats go through the same checks
+        let value: serde_json::Value = if is_json {
+            serde_json::from_str(&contents).map_err(|error| ThemeError::Parse(error.to_string()))?
+        } else {
+            toml::from_str(&contents).map_err(|error| ThemeError::Parse(error.to_string()))?
+        };
+        let parse = |value: serde_json::Value| {
+            Theme::deserialize(value).map_err(|error| ThemeError::Parse(error.to_string()))
+        };
+
+        let (dark, light) = match value {
+            serde_json::Value::Object(mut table)
+                if table.contains_key("dark") || table.contains_key("light") =>
+            {
+                let dark = table.remove("dark").map(parse).transpose()?;
+                let light = table.remove("light").map(parse).transpose()?;
+                if let Some(key) = table.keys().next() {
+                    return Err(ThemeError::Parse(format!(
+                        "unknown field `{key}` next to `dark`/`light`"
+                    )));
+                }
+                (dark, light)
+            }
+            value => (Some(parse(value)?), None),
+        };
+        Ok(match (dark, light) {
+            (Some(dark), Some(light)) => Self { dark, light },
+            (Some(theme), None) | (None, Some(theme)) => Self::single(theme),
+            (None, None) => unreachable!("one of dark and light was found"),
+        })
+    }
+}
+
+/// Why a theme could not be loaded
+#[derive(Debug)]
+pub enum ThemeError {
+    /// Not a preset name, and the file could not be read
+    Io(std::io::Error),
+    /// The file is not a valid theme
+    Parse(String),
+}
+
+impl fmt::Display for ThemeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(error) => write!(
+                f,
+                "{error} (built-in themes are {}, {})",
+                THEME_SETS.join(", "),
+                PRESETS.join(", ")
+            ),
+            Self::Parse(message) => write!(f, "invalid theme: {message}"),
+        }
+    }
+}
+
+impl std::error::Error for ThemeError {}
+
+/// Colors as "#rgb" / "#rrggbb" / "#rrggbbaa" strings in theme files
+mod hex_color {
+    use egui::Color32;
+    use serde::{de::Error as _, Deserialize as _, Deserializer};
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Color32, D::Error> {
+        let hex = String::deserialize(deserializer)?;
+        Color32::from_hex(&hex).map_err(|_| {
+            D::Error::custom(format!("`{hex}` is not a #rgb, #rrggbb or #rrggbbaa color"))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Load `contents` written to a temporary file with the given extension
+    fn load(name: &str, contents: &str) -> Result<ThemeSet, ThemeError> {
+        let path = std::env::temp_dir().join(format!("eframe_test-{}-{name}", std::process::id()));
+        std::fs::write(&path, contents).unwrap();
+        let themes = ThemeSet::load(path.to_str().unwrap());
+        std::fs::remove_file(&path).unwrap();
+        themes
+    }
+
+    #[test]
+    fn minimal_toml_and_json() {
+        let themes = load("minimal.toml", "name = \"mine\"\n").unwrap();
+        assert_eq!(themes.dark.name, "mine");
+        assert_eq!(themes.dark.colors, Theme::macos_dark().colors);
+        assert_eq!(themes.light, themes.dark);
+
+        let themes = load("minimal.json", r#"{"rounding": {}}"#).unwrap();
+        assert_eq!(themes, ThemeSet::single(Theme::default()));
+        assert_eq!(
+            load("empty.toml", "").unwrap(),
+            ThemeSet::single(Theme::default())
+        );
+    }
+
+    #[test]
+    fn dark_and_light_tables() {
+        let themes = load(
+            "both.toml",
+            "[dark]\nname = \"night\"\n\n[light]\nname = \"day\"\n[light.colors]\ntext = \"#000\"\n",
+        )
+        .unwrap();
+        assert_eq!(themes.dark.name, "night");
+        assert_eq!(themes.light.name, "day");
+        assert_eq!(themes.light.colors.text, Color32::BLACK);
+
+        let themes = load("light.json", r#"{"light": {"name": "day"}}"#).unwrap();
+        assert_eq!(themes.dark.name, "day");
+        assert_eq!(themes.light.name, "day");
+    }
+
+    #[test]
+    fn unknown_keys_are_rejected() {
+        for (name, contents) in [
+            ("unknown.toml", "colour = \"#fff\"\n"),
+            ("unknown-color.toml", "[colors]\nbackgroud = \"#fff\"\n"),
+            ("unknown-next.toml", "name = \"x\"\n[dark]\n"),
+            (
+                "unknown.json",
+                r#"{"dark": {"rounding": {"window": 4, "sides": 2}}}"#,
+            ),
+        ] {
+            assert!(
+                matches!(load(name, contents), Err(ThemeError::Parse(_))),
+                "{name}"
+            );
+        }
+    }
+
+    #[test]
+    fn hex_colors() {
+        let colors = |text: &str| {
+            load(
+                "colors.toml",
+                &format!(
+                    "[colors]\ntext = \"{text}\"\nlink = \"#1e90FF\"\nerror = \"#ff000080\"\n"
+                ),
+            )
+            .map(|themes| themes.dark.colors)
+        };
+        let parsed = colors("#fa0").unwrap();
+        assert_eq!(parsed.text, Color32::from_rgb(0xff, 0xaa, 0x00));
+        assert_eq!(parsed.link, Color32::from_rgb(0x1e, 0x90, 0xff));
+        assert_eq!(
+            parsed.error,
+            Color32::from_rgba_unmultiplied(0xff, 0x00, 0x00, 0x80)
+        );
+
+        for bad in ["fa0", "#ggg", "#12345", "#1234567", "", "red"] {
+            let Err(ThemeError::Parse(message)) = colors(bad) else {
+                panic!("{bad:?} is not a color");
+            };
+            assert!(message.contains("is not a #rgb"), "{message}");
+        }
+    }
+
+    #[test]
+    fn missing_file() {
+        assert!(matches!(
+            ThemeSet::load("/nonexistent/theme.toml"),
+            Err(ThemeError::Io(_))
+        ));
+        assert_eq!(
+            ThemeSet::load("breeze").unwrap(),
+            ThemeSet::preset("breeze").unwrap()
+        );
+    }
+}
@@ -0,0 +1,419 @@
+use std::fmt;
+use std::path::Path;
+
+use unicode_normalization::UnicodeNormalization;
+
+use crate::i18n::Messages;
+
+/// Longest file name most file systems accept, counted in UTF-8 bytes
+pub const MAX_NAME_BYTES: usize = 255;
+
+/// Characters that reorder the text around them, which can make "exe.pdf" display as "fdp.exe"
+const BIDI_CONTROLS: [char; 12] = [
+    '\u{061C}', // Arabic letter mark
+    '\u{200E}', // Left-to-right mark
+    '\u{200F}', // Right-to-left mark
+    '\u{202A}', // Left-to-right embedding
+    '\u{202B}', // Right-to-left embedding
+    '\u{202C}', // Pop directional formatting
+    '\u{202D}', // Left-to-right override
+    '\u{202E}', // Right-to-left override
+    '\u{2066}', // Left-to-right isolate
+    '\u{2067}', // Right-to-left isolate
+    '\u{2068}', // First strong isolate
+    '\u{2069}', // Pop directional isolate
+];
+
+/// Invisible characters that make a name look like another one
+const ZERO_WIDTH: [char; 5] = [
+    '\u{200B}', // Zero width space
+    '\u{200C}', // Zero width non-joiner
+    '\u{200D}', // Zero width joiner
+    '\u{2060}', // Word joiner
+    '\u{FEFF}', // Zero width no-break space (byte order mark)
+];
+
+/// Unicode normalization form file names are stored in
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(clap::ValueEnum))]
+pub enum Normalization {
+    /// Composed, "é" is one character: what most Linux and Windows software produces
+    #[default]
+    Nfc,
+    /// Decomposed, "é" is "e" and a combining accent: what HFS+ stored on older macOS
+    Nfd,
+    /// Keep the name as typed
+    None,
+}
+
+impl Normalization {
+    /// `name` in this normalization form
+    pub fn apply(self, name: &str) -> String {
+        match self {
+            Self::Nfc => name.nfc().collect(),
+            Self::Nfd => name.nfd().collect(),
+            Self::None => name.to_owned(),
+        }
+    }
+}
+
+/// Why a folder name can't be used
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NameError {
+    /// Nothing was typed, or only spaces
+    Empty,
+    /// "." or "..", which already mean the current and the parent folder
+    Reserved,
+    /// Contains a path separator, which would point into another folder: `/`, or `\`, which is
+    /// one on Windows and can't be copied there
+    Separator(char),
+    /// Contains a NUL byte, which no file system accepts
+    Nul,
+    /// Contains a bidirectional control character, which can disguise the extension
+    BidiControl(char),
+    /// Longer than [`MAX_NAME_BYTES`]
+    TooLong,
+}
+
+impl fmt::Display for NameError {
+    /// The English message, see [`Messages::name_error`] for other languages
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&Messages::default().name_error(*self))
+    }
+}
+
+/// Check that `name` can be used as the name of a new folder
+pub fn validate_folder_name(name: &str) -> Result<(), NameError> {
+    if name.trim().is_empty() {
+        return Err(NameError::Empty);
+    }
+    if name == "." || name == ".." {
+        return Err(NameError::Reserved);
+    }
+    if let Some(separator) = name.chars().find(|c| matches!(c, '/' | '\\')) {
+        return Err(NameError::Separator(separator));
+    }
+    if name.contains('\0') {
+        return Err(NameError::Nul);
+    }
+    if let Some(control) = name.chars().find(|c| BIDI_CONTROLS.contains(c)) {
+        return Err(NameError::BidiControl(control));
+    }
+    if name.len() > MAX_NAME_BYTES {
+        return Err(NameError::TooLong);
+    }
+    Ok(())
+}
+
+/// First invisible character of `name` that could make it pass for another name, if any.
+///
+/// Joiners are only suspicious next to ASCII: between emoji (👨‍👩‍👧) or in scripts like
+/// Persian and Devanagari they are part of the spelling.
+pub fn zero_width_char(name: &str) -> Option<char> {
+    let chars: Vec<char> = name.chars().collect();
+    chars.iter().enumerate().find_map(|(index, &c)| {
+        if !ZERO_WIDTH.contains(&c) {
+            return None;
+        }
+        if c == '\u{200C}' || c == '\u{200D}' {
+            let before = index.checked_sub(1).and_then(|before| chars.get(before));
+            let after = chars.get(index + 1);
+            let next_to_ascii =
+                before.is_some_and(char::is_ascii) || after.is_some_and(char::is_ascii);
+            if !next_to_ascii && (before.is_some() || after.is_some()) {
+                return None;
+            }
+        }
+        Some(c)
+    })
+}
+
+/// "U+200B" for a character, to name invisible ones
+pub fn code_point(c: char) -> String {
+    format!("U+{:04X}", u32::from(c))
+}
+
+/// Name of the entry in `parent` that `name` would collide with, if any.
+///
+/// The lookup goes through the file system, so case-insensitive (and normalization-insensitive)
+/// mounts report a collision for "Photos" when "photos" exists. The returned name is the entry
+/// as it is actually spelled on disk.
+pub fn existing_entry(parent: &Path, name: &str) -> Option<String> {
+    // An invalid name would join into the parent itself (or elsewhere), which isn't a collision
+    if validate_folder_name(name).is_err() {
+        return None;
+    }
+    parent.join(name).symlink_metadata().ok()?;
+
+    let entries: Vec<String> = std::fs::read_dir(parent)
+        .map(|entries| {
+            entries
+                .filter_map(|entry| entry.ok())
+                .map(|entry| entry.file_name().to_string_lossy().into_owned())
+                .collect()
+        })
+        .unwrap_or_default();
+    let lowercase_name = name.to_lowercase();
+    let spelled_on_disk = entries.iter().find(|entry| *entry == name).or_else(|| {
+        entries
+            .iter()
+            .find(|entry| entry.to_lowercase() == lowercase_name)
+    });
+    Some(spelled_on_disk.cloned().unwrap_or_else(|| name.to_owned()))
+}
+
+/// First of `name`, "`name` 2", "`name` 3", … not taken in `parent`, the way Finder numbers copies.
+///
+/// A name already ending in a number continues from it, so "untitled folder 2" suggests
+/// "untitled folder 3" rather than "untitled folder 2 2". The stem is shortened when the number
+/// would make the name longer than [`MAX_NAME_BYTES`].
+pub fn next_available_name(parent: &Path, name: &str) -> String {
+    if existing_entry(parent, name).is_none() {
+        return name.to_owned();
+    }
+
+    let numbered = name.rsplit_once(' ').and_then(|(stem, suffix)| {
+        let number = suffix
+            .parse::<u64>()
+            .ok()
+            .filter(|&number| !stem.is_empty() && number >= 2 && !suffix.starts_with('0'))?;
+        // "x 18446744073709551615" has no next number, it gets a suffix of its own
+        Some((stem, number.checked_add(1)?))
+    });
+    let (mut stem, mut number) = numbered.unwrap_or((name, 2));
+    loop {
+        let candidate = numbered_name(stem, number);
+        if existing_entry(parent, &candidate).is_none() {
+            return candidate;
+        }
+        match number.checked_add(1) {
+            Some(next) => number = next,
+            None => (stem, number) = (name, 2),
+        }
+    }
+}
+
+/// "`stem` `number`", the stem cut on a character boundary so the name fits in [`MAX_NAME_BYTES`]
+fn numbered_name(stem: &str, number: u64) -> String {
+    let suffix = format!(" {number}");
+    let mut end = stem.len().min(MAX_NAME_BYTES - suffix.len());
+    while !stem.is_char_boundary(end) {
+        end -= 1;
+    }
+    format!("{}{suffix}", &stem[..end])
+}
+
+/// Split a file name into its stem and extension, the extension keeping its dot: "report.pdf"
+/// gives ("report", Some(".pdf")). Only the last extension counts ("archive.tar" + ".gz"), and
+/// a leading dot starts a hidden name rather than an extension (".bashrc" has none).
+pub fn split_extension(name: &str) -> (&str, Option<&str>) {
+    match name.rfind('.') {
+        Some(dot) if dot > 0 && dot + 1 < name.len() => (&name[..dot], Some(&name[dot..])),
+        _ => (name, None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+
+    /// An empty folder of its own for each test, removed when dropped
+    struct TempDir(PathBuf);
+
+    impl TempDir {
+        fn new(name: &str) -> Self {
+            let path =
+                std::env::temp_dir().join(format!("eframe_test-{}-{name}", std::process::id()));
+            std::fs::remove_dir_all(&path).ok();
+            std::fs::create_dir_all(&path).unwrap();
+            Self(path)
+        }
+
+        fn with_entries(self, names: &[&str]) -> Self {
+            for name in names {
+                std::fs::create_dir(self.0.join(name)).unwrap();
+            }
+            self
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            std::fs::remove_dir_all(&self.0).ok();
+        }
+    }
+
+    #[test]
+    fn normalization_composes_or_decomposes() {
+        let decomposed = "e\u{301}te\u{301}";
+        let composed = "\u{e9}t\u{e9}";
+        assert_eq!(Normalization::Nfc.apply(decomposed), composed);
+        assert_eq!(Normalization::Nfd.apply(composed), decomposed);
+        assert_eq!(Normalization::None.apply(decomposed), decomposed);
+        // The same name is longer on disk in NFD
+        assert_eq!(Normalization::Nfc.apply(composed).len(), 5);
+        assert_eq!(Normalization::Nfd.apply(composed).len(), 7);
+    }
+
+    #[test]
+    fn invalid_names_and_their_messages() {
+        let error = |name: &str| {
+            let error = validate_folder_name(name).unwrap_err();
+            (error, error.to_string())
+        };
+        assert_eq!(
+            error(""),
+            (NameError::Empty, "Enter a name for the folder.".to_owned())
+        );
+        assert_eq!(
+            error(" \t "),
+            (NameError::Empty, "Enter a name for the folder.".to_owned())
+        );
+        let reserved = "The names “.” and “..” are reserved.".to_owned();
+        assert_eq!(error("."), (NameError::Reserved, reserved.clone()));
+        assert_eq!(error(".."), (NameError::Reserved, reserved));
+        assert_eq!(
+            error("a/b"),
+            (
+                NameError::Separator('/'),
+                "The name can't contain “/”.".to_owned()
+            )
+        );
+        assert_eq!(
+            error("a\\b"),
+            (
+                NameError::Separator('\\'),
+                "The name can't contain “\\”.".to_owned()
+            )
+        );
+        assert_eq!(
+            error("a\0b"),
+            (
+                NameError::Nul,
+                "The name can't contain a null character.".to_owned()
+            )
+        );
+        assert_eq!(
+            error(&"a".repeat(256)),
+            (
+                NameError::TooLong,
+                "The name can't be longer than 255 bytes.".to_owned()
+            )
+        );
+
+        // Only the whole name is reserved, and spaces around a name are kept
+        for name in ["...", ".hidden", "a.", " a "] {
+            assert_eq!(validate_folder_name(name), Ok(()), "{name:?}");
+        }
+    }
+
+    #[test]
+    fn names_are_limited_to_255_bytes() {
+        assert_eq!(validate_folder_name(&"a".repeat(255)), Ok(()));
+        assert_eq!(
+            validate_folder_name(&"a".repeat(256)),
+            Err(NameError::TooLong)
+        );
+        // Bytes, not characters: 128 characters of two bytes are too many
+        assert_eq!(
+            validate_folder_name(&format!("{}a", "é".repeat(127))),
+            Ok(())
+        );
+        assert_eq!(
+            validate_folder_name(&"é".repeat(128)),
+            Err(NameError::TooLong)
+        );
+        let accented = "é".repeat(100);
+        assert_eq!(
+            validate_folder_name(&Normalization::Nfc.apply(&accented)),
+            Ok(())
+        );
+        assert_eq!(
+            validate_folder_name(&Normalization::Nfd.apply(&accented)),
+            Err(NameError::TooLong)
+        );
+    }
+
+    #[test]
+    fn bidi_controls_are_rejected() {
+        assert_eq!(
+            validate_folder_name("invoice\u{202E}fdp.exe"),
+            Err(NameError::BidiControl('\u{202E}'))
+        );
+        assert_eq!(
+            validate_folder_name("\u{2066}report"),
+            Err(NameError::BidiControl('\u{2066}'))
+        );
+        assert_eq!(validate_folder_name("تقرير"), Ok(()));
+    }
+
+    #[test]
+    fn zero_width_characters_are_reported() {
+        assert_eq!(zero_width_char("pay\u{200B}pal"), Some('\u{200B}'));
+        assert_eq!(code_point('\u{200B}'), "U+200B");
+        assert_eq!(zero_width_char("paypal"), None);
+        // Joiners between emoji are part of the family emoji
+        assert_eq!(zero_width_char("👨\u{200D}👩\u{200D}👧"), None);
+        assert_eq!(zero_width_char("a\u{200D}b"), Some('\u{200D}'));
+    }
+
+    #[test]
+    fn next_available_name_keeps_a_free_name() {
+        let dir = TempDir::new("free").with_entries(&["other"]);
+        assert_eq!(
+            next_available_name(&dir.0, "untitled folder"),
+            "untitled folder"
+        );
+    }
+
+    #[test]
+    fn next_available_name_numbers_a_taken_name() {
+        let dir = TempDir::new("taken").with_entries(&["untitled folder", "untitled folder 2"]);
+        assert_eq!(
+            next_available_name(&dir.0, "untitled folder"),
+            "untitled folder 3"
+        );
+    }
+
+    #[test]
+    fn next_available_name_continues_a_number() {
+        let dir = TempDir::new("continues").with_entries(&["x 2"]);
+        assert_eq!(next_available_name(&dir.0, "x 2"), "x 3");
+    }
+
+    #[test]
+    fn next_available_name_goes_through_the_file_system_case() {
+        let dir = TempDir::new("case").with_entries(&["Photos"]);
+        // Only case-insensitive volumes, like the macOS and Windows defaults, have "photos" taken
+        let case_insensitive = dir.0.join("PHOTOS").exists();
+        let expected = if case_insensitive {
+            "photos 2"
+        } else {
+            "photos"
+        };
+        assert_eq!(next_available_name(&dir.0, "photos"), expected);
+        if case_insensitive {
+            assert_eq!(existing_entry(&dir.0, "photos").as_deref(), Some("Photos"));
+        }
+    }
+
+    #[test]
+    fn next_available_name_survives_the_largest_number() {
+        let name = format!("x {}", u64::MAX);
+        let dir = TempDir::new("overflow").with_entries(&[&name]);
+        assert_eq!(next_available_name(&dir.0, &name), format!("{name} 2"));
+    }
+
+    #[test]
+    fn next_available_name_fits_the_byte_limit() {
+        // 127 two-byte characters and an ASCII one, 255 bytes
+        let name = format!("{}a", "é".repeat(127));
+        let dir = TempDir::new("long").with_entries(&[&name]);
+        let next = next_available_name(&dir.0, &name);
+        assert_eq!(next, format!("{} 2", "é".repeat(126)));
+        assert!(next.len() <= MAX_NAME_BYTES);
+        assert_eq!(validate_folder_name(&next), Ok(()));
+    }
+}
@@ -0,0 +1,398 @@
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+
+use clap::{ArgGroup, Parser};
+
+use crate::file_chooser::FileFilter;
+use crate::i18n::{detect_locale, Messages};
+use crate::theme::Appearance;
+use crate::{DialogKind, DialogOutcome, MessageKind};
+
+/// Options that pick a zenity dialog, any of them as the first argument switches to zenity
+/// compatible mode
+const DIALOG_FLAGS: [&str; 8] = [
+    "--entry",
+    "--password",
+    "--question",
+    "--info",
+    "--warning",
+    "--error",
+    "--file-selection",
+    "--progress",
+];
+
+/// Exit code of zenity when the dialog could not be shown
+pub const EXIT_ERROR: i32 = -1;
+/// Exit code of zenity when the timeout was reached
+pub const EXIT_TIMEOUT: i32 = 5;
+
+/// The subset of zenity's command line this binary accepts, so scripts can swap it in
+#[derive(Debug, Parser)]
+#[command(
+    name = "zenity",
+    version,
+    about = "Display dialogs from shell scripts, compatible with zenity",
+    group(ArgGroup::new("dialog").required(true).args(["entry", "password", "question", "info", "warning", "error", "file_selection", "progress"]))
+)]
+pub struct ZenityCli {
+    /// Display a text entry dialog, the text is printed on stdout
+    #[arg(long)]
+    pub entry: bool,
+
+    /// Display a password dialog, the password is printed on stdout
+    #[arg(long)]
+    pub password: bool,
+
+    /// Display a question dialog, answered with the exit code
+    #[arg(long)]
+    pub question: bool,
+
+    /// Display an info dialog
+    #[arg(long)]
+    pub info: bool,
+
+    /// Display a warning dialog
+    #[arg(long)]
+    pub warning: bool,
+
+    /// Display an error dialog
+    #[arg(long)]
+    pub error: bool,
+
+    /// Display a file selection dialog, the chosen paths are printed on stdout
+    #[arg(long)]
+    pub file_selection: bool,
+
+    /// Display a progress dialog, fed on stdin with percentages and "# status" lines
+    #[arg(long)]
+    pub progress: bool,
+
+    /// Percentage the progress starts at
+    #[arg(
+        long,
+        value_name = "PERCENTAGE",
+        requires = "progress",
+        default_value_t = 0.0
+    )]
+    pub percentage: f32,
+
+    /// Slide the progress bar back and forth, for jobs of unknown length
+    #[arg(long, requires = "progress")]
+    pub pulsate: bool,
+
+    /// Close the progress dialog once 100% is reached
+    #[arg(long, requires = "progress")]
+    pub auto_close: bool,
+
+    /// Send SIGTERM to the parent process (the script) when the progress is cancelled
+    #[arg(long, requires = "progress")]
+    pub auto_kill: bool,
+
+    /// Leave out the Cancel button of the progress dialog
+    #[arg(long, requires = "progress")]
+    pub no_cancel: bool,
+
+    /// Select a folder rather than a file
+    #[arg(long, requires = "file_selection")]
+    pub directory: bool,
+
+    /// Choose where to save a file rather than an existing one
+    #[arg(long, requires = "file_selection")]
+    pub save: bool,
+
+    /// Let several files be selected, Ctrl/Cmd+click adding to the selection
+    #[arg(long, requires = "file_selection")]
+    pub multiple: bool,
+
+    /// Printed between the selected files
+    #[arg(
+        long,
+        value_name = "SEPARATOR",
+        requires = "file_selection",
+        default_value = "|"
+    )]
+    pub separator: String,
+
+    /// Ask before replacing an existing file, which is always done
+    #[arg(long, requires = "save")]
+    pub confirm_overwrite: bool,
+
+    /// Files listed, as NAME | PATTERN1 PATTERN2 (repeatable, the first one applies)
+    #[arg(
+        long = "file-filter",
+        value_name = "NAME | PATTERN1 PATTERN2",
+        requires = "file_selection"
+    )]
+    pub file_filters: Vec<String>,
+
+    /// Dialog heading, also used as the window title
+    #[arg(long)]
+    pub title: Option<String>,
+
+    /// Question or message of the dialog, with \n for line breaks
+    #[arg(long)]
+    pub text: Option<String>,
+
+    /// Text the entry field starts with
+    #[arg(long, value_name = "TEXT")]
+    pub entry_text: Option<String>,
+
+    /// Path the file selection starts with
+    #[arg(long, value_name = "PATH")]
+    pub filename: Option<PathBuf>,
+
+    /// Close the dialog after this many seconds, exiting with 5
+    #[arg(long, value_name = "SECONDS")]
+    pub timeout: Option<u64>,
+
+    /// Label of the OK (or Yes) button
+    #[arg(long, value_name = "TEXT")]
+    pub ok_label: Option<String>,
+
+    /// Label of the Cancel (or No) button
+    #[arg(long, value_name = "TEXT")]
+    pub cancel_label: Option<String>,
+
+    /// Window width
+    #[arg(long, value_name = "WIDTH")]
+    pub width: Option<f32>,
+
+    /// Window height
+    #[arg(long, value_name = "HEIGHT")]
+    pub height: Option<f32>,
+
+    /// Draw the dark or light variant of the theme, or follow the system preference
+    #[arg(long, value_enum, env = "EFRAME_TEST_APPEARANCE", default_value_t = Appearance::Auto, hide = true)]
+    pub appearance: Appearance,
+}
+
+impl ZenityCli {
+    /// The dialog to show, or why it can't be shown
+    pub fn kind(&self) -> Result<DialogKind, String> {
+        Ok(if self.entry {
+            DialogKind::Entry
+        } else if self.password {
+            DialogKind::Password
+        } else if self.question {
+            DialogKind::Message(MessageKind::Question)
+        } else if self.info {
+            DialogKind::Message(MessageKind::Info)
+        } else if self.warning {
+            DialogKind::Message(MessageKind::Warning)
+        } else if self.error {
+            DialogKind::Message(MessageKind::Error)
+        } else if self.progress {
+            DialogKind::Progress
+        } else if self.directory {
+            DialogKind::OpenFolder
+        } else if self.save {
+            DialogKind::SaveFile
+        } else {
+            DialogKind::OpenFile
+        })
+    }
+
+    /// Messages in the language of the environment, zenity has no option for it
+    pub fn messages(&self) -> Messages {
+        Messages::new(&detect_locale())
+    }
+
+    /// --title, or the default title of the dialog
+    pub fn title(&self, kind: DialogKind, messages: &Messages) -> String {
+        self.title.clone().unwrap_or_else(|| {
+            messages
+                .get(&format!("title_{}", catalog_name(kind)))
+                .to_owned()
+        })
+    }
+
+    /// --text with its escapes expanded, or the default text of the dialog
+    pub fn text(&self, kind: DialogKind, messages: &Messages) -> String {
+        match &self.text {
+            Some(text) => unescape(text),
+            None => messages
+                .get(&format!("text_{}", catalog_name(kind)))
+                .to_owned(),
+        }
+    }
+
+    /// The --file-filter options, in order
+    pub fn file_filters(&self) -> Vec<FileFilter> {
+        self.file_filters
+            .iter()
+            .map(|spec| FileFilter::parse(spec))
+            .collect()
+    }
+
+    /// Text the field starts with: --entry-text, or --filename for folders
+    pub fn entry_text(&self) -> String {
+        match (&self.entry_text, &self.filename) {
+            (Some(text), _) => text.clone(),
+            (None, Some(path)) => path.display().to_string(),
+            (None, None) => String::new(),
+        }
+    }
+}
+
+/// Whether the arguments (program name first) ask for zenity compatible mode: the binary is
+/// called `zenity`, e.g. through a symlink, or its first argument is one of zenity's dialog
+/// options. Later arguments may be values, like `--text --info`, which stay ours.
+pub fn is_invocation(args: &[OsString]) -> bool {
+    let called_zenity = args
+        .first()
+        .and_then(|program| Path::new(program).file_stem())
+        .is_some_and(|stem| stem == "zenity");
+    called_zenity
+        || args
+            .get(1)
+            .is_some_and(|arg| DIALOG_FLAGS.iter().any(|flag| arg == flag))
+}
+
+/// Exit code zenity uses for an outcome: closing the window counts as Cancel
+pub fn exit_code(outcome: &DialogOutcome) -> i32 {
+    match outcome {
+        DialogOutcome::Confirmed(_) => 0,
+        DialogOutcome::Cancelled | DialogOutcome::Dismissed => 1,
+        DialogOutcome::Error(_) => EXIT_ERROR,
+        DialogOutcome::TimedOut => EXIT_TIMEOUT,
+    }
+}
+
+/// Ask the parent process, the script feeding a progress dialog, to stop: SIGTERM lets its
+/// traps clean up
+pub fn terminate_parent() {
+    #[cfg(unix)]
+    {
+        let parent = std::os::unix::process::parent_id();
+        log::info!("Sending SIGTERM to the parent process {parent}");
+        // SAFETY: kill has no memory safety requirements, a stale pid only fails with ESRCH
+        let result = unsafe { libc::kill(parent as libc::pid_t, libc::SIGTERM) };
+        if result != 0 {
+            log::error!(
+                "Failed to signal the parent process: {}",
+                std::io::Error::last_os_error()
+            );
+        }
+    }
+    #[cfg(not(unix))]
+    log::warn!("--auto-kill is only supported on Unix");
+}
+
+/// Suffix of the catalog keys with the default title and text of a dialog
+fn catalog_name(kind: DialogKind) -> &'static str {
+    match kind {
+        DialogKind::NewFolder => "new_folder",
+        DialogKind::Entry => "entry",
+        DialogKind::Directory | DialogKind::OpenFolder => "directory",
+        DialogKind::Rename => "rename",
+        DialogKind::Password => "password",
+        DialogKind::Login => "login",
+        DialogKind::Progress => "progress",
+        DialogKind::List => "list",
+        DialogKind::OpenFile => "open",
+        DialogKind::SaveFile => "save",
+        DialogKind::Message(kind) => kind.name(),
+    }
+}
+
+/// Expand the backslash escapes zenity accepts in --text: \n, \t and \\
+fn unescape(text: &str) -> String {
+    let mut unescaped = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            unescaped.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => unescaped.push('\n'),
+            Some('t') => unescaped.push('\t'),
+            Some('\\') => unescaped.push('\\'),
+            Some(other) => {
+                unescaped.push('\\');
+                unescaped.push(other);
+            }
+            None => unescaped.push('\\'),
+        }
+    }
+    unescaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &[&str]) -> Vec<OsString> {
+        args.iter().map(OsString::from).collect()
+    }
+
+    #[test]
+    fn called_as_zenity() {
+        assert!(is_invocation(&args(&["/usr/local/bin/zenity", "--entry"])));
+        assert!(is_invocation(&args(&["zenity"])));
+        assert!(is_invocation(&args(&["zenity", "--text", "hi", "--info"])));
+    }
+
+    #[test]
+    fn dialog_option_first() {
+        assert!(is_invocation(&args(&[
+            "eframe_test",
+            "--entry",
+            "--text",
+            "Name"
+        ])));
+        assert!(is_invocation(&args(&[
+            "eframe_test",
+            "--file-selection",
+            "--directory"
+        ])));
+    }
+
+    #[test]
+    fn directory_selection_chooses_a_folder() {
+        let cli = ZenityCli::try_parse_from(["zenity", "--file-selection", "--directory"]).unwrap();
+        assert_eq!(cli.kind(), Ok(DialogKind::OpenFolder));
+    }
+
+    #[test]
+    fn dialog_option_as_a_value() {
+        assert!(!is_invocation(&args(&["eframe_test", "--text", "--info"])));
+        assert!(!is_invocation(&args(&[
+            "eframe_test",
+            "--default-name",
+            "--error"
+        ])));
+        assert!(!is_invocation(&args(&["eframe_test", "--parent", "/tmp"])));
+        assert!(!is_invocation(&args(&["eframe_test"])));
+    }
+
+    #[test]
+    fn every_dialog_has_a_default_title_and_text() {
+        let messages = Messages::new("en");
+        let kinds = [
+            DialogKind::NewFolder,
+            DialogKind::Entry,
+            DialogKind::Directory,
+            DialogKind::Rename,
+            DialogKind::Message(MessageKind::Question),
+            DialogKind::Message(MessageKind::Info),
+            DialogKind::Message(MessageKind::Warning),
+            DialogKind::Message(MessageKind::Error),
+            DialogKind::Password,
+            DialogKind::Login,
+            DialogKind::Progress,
+            DialogKind::List,
+            DialogKind::OpenFile,
+            DialogKind::OpenFolder,
+            DialogKind::SaveFile,
+        ];
+        for kind in kinds {
+            for key in [
+                format!("title_{}", catalog_name(kind)),
+                format!("text_{}", catalog_name(kind)),
+            ] {
+                assert_ne!(messages.get(&key), key, "{kind:?}");
+            }
+        }
+    }
+}